@@ -18,15 +18,15 @@ use crate::grpc::qdrant::value::Kind;
 use crate::grpc::qdrant::vectors::VectorsOptions;
 use crate::grpc::qdrant::with_payload_selector::SelectorOptions;
 use crate::grpc::qdrant::{
-    with_vectors_selector, CollectionDescription, CollectionOperationResponse, Condition, Distance,
-    FieldCondition, Filter, GeoBoundingBox, GeoPoint, GeoPolygon, GeoRadius, HasIdCondition,
-    HealthCheckReply, HnswConfigDiff, IsEmptyCondition, IsNullCondition, ListCollectionsResponse,
-    ListValue, Match, NamedVectors, NestedCondition, PayloadExcludeSelector,
-    PayloadIncludeSelector, PayloadIndexParams, PayloadSchemaInfo, PayloadSchemaType, PointId,
-    ProductQuantization, QuantizationConfig, QuantizationSearchParams, QuantizationType, Range,
-    RepeatedIntegers, RepeatedStrings, ScalarQuantization, ScoredPoint, SearchParams, Struct,
-    TextIndexParams, TokenizerType, Value, ValuesCount, Vector, Vectors, VectorsSelector,
-    WithPayloadSelector, WithVectorsSelector,
+    with_vectors_selector, CollectionDescription, CollectionOperationResponse, Condition, Datatype,
+    Distance, FieldCondition, Filter, GeoBoundingBox, GeoPoint, GeoPolygon, GeoRadius,
+    HasIdCondition, HealthCheckReply, HnswConfigDiff, IsEmptyCondition, IsNullCondition,
+    ListCollectionsResponse, ListValue, Match, NamedVectors, NestedCondition,
+    PayloadExcludeSelector, PayloadIncludeSelector, PayloadIndexParams, PayloadSchemaInfo,
+    PayloadSchemaType, PointId, ProductQuantization, QuantizationConfig, QuantizationSearchParams,
+    QuantizationType, Range, RepeatedIntegers, RepeatedStrings, ScalarQuantization, ScoredPoint,
+    SearchParams, Struct, TextIndexParams, TokenizerType, Value, ValuesCount, Vector, Vectors,
+    VectorsSelector, WithPayloadSelector, WithVectorsSelector,
 };
 
 pub fn payload_to_proto(payload: segment::types::Payload) -> HashMap<String, Value> {
@@ -366,6 +366,7 @@ impl From<SearchParams> for segment::types::SearchParams {
             exact: params.exact.unwrap_or(false),
             quantization: params.quantization.map(|q| q.into()),
             indexed_only: params.indexed_only.unwrap_or(false),
+            normalize_score: params.normalize_score.unwrap_or(false),
         }
     }
 }
@@ -377,6 +378,7 @@ impl From<segment::types::SearchParams> for SearchParams {
             exact: Some(params.exact),
             quantization: params.quantization.map(|q| q.into()),
             indexed_only: Some(params.indexed_only),
+            normalize_score: Some(params.normalize_score),
         }
     }
 }
@@ -409,28 +411,38 @@ impl From<HashMap<String, Vec<VectorElementType>>> for NamedVectors {
     }
 }
 
-impl From<segment::data_types::vectors::VectorStruct> for Vectors {
-    fn from(vector_struct: segment::data_types::vectors::VectorStruct) -> Self {
+impl TryFrom<segment::data_types::vectors::VectorStruct> for Vectors {
+    type Error = Status;
+
+    fn try_from(
+        vector_struct: segment::data_types::vectors::VectorStruct,
+    ) -> Result<Self, Self::Error> {
         match vector_struct {
-            segment::data_types::vectors::VectorStruct::Single(vector) => Self {
+            segment::data_types::vectors::VectorStruct::Single(vector) => Ok(Self {
                 vectors_options: Some(VectorsOptions::Vector(vector.into())),
-            },
-            segment::data_types::vectors::VectorStruct::Multi(vectors) => Self {
+            }),
+            segment::data_types::vectors::VectorStruct::Multi(vectors) => Ok(Self {
                 vectors_options: Some(VectorsOptions::Vectors(vectors.into())),
-            },
+            }),
+            segment::data_types::vectors::VectorStruct::Sparse(_)
+            | segment::data_types::vectors::VectorStruct::MultiSparse(_) => Err(
+                Status::invalid_argument("Sparse vectors are not supported by gRPC API yet"),
+            ),
         }
     }
 }
 
-impl From<segment::types::ScoredPoint> for ScoredPoint {
-    fn from(point: segment::types::ScoredPoint) -> Self {
-        Self {
+impl TryFrom<segment::types::ScoredPoint> for ScoredPoint {
+    type Error = Status;
+
+    fn try_from(point: segment::types::ScoredPoint) -> Result<Self, Self::Error> {
+        Ok(Self {
             id: Some(point.id.into()),
             payload: point.payload.map(payload_to_proto).unwrap_or_default(),
             score: point.score,
             version: point.version,
-            vectors: point.vector.map(|v| v.into()),
-        }
+            vectors: point.vector.map(|v| v.try_into()).transpose()?,
+        })
     }
 }
 
@@ -1139,6 +1151,9 @@ impl TryFrom<Distance> for segment::types::Distance {
             Distance::Cosine => segment::types::Distance::Cosine,
             Distance::Euclid => segment::types::Distance::Euclid,
             Distance::Dot => segment::types::Distance::Dot,
+            Distance::L1 => segment::types::Distance::L1,
+            Distance::Hamming => segment::types::Distance::Hamming,
+            Distance::Chebyshev => segment::types::Distance::Chebyshev,
         })
     }
 }
@@ -1151,3 +1166,30 @@ pub fn from_grpc_dist(dist: i32) -> Result<segment::types::Distance, Status> {
         Some(grpc_distance) => Ok(grpc_distance.try_into()?),
     }
 }
+
+impl From<Datatype> for segment::types::VectorStorageDatatype {
+    fn from(value: Datatype) -> Self {
+        match value {
+            Datatype::Float32 => segment::types::VectorStorageDatatype::Float32,
+            Datatype::Float16 => segment::types::VectorStorageDatatype::Float16,
+        }
+    }
+}
+
+impl From<segment::types::VectorStorageDatatype> for Datatype {
+    fn from(value: segment::types::VectorStorageDatatype) -> Self {
+        match value {
+            segment::types::VectorStorageDatatype::Float32 => Datatype::Float32,
+            segment::types::VectorStorageDatatype::Float16 => Datatype::Float16,
+        }
+    }
+}
+
+pub fn from_grpc_datatype(datatype: i32) -> Result<segment::types::VectorStorageDatatype, Status> {
+    match Datatype::from_i32(datatype) {
+        None => Err(Status::invalid_argument(format!(
+            "Malformed datatype parameter, unexpected value: {datatype}"
+        ))),
+        Some(grpc_datatype) => Ok(grpc_datatype.into()),
+    }
+}
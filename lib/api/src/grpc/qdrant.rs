@@ -21,6 +21,9 @@ pub struct VectorParams {
     /// If true - serve vectors from disk. If set to false, the vectors will be loaded in RAM.
     #[prost(bool, optional, tag = "5")]
     pub on_disk: ::core::option::Option<bool>,
+    /// Element type to store vectors with in mmap storage. If omitted - Float32 is used
+    #[prost(enumeration = "Datatype", optional, tag = "6")]
+    pub datatype: ::core::option::Option<i32>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -859,6 +862,9 @@ pub enum Distance {
     Cosine = 1,
     Euclid = 2,
     Dot = 3,
+    L1 = 4,
+    Hamming = 5,
+    Chebyshev = 6,
 }
 impl Distance {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -871,6 +877,9 @@ impl Distance {
             Distance::Cosine => "Cosine",
             Distance::Euclid => "Euclid",
             Distance::Dot => "Dot",
+            Distance::L1 => "L1",
+            Distance::Hamming => "Hamming",
+            Distance::Chebyshev => "Chebyshev",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -880,6 +889,36 @@ impl Distance {
             "Cosine" => Some(Self::Cosine),
             "Euclid" => Some(Self::Euclid),
             "Dot" => Some(Self::Dot),
+            "L1" => Some(Self::L1),
+            "Hamming" => Some(Self::Hamming),
+            "Chebyshev" => Some(Self::Chebyshev),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Datatype {
+    Float32 = 0,
+    Float16 = 1,
+}
+impl Datatype {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Datatype::Float32 => "Float32",
+            Datatype::Float16 => "Float16",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Float32" => Some(Self::Float32),
+            "Float16" => Some(Self::Float16),
             _ => None,
         }
     }
@@ -3065,6 +3104,11 @@ pub struct SearchParams {
     /// guarantee that all uploaded vectors will be included in search results
     #[prost(bool, optional, tag = "4")]
     pub indexed_only: ::core::option::Option<bool>,
+    ///
+    /// If enabled, returned scores are mapped into \[0, 1\] range, larger is better.
+    /// Score threshold still applies to raw scores.
+    #[prost(bool, optional, tag = "5")]
+    pub normalize_score: ::core::option::Option<bool>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
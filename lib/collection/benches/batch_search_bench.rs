@@ -33,7 +33,7 @@ fn create_rnd_batch() -> CollectionUpdateOperations {
         let vectors = only_default_vector(&vector);
         let point = PointStruct {
             id: (i as u64).into(),
-            vector: vectors.into(),
+            vector: vectors.try_into().unwrap(),
             payload: Some(Payload(payload_map)),
         };
         points.push(point);
@@ -63,6 +63,7 @@ fn batch_search_bench(c: &mut Criterion) {
             hnsw_config: None,
             quantization_config: None,
             on_disk: None,
+            datatype: None,
         }
         .into(),
         ..CollectionParams::empty()
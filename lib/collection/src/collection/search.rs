@@ -338,6 +338,13 @@ impl Collection {
                         top_res.clear()
                     }
                 }
+                // Shard level results stay raw, to be merged by the requesting peer
+                let normalize_score = request.params.is_some_and(|params| params.normalize_score);
+                if shard_selection.is_none() && normalize_score {
+                    for scored_point in &mut top_res {
+                        scored_point.score = distance.normalize_score(scored_point.score);
+                    }
+                }
                 Ok(top_res)
             })
             .collect::<CollectionResult<Vec<_>>>()?;
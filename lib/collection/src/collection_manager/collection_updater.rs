@@ -97,17 +97,17 @@ mod tests {
         let points = vec![
             PointStruct {
                 id: 11.into(),
-                vector: vec11.into(),
+                vector: vec11.try_into().unwrap(),
                 payload: None,
             },
             PointStruct {
                 id: 12.into(),
-                vector: vec12.into(),
+                vector: vec12.try_into().unwrap(),
                 payload: None,
             },
             PointStruct {
                 id: 13.into(),
-                vector: vec13.into(),
+                vector: vec13.try_into().unwrap(),
                 payload: Some(json!({ "color": "red" }).into()),
             },
             PointStruct {
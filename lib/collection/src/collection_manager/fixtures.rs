@@ -218,6 +218,7 @@ pub(crate) fn get_merge_optimizer(
                 hnsw_config: None,
                 quantization_config: None,
                 on_disk: None,
+                datatype: None,
             }),
             ..CollectionParams::empty()
         },
@@ -246,6 +247,7 @@ pub(crate) fn get_indexing_optimizer(
                 hnsw_config: None,
                 quantization_config: None,
                 on_disk: None,
+                datatype: None,
             }),
             ..CollectionParams::empty()
         },
@@ -1322,6 +1322,10 @@ mod tests {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
                 (
@@ -1332,6 +1336,10 @@ mod tests {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
             ]),
@@ -286,6 +286,7 @@ mod tests {
                 hnsw_config: None,
                 quantization_config: None,
                 on_disk: None,
+                datatype: None,
             }),
             ..CollectionParams::empty()
         };
@@ -418,6 +419,7 @@ mod tests {
                         hnsw_config: Some(hnsw_config_vector1),
                         quantization_config: None,
                         on_disk: None,
+                        datatype: None,
                     },
                 ),
                 (
@@ -428,6 +430,7 @@ mod tests {
                         hnsw_config: None,
                         quantization_config: None,
                         on_disk: None,
+                        datatype: None,
                     },
                 ),
             ])),
@@ -583,6 +586,7 @@ mod tests {
                         hnsw_config: None,
                         quantization_config: Some(quantization_config_vector1.clone()),
                         on_disk: None,
+                        datatype: None,
                     },
                 ),
                 (
@@ -593,6 +597,7 @@ mod tests {
                         hnsw_config: None,
                         quantization_config: None,
                         on_disk: None,
+                        datatype: None,
                     },
                 ),
             ])),
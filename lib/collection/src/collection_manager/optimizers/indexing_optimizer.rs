@@ -302,6 +302,7 @@ mod tests {
                         hnsw_config: None,
                         quantization_config: None,
                         on_disk: None,
+                        datatype: None,
                     },
                 )
             })
@@ -417,6 +418,7 @@ mod tests {
                     hnsw_config: None,
                     quantization_config: None,
                     on_disk: None,
+                    datatype: None,
                 }),
                 ..CollectionParams::empty()
             },
@@ -310,6 +310,7 @@ mod tests {
                     hnsw_config: None,
                     quantization_config: None,
                     on_disk: None,
+                    datatype: None,
                 }),
                 ..CollectionParams::empty()
             },
@@ -400,6 +401,7 @@ mod tests {
                         hnsw_config: None,
                         quantization_config: None,
                         on_disk: None,
+                        datatype: None,
                     },
                 ),
                 (
@@ -410,6 +412,7 @@ mod tests {
                         hnsw_config: None,
                         quantization_config: None,
                         on_disk: None,
+                        datatype: None,
                     },
                 ),
             ])),
@@ -319,7 +319,7 @@ impl SegmentsSearcher {
                             None
                         },
                         vector: match with_vector {
-                            WithVector::Bool(true) => Some(segment.all_vectors(id)?.into()),
+                            WithVector::Bool(true) => Some(segment.all_vectors(id)?.try_into()?),
                             WithVector::Bool(false) => None,
                             WithVector::Selector(vector_names) => {
                                 let mut selected_vectors = NamedVectors::default();
@@ -328,7 +328,7 @@ impl SegmentsSearcher {
                                         selected_vectors.insert(vector_name.into(), vector);
                                     }
                                 }
-                                Some(selected_vectors.into())
+                                Some(selected_vectors.try_into()?)
                             }
                         },
                     },
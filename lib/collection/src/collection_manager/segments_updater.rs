@@ -408,20 +408,24 @@ pub(crate) fn process_point_operation(
                     let vectors_iter = batch.ids.into_iter().zip(all_vectors);
                     match batch.payloads {
                         None => vectors_iter
-                            .map(|(id, vectors)| PointStruct {
-                                id,
-                                vector: vectors.into(),
-                                payload: None,
+                            .map(|(id, vectors)| {
+                                Ok(PointStruct {
+                                    id,
+                                    vector: vectors.try_into()?,
+                                    payload: None,
+                                })
                             })
-                            .collect(),
+                            .collect::<CollectionResult<_>>()?,
                         Some(payloads) => vectors_iter
                             .zip(payloads)
-                            .map(|((id, vectors), payload)| PointStruct {
-                                id,
-                                vector: vectors.into(),
-                                payload,
+                            .map(|((id, vectors), payload)| {
+                                Ok(PointStruct {
+                                    id,
+                                    vector: vectors.try_into()?,
+                                    payload,
+                                })
                             })
-                            .collect(),
+                            .collect::<CollectionResult<_>>()?,
                     }
                 }
                 PointInsertOperations::PointsList(points) => points,
@@ -69,12 +69,12 @@ fn test_update_proxy_segments() {
         let points = vec![
             PointStruct {
                 id: (100 * i + 1).into(),
-                vector: vectors[0].clone().into(),
+                vector: vectors[0].clone().try_into().unwrap(),
                 payload: None,
             },
             PointStruct {
                 id: (100 * i + 2).into(),
-                vector: vectors[1].clone().into(),
+                vector: vectors[1].clone().try_into().unwrap(),
                 payload: None,
             },
         ];
@@ -265,6 +265,10 @@ impl CollectionParams {
                         } else {
                             VectorStorageType::Memory
                         },
+                        max_sparse_dimension: None,
+                        datatype: params.datatype.unwrap_or_default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 )
             })
@@ -4,8 +4,8 @@ use std::future::Future;
 use itertools::Itertools;
 use segment::data_types::vectors::{Named, DEFAULT_VECTOR_NAME};
 use segment::types::{
-    AnyVariants, Condition, FieldCondition, Filter, Match, ScoredPoint, WithPayloadInterface,
-    WithVector,
+    AnyVariants, Condition, FieldCondition, Filter, Match, Order, ScoredPoint,
+    WithPayloadInterface, WithVector,
 };
 use serde_json::Value;
 use tokio::sync::RwLockReadGuard;
@@ -68,6 +68,14 @@ impl SourceRequest {
             SourceRequest::Recommend(request) => request.with_vector.clone(),
         }
     }
+
+    fn normalize_score(&self) -> bool {
+        let params = match self {
+            SourceRequest::Search(request) => request.params,
+            SourceRequest::Recommend(request) => request.params,
+        };
+        params.is_some_and(|params| params.normalize_score)
+    }
 }
 
 #[derive(Clone)]
@@ -271,7 +279,10 @@ where
     F: Fn(String) -> Fut + Clone,
     Fut: Future<Output = Option<RwLockReadGuard<'a, Collection>>>,
 {
-    let score_ordering = {
+    let score_ordering = if request.source.normalize_score() {
+        // Normalized scores are larger-is-better for every distance
+        Order::LargeBetter
+    } else {
         let vector_name = request.source.vector_field_name();
         let collection_params = collection.collection_config.read().await;
         let vector_params = collection_params.params.get_vector_params(vector_name)?;
@@ -339,6 +339,7 @@ mod tests {
                 hnsw_config: None,
                 quantization_config: None,
                 on_disk: None,
+                datatype: None,
             }
             .into(),
             ..CollectionParams::empty()
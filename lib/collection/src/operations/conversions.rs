@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, HashMap};
 use std::num::{NonZeroU32, NonZeroU64};
 
-use api::grpc::conversions::{from_grpc_dist, payload_to_proto, proto_to_payloads};
+use api::grpc::conversions::{
+    from_grpc_datatype, from_grpc_dist, payload_to_proto, proto_to_payloads,
+};
 use api::grpc::qdrant::quantization_config_diff::Quantization;
 use api::grpc::qdrant::update_collection_cluster_setup_request::Operation as ClusterOperationsPb;
 use itertools::Itertools;
@@ -304,15 +306,20 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
     }
 }
 
-impl From<Record> for api::grpc::qdrant::RetrievedPoint {
-    fn from(record: Record) -> Self {
-        let vectors = record.vector.map(|vector_struct| vector_struct.into());
+impl TryFrom<Record> for api::grpc::qdrant::RetrievedPoint {
+    type Error = Status;
 
-        Self {
+    fn try_from(record: Record) -> Result<Self, Self::Error> {
+        let vectors = record
+            .vector
+            .map(|vector_struct| vector_struct.try_into())
+            .transpose()?;
+
+        Ok(Self {
             id: Some(record.id.into()),
             payload: record.payload.map(payload_to_proto).unwrap_or_default(),
             vectors,
-        }
+        })
     }
 }
 
@@ -414,6 +421,7 @@ impl TryFrom<api::grpc::qdrant::VectorParams> for VectorParams {
                 .map(grpc_to_segment_quantization_config)
                 .transpose()?,
             on_disk: vector_params.on_disk,
+            datatype: vector_params.datatype.map(from_grpc_datatype).transpose()?,
         })
     }
 }
@@ -605,7 +613,7 @@ impl TryFrom<PointStruct> for api::grpc::qdrant::PointStruct {
     type Error = Status;
 
     fn try_from(value: PointStruct) -> Result<Self, Self::Error> {
-        let vectors: api::grpc::qdrant::Vectors = value.vector.into();
+        let vectors: api::grpc::qdrant::Vectors = value.vector.try_into()?;
 
         let id = value.id;
         let payload = value.payload;
@@ -642,7 +650,7 @@ impl TryFrom<Batch> for Vec<api::grpc::qdrant::PointStruct> {
 
             let point = api::grpc::qdrant::PointStruct {
                 id,
-                vectors: vectors.map(|v| v.into()),
+                vectors: vectors.map(|v| v.try_into()).transpose()?,
                 payload: payload.unwrap_or_default(),
             };
             points.push(point);
@@ -724,13 +732,17 @@ impl From<CountResult> for api::grpc::qdrant::CountResult {
 }
 
 // Use wrapper type to bundle CollectionId & SearchRequest
-impl<'a> From<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
-    fn from(value: CollectionSearchRequest<'a>) -> Self {
+impl<'a> TryFrom<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
+    type Error = Status;
+
+    fn try_from(value: CollectionSearchRequest<'a>) -> Result<Self, Self::Error> {
         let (collection_id, request) = value.0;
 
-        Self {
+        Ok(Self {
             collection_name: collection_id,
-            vector: request.vector.get_vector().to_vec(),
+            vector: request.vector.get_vector().cloned().ok_or_else(|| {
+                Status::invalid_argument("Sparse vectors are not supported by gRPC API yet")
+            })?,
             filter: request.filter.clone().map(|f| f.into()),
             limit: request.limit as u64,
             with_vectors: request.with_vector.clone().map(|wv| wv.into()),
@@ -743,15 +755,24 @@ impl<'a> From<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
                 vector_name => Some(vector_name.to_string()),
             },
             read_consistency: None,
-        }
+        })
     }
 }
-impl From<QueryEnum> for api::grpc::qdrant::QueryEnum {
-    fn from(value: QueryEnum) -> Self {
-        match value {
+impl TryFrom<QueryEnum> for api::grpc::qdrant::QueryEnum {
+    type Error = Status;
+
+    fn try_from(value: QueryEnum) -> Result<Self, Self::Error> {
+        let query = match value {
             QueryEnum::Nearest(vector) => api::grpc::qdrant::QueryEnum {
                 query: Some(api::grpc::qdrant::query_enum::Query::NearestNeighbors(
-                    vector.to_vector().into(),
+                    vector
+                        .to_vector()
+                        .ok_or_else(|| {
+                            Status::invalid_argument(
+                                "Sparse vectors are not supported by gRPC API yet",
+                            )
+                        })?
+                        .into(),
                 )),
             },
             QueryEnum::RecommendBestScore(named) => api::grpc::qdrant::QueryEnum {
@@ -772,17 +793,20 @@ impl From<QueryEnum> for api::grpc::qdrant::QueryEnum {
                     },
                 )),
             },
-        }
+        };
+        Ok(query)
     }
 }
 
-impl<'a> From<CollectionCoreSearchRequest<'a>> for api::grpc::qdrant::CoreSearchPoints {
-    fn from(value: CollectionCoreSearchRequest<'a>) -> Self {
+impl<'a> TryFrom<CollectionCoreSearchRequest<'a>> for api::grpc::qdrant::CoreSearchPoints {
+    type Error = Status;
+
+    fn try_from(value: CollectionCoreSearchRequest<'a>) -> Result<Self, Self::Error> {
         let (collection_id, request) = value.0;
 
-        Self {
+        Ok(Self {
             collection_name: collection_id,
-            query: Some(request.query.clone().into()),
+            query: Some(request.query.clone().try_into()?),
             filter: request.filter.clone().map(|f| f.into()),
             limit: request.limit as u64,
             with_vectors: request.with_vector.clone().map(|wv| wv.into()),
@@ -792,7 +816,7 @@ impl<'a> From<CollectionCoreSearchRequest<'a>> for api::grpc::qdrant::CoreSearch
             offset: Some(request.offset as u64),
             vector_name: Some(request.query.get_vector_name().to_owned()),
             read_consistency: None,
-        }
+        })
     }
 }
 
@@ -945,13 +969,19 @@ impl TryFrom<api::grpc::qdrant::SearchPointGroups> for SearchGroupsRequest {
     }
 }
 
-impl From<PointGroup> for api::grpc::qdrant::PointGroup {
-    fn from(group: PointGroup) -> Self {
-        Self {
-            hits: group.hits.into_iter().map_into().collect(),
+impl TryFrom<PointGroup> for api::grpc::qdrant::PointGroup {
+    type Error = Status;
+
+    fn try_from(group: PointGroup) -> Result<Self, Self::Error> {
+        Ok(Self {
+            hits: group
+                .hits
+                .into_iter()
+                .map(|hit| hit.try_into())
+                .try_collect()?,
             id: Some(group.id.into()),
-            lookup: group.lookup.map(|record| record.into()),
-        }
+            lookup: group.lookup.map(|record| record.try_into()).transpose()?,
+        })
     }
 }
 
@@ -1101,11 +1131,17 @@ impl TryFrom<api::grpc::qdrant::RecommendPointGroups> for RecommendGroupsRequest
     }
 }
 
-impl From<GroupsResult> for api::grpc::qdrant::GroupsResult {
-    fn from(value: GroupsResult) -> Self {
-        Self {
-            groups: value.groups.into_iter().map(Into::into).collect(),
-        }
+impl TryFrom<GroupsResult> for api::grpc::qdrant::GroupsResult {
+    type Error = Status;
+
+    fn try_from(value: GroupsResult) -> Result<Self, Self::Error> {
+        Ok(Self {
+            groups: value
+                .groups
+                .into_iter()
+                .map(|group| group.try_into())
+                .try_collect()?,
+        })
     }
 }
 
@@ -1117,11 +1153,17 @@ impl From<VectorParams> for api::grpc::qdrant::VectorParams {
                 Distance::Cosine => api::grpc::qdrant::Distance::Cosine,
                 Distance::Euclid => api::grpc::qdrant::Distance::Euclid,
                 Distance::Dot => api::grpc::qdrant::Distance::Dot,
+                Distance::L1 => api::grpc::qdrant::Distance::L1,
+                Distance::Hamming => api::grpc::qdrant::Distance::Hamming,
+                Distance::Chebyshev => api::grpc::qdrant::Distance::Chebyshev,
             }
             .into(),
             hnsw_config: value.hnsw_config.map(Into::into),
             quantization_config: value.quantization_config.map(Into::into),
             on_disk: value.on_disk,
+            datatype: value
+                .datatype
+                .map(|datatype| api::grpc::qdrant::Datatype::from(datatype).into()),
         }
     }
 }
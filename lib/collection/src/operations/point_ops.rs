@@ -5,9 +5,13 @@ use itertools::izip;
 use schemars::gen::SchemaGenerator;
 use schemars::schema::{ObjectValidation, Schema, SchemaObject, SubschemaValidation};
 use schemars::JsonSchema;
-use segment::common::utils::transpose_map_into_named_vector;
+use segment::common::utils::{
+    transpose_map_into_named_sparse_vector, transpose_map_into_named_vector,
+};
 use segment::data_types::named_vectors::NamedVectors;
-use segment::data_types::vectors::{only_default_vector, BatchVectorStruct, VectorStruct};
+use segment::data_types::vectors::{
+    only_default_vector, BatchVectorStruct, VectorOrSparse, VectorStruct, DEFAULT_VECTOR_NAME,
+};
 use segment::types::{Filter, Payload, PointIdType};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -251,6 +255,24 @@ impl Validate for PointInsertOperations {
                             }
                         }
                     }
+                    BatchVectorStruct::SparseSingle(vectors) => {
+                        if batch.ids.len() != vectors.len() {
+                            return Err(create_error(bad_input_description(
+                                batch.ids.len(),
+                                vectors.len(),
+                            )));
+                        }
+                    }
+                    BatchVectorStruct::SparseMulti(named_vectors) => {
+                        for vectors in named_vectors.values() {
+                            if batch.ids.len() != vectors.len() {
+                                return Err(create_error(bad_input_description(
+                                    batch.ids.len(),
+                                    vectors.len(),
+                                )));
+                            }
+                        }
+                    }
                 }
                 if let Some(payload_vector) = &batch.payloads {
                     if payload_vector.len() != batch.ids.len() {
@@ -369,7 +391,53 @@ impl SplitByShard for Batch {
                         let batch_vectors = batch.vectors.multi();
                         for (name, vector) in named_vector {
                             let name = name.into_owned();
-                            let vector = vector.into_owned();
+                            let vector = match vector.into_owned_vector() {
+                                VectorOrSparse::Vector(vector) => vector,
+                                VectorOrSparse::Sparse(_) => {
+                                    unreachable!("dense batch contains only dense vectors")
+                                }
+                            };
+                            batch_vectors.entry(name).or_default().push(vector);
+                        }
+                        batch.payloads.as_mut().unwrap().push(payload);
+                    }
+                }
+                BatchVectorStruct::SparseSingle(vectors) => {
+                    for (id, vector, payload) in izip!(ids, vectors, payloads) {
+                        let shard_id = point_to_shard(id, ring);
+                        let batch = batch_by_shard.entry(shard_id).or_insert_with(|| Batch {
+                            ids: vec![],
+                            vectors: BatchVectorStruct::SparseSingle(vec![]),
+                            payloads: Some(vec![]),
+                        });
+                        batch.ids.push(id);
+                        batch.vectors.sparse_single().push(vector);
+                        batch.payloads.as_mut().unwrap().push(payload);
+                    }
+                }
+                BatchVectorStruct::SparseMulti(named_vectors) => {
+                    let named_vectors_list = if !named_vectors.is_empty() {
+                        transpose_map_into_named_sparse_vector(named_vectors)
+                    } else {
+                        vec![NamedVectors::default(); ids.len()]
+                    };
+                    for (id, named_vector, payload) in izip!(ids, named_vectors_list, payloads) {
+                        let shard_id = point_to_shard(id, ring);
+                        let batch = batch_by_shard.entry(shard_id).or_insert_with(|| Batch {
+                            ids: vec![],
+                            vectors: BatchVectorStruct::SparseMulti(HashMap::new()),
+                            payloads: Some(vec![]),
+                        });
+                        batch.ids.push(id);
+                        let batch_vectors = batch.vectors.sparse_multi();
+                        for (name, vector) in named_vector {
+                            let name = name.into_owned();
+                            let vector = match vector.into_owned_vector() {
+                                VectorOrSparse::Vector(_) => {
+                                    unreachable!("sparse batch contains only sparse vectors")
+                                }
+                                VectorOrSparse::Sparse(vector) => vector,
+                            };
                             batch_vectors.entry(name).or_default().push(vector);
                         }
                         batch.payloads.as_mut().unwrap().push(payload);
@@ -407,7 +475,51 @@ impl SplitByShard for Batch {
                         let batch_vectors = batch.vectors.multi();
                         for (name, vector) in named_vector {
                             let name = name.into_owned();
-                            let vector = vector.into_owned();
+                            let vector = match vector.into_owned_vector() {
+                                VectorOrSparse::Vector(vector) => vector,
+                                VectorOrSparse::Sparse(_) => {
+                                    unreachable!("dense batch contains only dense vectors")
+                                }
+                            };
+                            batch_vectors.entry(name).or_default().push(vector);
+                        }
+                    }
+                }
+                BatchVectorStruct::SparseSingle(vectors) => {
+                    for (id, vector) in izip!(ids, vectors) {
+                        let shard_id = point_to_shard(id, ring);
+                        let batch = batch_by_shard.entry(shard_id).or_insert_with(|| Batch {
+                            ids: vec![],
+                            vectors: BatchVectorStruct::SparseSingle(vec![]),
+                            payloads: None,
+                        });
+                        batch.ids.push(id);
+                        batch.vectors.sparse_single().push(vector);
+                    }
+                }
+                BatchVectorStruct::SparseMulti(named_vectors) => {
+                    let named_vectors_list = if !named_vectors.is_empty() {
+                        transpose_map_into_named_sparse_vector(named_vectors)
+                    } else {
+                        vec![NamedVectors::default(); ids.len()]
+                    };
+                    for (id, named_vector) in izip!(ids, named_vectors_list) {
+                        let shard_id = point_to_shard(id, ring);
+                        let batch = batch_by_shard.entry(shard_id).or_insert_with(|| Batch {
+                            ids: vec![],
+                            vectors: BatchVectorStruct::SparseMulti(HashMap::new()),
+                            payloads: None,
+                        });
+                        batch.ids.push(id);
+                        let batch_vectors = batch.vectors.sparse_multi();
+                        for (name, vector) in named_vector {
+                            let name = name.into_owned();
+                            let vector = match vector.into_owned_vector() {
+                                VectorOrSparse::Vector(_) => {
+                                    unreachable!("sparse batch contains only sparse vectors")
+                                }
+                                VectorOrSparse::Sparse(vector) => vector,
+                            };
                             batch_vectors.entry(name).or_default().push(vector);
                         }
                     }
@@ -463,6 +575,10 @@ impl PointStruct {
         match &self.vector {
             VectorStruct::Single(vector) => only_default_vector(vector),
             VectorStruct::Multi(vectors) => NamedVectors::from_map_ref(vectors),
+            VectorStruct::Sparse(vector) => {
+                NamedVectors::from_sparse_ref(DEFAULT_VECTOR_NAME, vector)
+            }
+            VectorStruct::MultiSparse(vectors) => NamedVectors::from_sparse_map_ref(vectors),
         }
     }
 }
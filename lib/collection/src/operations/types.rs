@@ -22,10 +22,10 @@ use segment::data_types::vectors::{
 };
 use segment::types::{
     Distance, Filter, Payload, PayloadIndexInfo, PayloadKeyType, PointIdType, QuantizationConfig,
-    ScoredPoint, SearchParams, SeqNumberType, WithPayloadInterface, WithVector,
+    ScoredPoint, SearchParams, SeqNumberType, VectorStorageDatatype, WithPayloadInterface,
+    WithVector,
 };
-use serde;
-use serde::{Deserialize, Serialize};
+use serde::{self, Deserialize, Serialize};
 use serde_json::Error as JsonError;
 use thiserror::Error;
 use tokio::sync::mpsc::error::SendError;
@@ -738,6 +738,15 @@ impl From<OperationError> for CollectionError {
             OperationError::ValidationError { .. } => Self::BadInput {
                 description: format!("{err}"),
             },
+            OperationError::WrongSparse => Self::BadInput {
+                description: format!("{err}"),
+            },
+            OperationError::VectorTypeMismatch { .. } => Self::BadInput {
+                description: format!("{err}"),
+            },
+            OperationError::InvalidVector { .. } => Self::BadInput {
+                description: format!("{err}"),
+            },
         }
     }
 }
@@ -895,18 +904,15 @@ impl Record {
         match &self.vector {
             None => vec![],
             Some(vectors) => match vectors {
-                VectorStruct::Single(_) => vec![DEFAULT_VECTOR_NAME],
+                VectorStruct::Single(_) | VectorStruct::Sparse(_) => vec![DEFAULT_VECTOR_NAME],
                 VectorStruct::Multi(vectors) => vectors.keys().map(|x| x.as_str()).collect(),
+                VectorStruct::MultiSparse(vectors) => vectors.keys().map(|x| x.as_str()).collect(),
             },
         }
     }
 
     pub fn get_vector_by_name(&self, name: &str) -> Option<&VectorType> {
-        match &self.vector {
-            Some(VectorStruct::Single(vector)) => (name == DEFAULT_VECTOR_NAME).then_some(vector),
-            Some(VectorStruct::Multi(vectors)) => vectors.get(name),
-            None => None,
-        }
+        self.vector.as_ref()?.get(name)
     }
 }
 
@@ -935,6 +941,10 @@ pub struct VectorParams {
     /// Default: false
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub on_disk: Option<bool>,
+    /// Element type to store vectors with once they are moved to mmap storage, see `on_disk`
+    /// Default: float32
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datatype: Option<VectorStorageDatatype>,
 }
 
 /// Validate the value is in `[1, 65536]` or `None`.
@@ -1316,8 +1326,8 @@ impl From<SearchRequest> for CoreSearchRequest {
 impl From<QueryEnum> for QueryVector {
     fn from(query: QueryEnum) -> Self {
         match query {
-            QueryEnum::Nearest(named) => QueryVector::Nearest(named.to_vector()),
-            QueryEnum::RecommendBestScore(named) => QueryVector::Recommend(named.query),
+            QueryEnum::Nearest(named) => QueryVector::Nearest(named.to_vector_or_sparse()),
+            QueryEnum::RecommendBestScore(named) => named.query.into(),
         }
     }
 }
@@ -116,8 +116,8 @@ pub fn internal_update_vectors(
     update_vectors: UpdateVectors,
     wait: bool,
     ordering: Option<WriteOrdering>,
-) -> UpdateVectorsInternal {
-    UpdateVectorsInternal {
+) -> CollectionResult<UpdateVectorsInternal> {
+    Ok(UpdateVectorsInternal {
         shard_id,
         update_vectors: Some(UpdatePointVectors {
             collection_name,
@@ -125,14 +125,16 @@ pub fn internal_update_vectors(
             points: update_vectors
                 .points
                 .into_iter()
-                .map(|point| PointVectors {
-                    id: Some(point.id.into()),
-                    vectors: Some(point.vector.into()),
+                .map(|point| {
+                    Ok(PointVectors {
+                        id: Some(point.id.into()),
+                        vectors: Some(point.vector.try_into()?),
+                    })
                 })
-                .collect(),
+                .collect::<Result<Vec<_>, Status>>()?,
             ordering: ordering.map(write_ordering_to_proto),
         }),
-    }
+    })
 }
 
 pub fn internal_delete_vectors(
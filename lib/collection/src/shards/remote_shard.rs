@@ -237,7 +237,7 @@ impl RemoteShard {
                         update_operation,
                         wait,
                         ordering,
-                    );
+                    )?;
                     self.with_points_client(|mut client| async move {
                         client
                             .update_vectors(tonic::Request::new(request.clone()))
@@ -492,8 +492,8 @@ impl ShardOperation for RemoteShard {
         let search_points = batch_request
             .searches
             .iter()
-            .map(|s| CollectionSearchRequest((self.collection_id.clone(), s)).into())
-            .collect();
+            .map(|s| CollectionSearchRequest((self.collection_id.clone(), s)).try_into())
+            .collect::<Result<_, Status>>()?;
 
         let request = &SearchBatchPointsInternal {
             collection_name: self.collection_id.clone(),
@@ -546,8 +546,8 @@ impl ShardOperation for RemoteShard {
         let search_points = batch_request
             .searches
             .iter()
-            .map(|s| CollectionCoreSearchRequest((self.collection_id.clone(), s)).into())
-            .collect();
+            .map(|s| CollectionCoreSearchRequest((self.collection_id.clone(), s)).try_into())
+            .collect::<Result<_, Status>>()?;
 
         let request = &CoreSearchBatchPointsInternal {
             collection_name: self.collection_id.clone(),
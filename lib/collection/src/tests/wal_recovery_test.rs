@@ -27,6 +27,7 @@ fn create_collection_config() -> CollectionConfig {
             hnsw_config: None,
             quantization_config: None,
             on_disk: None,
+            datatype: None,
         }),
         ..CollectionParams::empty()
     };
@@ -41,6 +41,7 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
         hnsw_config: None,
         quantization_config: None,
         on_disk: None,
+        datatype: None,
     };
     let vector_params2 = VectorParams {
         size: NonZeroU64::new(4).unwrap(),
@@ -48,6 +49,7 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
         hnsw_config: None,
         quantization_config: None,
         on_disk: None,
+        datatype: None,
     };
 
     let mut vectors_config = BTreeMap::new();
@@ -99,7 +101,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
 
         points.push(PointStruct {
             id: i.into(),
-            vector: vectors.into(),
+            vector: vectors.try_into().unwrap(),
             payload: Some(serde_json::from_str(r#"{"number": "John Doe"}"#).unwrap()),
         });
     }
@@ -135,7 +137,9 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
 
     for hit in result {
         match hit.vector.unwrap() {
-            VectorStruct::Single(_) => panic!("expected multi vector"),
+            VectorStruct::Single(_) | VectorStruct::Sparse(_) | VectorStruct::MultiSparse(_) => {
+                panic!("expected multi vector")
+            }
             VectorStruct::Multi(vectors) => {
                 assert!(vectors.contains_key(VEC_NAME1));
                 assert!(vectors.contains_key(VEC_NAME2));
@@ -185,7 +189,9 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
 
     for hit in result {
         match hit.vector.unwrap() {
-            VectorStruct::Single(_) => panic!("expected multi vector"),
+            VectorStruct::Single(_) | VectorStruct::Sparse(_) | VectorStruct::MultiSparse(_) => {
+                panic!("expected multi vector")
+            }
             VectorStruct::Multi(vectors) => {
                 assert!(vectors.contains_key(VEC_NAME1));
                 assert!(vectors.contains_key(VEC_NAME2));
@@ -208,7 +214,9 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
 
     assert_eq!(retrieve.len(), 1);
     match retrieve[0].vector.as_ref().unwrap() {
-        VectorStruct::Single(_) => panic!("expected multi vector"),
+        VectorStruct::Single(_) | VectorStruct::Sparse(_) | VectorStruct::MultiSparse(_) => {
+            panic!("expected multi vector")
+        }
         VectorStruct::Multi(vectors) => {
             assert!(vectors.contains_key(VEC_NAME1));
             assert!(!vectors.contains_key(VEC_NAME2));
@@ -257,7 +265,9 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
     assert_eq!(recommend_result.len(), 10);
     for hit in recommend_result {
         match hit.vector.as_ref().unwrap() {
-            VectorStruct::Single(_) => panic!("expected multi vector"),
+            VectorStruct::Single(_) | VectorStruct::Sparse(_) | VectorStruct::MultiSparse(_) => {
+                panic!("expected multi vector")
+            }
             VectorStruct::Multi(vectors) => {
                 assert!(!vectors.contains_key(VEC_NAME1));
                 assert!(vectors.contains_key(VEC_NAME2));
@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::{io, mem, ops, time};
 
-use memmap2::{Mmap, MmapMut};
+use memmap2::{Mmap, MmapMut, MmapOptions};
 
 use crate::madvise;
 use crate::madvise::Madviseable;
@@ -48,6 +48,22 @@ pub fn open_write_mmap(path: &Path) -> io::Result<MmapMut> {
     Ok(mmap)
 }
 
+/// Open existing file as a private copy-on-write mmap
+///
+/// Changes to the mmap are never written back to the file, the file itself is opened read-only.
+pub fn open_copy_on_write_mmap(path: &Path) -> io::Result<MmapMut> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(false)
+        .create(false)
+        .open(path)?;
+
+    let mmap = unsafe { MmapOptions::new().map_copy(&file)? };
+    madvise::madvise(&mmap, madvise::get_global())?;
+
+    Ok(mmap)
+}
+
 #[derive(Clone, Debug)]
 pub struct PrefaultMmapPages {
     mmap: Arc<Mmap>,
@@ -24,10 +24,11 @@
 
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use std::{mem, slice};
+use std::{io, mem, slice};
 
 use bitvec::slice::BitSlice;
 use memmap2::MmapMut;
+use memory::madvise::{self, Advice};
 
 use crate::common::Flusher;
 
@@ -180,6 +181,11 @@ where
             }
         })
     }
+
+    /// Advise OS how the underlying mmap will be accessed
+    pub fn madvise(&self, advice: Advice) -> io::Result<()> {
+        madvise::madvise(self.mmap.as_ref(), advice)
+    }
 }
 
 impl<T> Deref for MmapType<T>
@@ -262,6 +268,11 @@ impl<T> MmapSlice<T> {
     pub fn flusher(&self) -> Flusher {
         self.mmap.flusher()
     }
+
+    /// Advise OS how the underlying mmap will be accessed
+    pub fn madvise(&self, advice: Advice) -> io::Result<()> {
+        self.mmap.madvise(advice)
+    }
 }
 
 impl<T> Deref for MmapSlice<T> {
@@ -13,13 +13,19 @@ pub mod version;
 
 use std::sync::atomic::AtomicBool;
 
+use common::types::PointOffsetType;
+
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::data_types::named_vectors::NamedVectors;
-use crate::data_types::vectors::{QueryVector, VectorElementType};
-use crate::types::{SegmentConfig, VectorDataConfig};
+use crate::data_types::vectors::{QueryVector, VectorOrSparse, VectorOrSparseRef, DENSE_TYPE_NAME};
+use crate::types::{Distance, SegmentConfig, VectorDataConfig};
+use crate::vector_storage::{VectorStorage, VectorStorageEnum};
 
 pub type Flusher = Box<dyn FnOnce() -> OperationResult<()> + Send>;
 
+/// Same as [`Flusher`], reporting progress in range `[0.0, 1.0]` to the given callback
+pub type ProgressFlusher = Box<dyn FnOnce(&dyn Fn(f32)) -> OperationResult<()> + Send>;
+
 /// Check that the given vector name is part of the segment config.
 ///
 /// Returns an error if incompatible.
@@ -30,7 +36,7 @@ pub fn check_vector_name(vector_name: &str, segment_config: &SegmentConfig) -> O
 
 /// Check that the given vector name and elements are compatible with the given segment config.
 ///
-/// Returns an error if incompatible.
+/// Returns an error if incompatible or if elements are not finite.
 pub fn check_vector(
     vector_name: &str,
     query_vector: &QueryVector,
@@ -44,11 +50,11 @@ fn _check_query_vector(
     query_vector: &QueryVector,
     vector_config: &VectorDataConfig,
 ) -> OperationResult<()> {
+    let check = |vector: &VectorOrSparse| check_vector_against_config(vector.into(), vector_config);
     match query_vector {
-        QueryVector::Nearest(vector) => check_vector_against_config(vector, vector_config)?,
-        QueryVector::Recommend(reco_query) => reco_query
-            .iter_all()
-            .try_for_each(|vector| check_vector_against_config(vector, vector_config))?,
+        QueryVector::Nearest(vector) => check(vector)?,
+        QueryVector::Recommend(reco_query) => reco_query.iter_all().try_for_each(check)?,
+        QueryVector::Context(context_query) => context_query.iter_all().try_for_each(check)?,
     }
 
     Ok(())
@@ -56,7 +62,7 @@ fn _check_query_vector(
 
 /// Check that the given vector name and elements are compatible with the given segment config.
 ///
-/// Returns an error if incompatible.
+/// Returns an error if incompatible or if elements are not finite.
 pub fn check_query_vectors(
     vector_name: &str,
     query_vectors: &[&QueryVector],
@@ -71,17 +77,36 @@ pub fn check_query_vectors(
 
 /// Check that the given named vectors are compatible with the given segment config.
 ///
-/// Returns an error if incompatible.
+/// Returns an error if incompatible or if elements are not finite.
 pub fn check_named_vectors(
     vectors: &NamedVectors,
     segment_config: &SegmentConfig,
 ) -> OperationResult<()> {
     for (vector_name, vector_data) in vectors.iter() {
-        check_vector(vector_name, &vector_data.to_vec().into(), segment_config)?;
+        let vector_config = get_vector_config_or_error(vector_name, segment_config)?;
+        check_vector_against_config(vector_data, vector_config)?;
     }
     Ok(())
 }
 
+/// Check that all given named vectors are dense, as segment vector storages only hold dense
+/// vectors.
+///
+/// Returns an error naming the first sparse vector.
+pub fn check_dense_named_vectors(vectors: &NamedVectors) -> OperationResult<()> {
+    match vectors
+        .iter()
+        .find(|(_, vector)| matches!(vector, VectorOrSparseRef::Sparse(_)))
+    {
+        Some((vector_name, vector)) => Err(OperationError::VectorTypeMismatch {
+            vector_name: vector_name.to_owned(),
+            expected: DENSE_TYPE_NAME,
+            got: vector.type_name(),
+        }),
+        None => Ok(()),
+    }
+}
+
 /// Get the vector config for the given name, or return a name error.
 ///
 /// Returns an error if incompatible.
@@ -99,29 +124,408 @@ fn get_vector_config_or_error<'a>(
 
 /// Check if the given vector data is compatible with the given configuration.
 ///
+/// Dense vectors must match the configured size, sparse vectors must not have indices above
+/// [`VectorDataConfig::max_sparse_dimension`]. Elements are also checked to be finite, see
+/// [`check_vector_finite`]. With [`VectorDataConfig::strict_vector_checks`], see
+/// [`check_vector_strict`] too.
+///
 /// Returns an error if incompatible.
 fn check_vector_against_config(
-    vector: &[VectorElementType],
+    vector: VectorOrSparseRef,
     vector_config: &VectorDataConfig,
 ) -> OperationResult<()> {
-    // Check dimensionality
-    let dim = vector_config.size;
-    if vector.len() != dim {
-        return Err(OperationError::WrongVector {
-            expected_dim: dim,
-            received_dim: vector.len(),
-        });
+    match vector {
+        VectorOrSparseRef::Vector(vector) => {
+            // Check dimensionality
+            let dim = vector_config.size;
+            if vector.len() != dim {
+                return Err(OperationError::WrongVector {
+                    expected_dim: dim,
+                    received_dim: vector.len(),
+                });
+            }
+        }
+        VectorOrSparseRef::Sparse(vector) => {
+            let max_index = vector.max_index();
+            if let (Some(max_index), Some(max_dimension)) =
+                (max_index, vector_config.max_sparse_dimension)
+            {
+                if max_index > max_dimension {
+                    return Err(OperationError::ValidationError {
+                        description: format!(
+                            "Sparse vector index {max_index} exceeds maximum dimension {max_dimension}"
+                        ),
+                    });
+                }
+            }
+        }
     }
-    Ok(())
+    if vector_config.strict_vector_checks {
+        check_vector_strict(vector, vector_config)?;
+    }
+    check_vector_finite(vector)
+}
+
+/// Check that the given vector can be scored with the configured distance, e.g. dense vectors are
+/// not all-zero with cosine distance.
+///
+/// Opt-in with [`VectorDataConfig::strict_vector_checks`], the regular checks accept zero
+/// vectors, as cosine preprocessing leaves them unchanged.
+pub fn check_vector_strict(
+    vector: VectorOrSparseRef,
+    vector_config: &VectorDataConfig,
+) -> OperationResult<()> {
+    match vector {
+        VectorOrSparseRef::Vector(vector)
+            if vector_config.distance == Distance::Cosine && vector.iter().all(|&x| x == 0.0) =>
+        {
+            Err(OperationError::ValidationError {
+                description: "All-zero vector is not allowed with Cosine distance".to_owned(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that dense vector elements or sparse vector weights contain no NaN or infinite values.
+///
+/// Non-finite values produce NaN scores, which break ordering of search results.
+/// Returns [`OperationError::InvalidVector`] with the position of the first offending value.
+pub fn check_vector_finite(vector: VectorOrSparseRef) -> OperationResult<()> {
+    let values = match vector {
+        VectorOrSparseRef::Vector(vector) => vector,
+        VectorOrSparseRef::Sparse(vector) => vector.weights.as_slice(),
+    };
+    match values.iter().position(|value| !value.is_finite()) {
+        Some(position) => Err(OperationError::InvalidVector { position }),
+        None => Ok(()),
+    }
+}
+
+/// Propagate deletion of a point to its vectors in all given storages
+///
+/// See [`VectorStorage::deleted_vector_count`]. Returns the number of storages, in which the
+/// vector was not deleted before.
+pub fn delete_point_vectors(
+    storages: &mut [&mut VectorStorageEnum],
+    key: PointOffsetType,
+) -> OperationResult<usize> {
+    let mut newly_deleted = 0;
+    for storage in storages.iter_mut() {
+        if storage.delete_vector(key)? {
+            newly_deleted += 1;
+        }
+    }
+    Ok(newly_deleted)
 }
 
 pub fn check_stopped(is_stopped: &AtomicBool) -> OperationResult<()> {
+    check_stopped_with(is_stopped, "Operation")
+}
+
+/// Same as [`check_stopped`], `context` names the cancelled operation or phase in the error
+pub fn check_stopped_with(is_stopped: &AtomicBool, context: &str) -> OperationResult<()> {
     if is_stopped.load(std::sync::atomic::Ordering::Relaxed) {
         return Err(OperationError::Cancelled {
-            description: "Operation is stopped externally".to_string(),
+            description: format!("{context} is stopped externally"),
         });
     }
     Ok(())
 }
 
 pub const BYTES_IN_KB: usize = 1024;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sparse::common::sparse_vector::SparseVector;
+    use sparse::common::types::DimId;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+    use crate::data_types::vectors::VectorElementType;
+    use crate::types::{Indexes, VectorStorageDatatype, VectorStorageType};
+    use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
+
+    #[test]
+    fn test_check_stopped_with_context() {
+        let is_stopped = AtomicBool::new(false);
+        check_stopped(&is_stopped).unwrap();
+        check_stopped_with(&is_stopped, "Vector index build").unwrap();
+
+        is_stopped.store(true, std::sync::atomic::Ordering::Relaxed);
+        let Err(OperationError::Cancelled { description }) =
+            check_stopped_with(&is_stopped, "Vector index build")
+        else {
+            panic!("expected cancellation");
+        };
+        assert_eq!(description, "Vector index build is stopped externally");
+
+        let Err(OperationError::Cancelled { description }) = check_stopped(&is_stopped) else {
+            panic!("expected cancellation");
+        };
+        assert_eq!(description, "Operation is stopped externally");
+    }
+
+    #[test]
+    fn test_delete_point_vectors() {
+        let dirs: Vec<_> = (0..2)
+            .map(|_| Builder::new().prefix("storage_dir").tempdir().unwrap())
+            .collect();
+        let storages: Vec<_> = dirs
+            .iter()
+            .map(|dir| {
+                let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+                open_simple_vector_storage(db, DB_VECTOR_CF, 2, Distance::Dot).unwrap()
+            })
+            .collect();
+        let mut storages: Vec<_> = storages
+            .iter()
+            .map(|storage| storage.borrow_mut())
+            .collect();
+        for storage in storages.iter_mut() {
+            for key in 0..3 {
+                storage.insert_vector(key, &[1.0, 0.0]).unwrap();
+            }
+        }
+        // Vector may already be deleted in some of the storages
+        storages[1].delete_vector(1).unwrap();
+
+        let mut storages: Vec<&mut VectorStorageEnum> =
+            storages.iter_mut().map(|storage| &mut **storage).collect();
+        assert_eq!(delete_point_vectors(&mut storages, 0).unwrap(), 2);
+        assert_eq!(delete_point_vectors(&mut storages, 1).unwrap(), 1);
+        assert_eq!(delete_point_vectors(&mut storages, 1).unwrap(), 0);
+        for storage in &storages {
+            assert!(storage.is_deleted_vector(0));
+            assert!(storage.is_deleted_vector(1));
+            assert!(!storage.is_deleted_vector(2));
+            assert_eq!(storage.deleted_vector_count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_check_vector_finite() {
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                "image".to_owned(),
+                VectorDataConfig {
+                    size: 4,
+                    distance: Distance::Dot,
+                    storage_type: VectorStorageType::Memory,
+                    index: Indexes::Plain {},
+                    quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
+                },
+            )]),
+            payload_storage_type: Default::default(),
+        };
+
+        let nan: &[VectorElementType] = &[0.1, 0.2, f32::NAN, 0.4];
+        let inf: &[VectorElementType] = &[0.1, 0.2, 0.3, f32::INFINITY];
+        for (vector, expected_position) in [(nan, 2), (inf, 3)] {
+            let Err(OperationError::InvalidVector { position }) =
+                check_vector("image", &vector.into(), &config)
+            else {
+                panic!("expected invalid vector");
+            };
+            assert_eq!(position, expected_position);
+
+            let vectors = NamedVectors::from_ref("image", vector);
+            let Err(OperationError::InvalidVector { position }) =
+                check_named_vectors(&vectors, &config)
+            else {
+                panic!("expected invalid vector");
+            };
+            assert_eq!(position, expected_position);
+        }
+        check_vector("image", &[0.1, 0.2, 0.3, 0.4].into(), &config).unwrap();
+
+        let sparse = SparseVector::new(vec![1, 5, 7], vec![0.5, f32::NEG_INFINITY, 0.1]);
+        let Err(OperationError::InvalidVector { position }) = check_vector_finite((&sparse).into())
+        else {
+            panic!("expected invalid vector");
+        };
+        assert_eq!(position, 1);
+        let sparse = SparseVector::new(vec![1, 5], vec![0.5, 0.1]);
+        check_vector_finite((&sparse).into()).unwrap();
+
+        let nan_sparse = SparseVector::new(vec![1, 5, 7], vec![0.5, f32::NAN, 0.1]);
+        let Err(OperationError::InvalidVector { position }) =
+            check_vector("image", &nan_sparse.clone().into(), &config)
+        else {
+            panic!("expected invalid vector");
+        };
+        assert_eq!(position, 1);
+        let vectors = NamedVectors::from_sparse_ref("image", &nan_sparse);
+        let Err(OperationError::InvalidVector { position }) =
+            check_named_vectors(&vectors, &config)
+        else {
+            panic!("expected invalid vector");
+        };
+        assert_eq!(position, 1);
+    }
+
+    #[test]
+    fn test_zero_vector_cosine_check() {
+        let vector_config = |distance, strict_vector_checks| VectorDataConfig {
+            size: 2,
+            distance,
+            storage_type: VectorStorageType::Memory,
+            index: Indexes::Plain {},
+            quantization_config: None,
+            max_sparse_dimension: None,
+            datatype: Default::default(),
+            vector_cache_size: None,
+            strict_vector_checks,
+        };
+        let config = SegmentConfig {
+            vector_data: HashMap::from([
+                ("cos".into(), vector_config(Distance::Cosine, true)),
+                ("dot".into(), vector_config(Distance::Dot, true)),
+                ("cos_lenient".into(), vector_config(Distance::Cosine, false)),
+            ]),
+            payload_storage_type: Default::default(),
+        };
+
+        let zero: &[VectorElementType] = &[0.0, 0.0];
+        let non_zero: &[VectorElementType] = &[0.0, 0.1];
+        check_vector("dot", &zero.into(), &config).unwrap();
+        check_vector("cos", &non_zero.into(), &config).unwrap();
+        let Err(OperationError::ValidationError { description }) =
+            check_vector("cos", &zero.into(), &config)
+        else {
+            panic!("expected validation error");
+        };
+        assert!(description.contains("Cosine"));
+        let vectors = NamedVectors::from_ref("cos", zero);
+        assert!(check_named_vectors(&vectors, &config).is_err());
+
+        // Without strict checks zero vectors are accepted
+        check_vector("cos_lenient", &zero.into(), &config).unwrap();
+        let vectors = NamedVectors::from_ref("cos_lenient", zero);
+        check_named_vectors(&vectors, &config).unwrap();
+    }
+
+    fn sparse_segment_config(max_sparse_dimension: Option<DimId>) -> SegmentConfig {
+        SegmentConfig {
+            vector_data: HashMap::from([(
+                "text".to_owned(),
+                VectorDataConfig {
+                    size: 4,
+                    distance: Distance::Dot,
+                    storage_type: VectorStorageType::Memory,
+                    index: Indexes::Plain {},
+                    quantization_config: None,
+                    max_sparse_dimension,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
+                },
+            )]),
+            payload_storage_type: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_sparse_vector_within_max_dimension() {
+        let config = sparse_segment_config(Some(100));
+        let within = SparseVector::new(vec![3, 100, 7], vec![0.1, 0.2, 0.3]);
+        let empty = SparseVector::new(vec![], vec![]);
+
+        for sparse in [within, empty] {
+            check_vector("text", &sparse.clone().into(), &config).unwrap();
+            let vectors = NamedVectors::from_sparse_ref("text", &sparse);
+            check_named_vectors(&vectors, &config).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sparse_vector_exceeding_max_dimension() {
+        let config = sparse_segment_config(Some(100));
+        let exceeding = SparseVector::new(vec![3, 101], vec![0.1, 0.2]);
+
+        let Err(OperationError::ValidationError { description }) =
+            check_vector("text", &exceeding.clone().into(), &config)
+        else {
+            panic!("expected validation error");
+        };
+        assert!(description.contains("101"));
+
+        let vectors = NamedVectors::from_sparse_ref("text", &exceeding);
+        let Err(OperationError::ValidationError { description }) =
+            check_named_vectors(&vectors, &config)
+        else {
+            panic!("expected validation error");
+        };
+        assert!(description.contains("101"));
+    }
+
+    #[test]
+    fn test_sparse_vector_without_max_dimension() {
+        let config = sparse_segment_config(None);
+        let exceeding = SparseVector::new(vec![3, 101], vec![0.1, 0.2]);
+        let huge = SparseVector::new(vec![DimId::MAX], vec![1.0]);
+
+        for sparse in [exceeding, huge] {
+            check_vector("text", &sparse.clone().into(), &config).unwrap();
+            let vectors = NamedVectors::from_sparse_ref("text", &sparse);
+            check_named_vectors(&vectors, &config).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sparse_vector_error_names_vector() {
+        let config = sparse_segment_config(None);
+        let sparse = SparseVector::new(vec![1], vec![0.5]);
+
+        let vectors = NamedVectors::from_sparse_ref("text", &sparse);
+        check_named_vectors(&vectors, &config).unwrap();
+        let error = check_dense_named_vectors(&vectors).unwrap_err();
+        assert!(matches!(error, OperationError::VectorTypeMismatch { .. }));
+        assert!(error.to_string().contains("`text`"));
+
+        let dense: &[VectorElementType] = &[0.1, 0.2, 0.3, 0.4];
+        check_dense_named_vectors(&NamedVectors::from_ref("text", dense)).unwrap();
+    }
+
+    #[test]
+    fn test_f16_vector_checks_logical_dimension() {
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                "image".to_owned(),
+                VectorDataConfig {
+                    size: 4,
+                    distance: Distance::Dot,
+                    storage_type: VectorStorageType::Mmap,
+                    index: Indexes::Plain {},
+                    quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: VectorStorageDatatype::Float16,
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
+                },
+            )]),
+            payload_storage_type: Default::default(),
+        };
+
+        check_vector("image", &[0.1, 0.2, 0.3, 0.4].into(), &config).unwrap();
+        // Size of f16 elements must not matter
+        let Err(OperationError::WrongVector {
+            expected_dim,
+            received_dim,
+        }) = check_vector("image", &[0.1, 0.2].into(), &config)
+        else {
+            panic!("expected wrong vector dimension");
+        };
+        assert_eq!((expected_dim, received_dim), (4, 2));
+        let vector: &[VectorElementType] = &[0.1; 8];
+        let vectors = NamedVectors::from_ref("image", vector);
+        assert!(check_named_vectors(&vectors, &config).is_err());
+    }
+}
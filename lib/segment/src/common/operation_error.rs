@@ -50,6 +50,16 @@ pub enum OperationError {
     Cancelled { description: String },
     #[error("Validation failed: {description}")]
     ValidationError { description: String },
+    #[error("Wrong usage of sparse vectors")]
+    WrongSparse,
+    #[error("Vector contains non-finite value at position {position}")]
+    InvalidVector { position: usize },
+    #[error("Wrong vector type for vector `{vector_name}`: expected {expected}, got {got}")]
+    VectorTypeMismatch {
+        vector_name: String,
+        expected: &'static str,
+        got: &'static str,
+    },
 }
 
 impl OperationError {
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use serde_json::Value;
+use sparse::common::sparse_vector::SparseVector;
 
 use crate::data_types::named_vectors::NamedVectors;
 use crate::data_types::vectors::VectorElementType;
@@ -438,6 +439,19 @@ pub fn transpose_map_into_named_vector(
     result
 }
 
+pub fn transpose_map_into_named_sparse_vector(
+    map: HashMap<String, Vec<SparseVector>>,
+) -> Vec<NamedVectors<'static>> {
+    let mut result = Vec::new();
+    for (key, values) in map {
+        result.resize_with(values.len(), NamedVectors::default);
+        for (i, value) in values.into_iter().enumerate() {
+            result[i].insert_sparse(key.clone(), value);
+        }
+    }
+    result
+}
+
 /// Light abstraction over a JSON path to avoid concatenating strings
 #[derive(Debug, Clone)]
 pub struct JsonPathPayload {
@@ -52,6 +52,10 @@ impl From<SegmentConfigV5> for SegmentConfig {
                     storage_type: (old_data.on_disk == Some(true))
                         .then_some(VectorStorageType::Mmap)
                         .unwrap_or_else(|| old_segment.storage_type.into()),
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 };
 
                 (vector_name, new_data)
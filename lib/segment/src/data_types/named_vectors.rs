@@ -1,14 +1,74 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use sparse::common::sparse_vector::SparseVector;
+
 use super::tiny_map;
-use super::vectors::{VectorElementType, DEFAULT_VECTOR_NAME};
+use super::vectors::{VectorElementType, VectorOrSparse, VectorOrSparseRef, DEFAULT_VECTOR_NAME};
 use crate::types::Distance;
 
 type CowKey<'a> = Cow<'a, str>;
-type CowValue<'a> = Cow<'a, [VectorElementType]>;
 type TinyMap<'a> = tiny_map::TinyMap<CowKey<'a>, CowValue<'a>>;
 
+/// Owned or borrowed dense or sparse vector
+#[derive(Clone, Debug, PartialEq)]
+pub enum CowValue<'a> {
+    Dense(Cow<'a, [VectorElementType]>),
+    Sparse(Cow<'a, SparseVector>),
+}
+
+impl<'a> Default for CowValue<'a> {
+    fn default() -> Self {
+        CowValue::Dense(Cow::Owned(Vec::new()))
+    }
+}
+
+impl<'a> CowValue<'a> {
+    pub fn as_vec_ref(&self) -> VectorOrSparseRef {
+        match self {
+            CowValue::Dense(v) => VectorOrSparseRef::Vector(v.as_ref()),
+            CowValue::Sparse(v) => VectorOrSparseRef::Sparse(v.as_ref()),
+        }
+    }
+
+    pub fn into_owned_vector(self) -> VectorOrSparse {
+        match self {
+            CowValue::Dense(v) => VectorOrSparse::Vector(v.into_owned()),
+            CowValue::Sparse(v) => VectorOrSparse::Sparse(v.into_owned()),
+        }
+    }
+}
+
+impl<'a> From<Vec<VectorElementType>> for CowValue<'a> {
+    fn from(v: Vec<VectorElementType>) -> Self {
+        CowValue::Dense(Cow::Owned(v))
+    }
+}
+
+impl<'a> From<&'a [VectorElementType]> for CowValue<'a> {
+    fn from(v: &'a [VectorElementType]) -> Self {
+        CowValue::Dense(Cow::Borrowed(v))
+    }
+}
+
+impl<'a> From<&'a Vec<VectorElementType>> for CowValue<'a> {
+    fn from(v: &'a Vec<VectorElementType>) -> Self {
+        CowValue::Dense(Cow::Borrowed(v))
+    }
+}
+
+impl<'a> From<SparseVector> for CowValue<'a> {
+    fn from(v: SparseVector) -> Self {
+        CowValue::Sparse(Cow::Owned(v))
+    }
+}
+
+impl<'a> From<&'a SparseVector> for CowValue<'a> {
+    fn from(v: &'a SparseVector) -> Self {
+        CowValue::Sparse(Cow::Borrowed(v))
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct NamedVectors<'a> {
     map: TinyMap<'a>,
@@ -17,7 +77,22 @@ pub struct NamedVectors<'a> {
 impl<'a> NamedVectors<'a> {
     pub fn from_ref(key: &'a str, value: &'a [VectorElementType]) -> Self {
         let mut map = TinyMap::new();
-        map.insert(Cow::Borrowed(key), Cow::Borrowed(value));
+        map.insert(Cow::Borrowed(key), CowValue::from(value));
+        Self { map }
+    }
+
+    pub fn from_sparse<const N: usize>(arr: [(String, SparseVector); N]) -> Self {
+        NamedVectors {
+            map: arr
+                .into_iter()
+                .map(|(k, v)| (CowKey::from(k), CowValue::from(v)))
+                .collect(),
+        }
+    }
+
+    pub fn from_sparse_ref(key: &'a str, value: &'a SparseVector) -> Self {
+        let mut map = TinyMap::new();
+        map.insert(Cow::Borrowed(key), CowValue::from(value));
         Self { map }
     }
 
@@ -48,14 +123,35 @@ impl<'a> NamedVectors<'a> {
         }
     }
 
+    pub fn from_sparse_map(map: HashMap<String, SparseVector>) -> Self {
+        Self {
+            map: map
+                .into_iter()
+                .map(|(k, v)| (CowKey::from(k), CowValue::from(v)))
+                .collect(),
+        }
+    }
+
+    pub fn from_sparse_map_ref(map: &'a HashMap<String, SparseVector>) -> Self {
+        Self {
+            map: map
+                .iter()
+                .map(|(k, v)| (CowKey::from(k), CowValue::from(v)))
+                .collect(),
+        }
+    }
+
     pub fn insert(&mut self, name: String, vector: Vec<VectorElementType>) {
-        self.map
-            .insert(CowKey::Owned(name), CowValue::Owned(vector));
+        self.map.insert(CowKey::Owned(name), CowValue::from(vector));
     }
 
     pub fn insert_ref(&mut self, name: &'a str, vector: &'a [VectorElementType]) {
         self.map
-            .insert(CowKey::Borrowed(name), CowValue::Borrowed(vector));
+            .insert(CowKey::Borrowed(name), CowValue::from(vector));
+    }
+
+    pub fn insert_sparse(&mut self, name: String, vector: SparseVector) {
+        self.map.insert(CowKey::Owned(name), CowValue::from(vector));
     }
 
     pub fn contains_key(&self, key: &str) -> bool {
@@ -74,25 +170,37 @@ impl<'a> NamedVectors<'a> {
         self.map.iter().map(|(k, _)| k.as_ref())
     }
 
-    pub fn into_default_vector(mut self) -> Option<Vec<VectorElementType>> {
+    pub fn into_default_vector(mut self) -> Option<VectorOrSparse> {
         self.map
             .get_mut(DEFAULT_VECTOR_NAME)
-            .map(|src| std::mem::take(src).into_owned())
+            .map(|src| std::mem::take(src).into_owned_vector())
     }
 
-    pub fn into_owned_map(self) -> HashMap<String, Vec<VectorElementType>> {
+    pub fn into_owned_map(self) -> HashMap<String, VectorOrSparse> {
         self.map
             .into_iter()
-            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .map(|(k, v)| (k.into_owned(), v.into_owned_vector()))
             .collect()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = (&str, &[VectorElementType])> {
-        self.map.iter().map(|(k, v)| (k.as_ref(), v.as_ref()))
+    pub fn iter(&self) -> impl Iterator<Item = (&str, VectorOrSparseRef)> {
+        self.map.iter().map(|(k, v)| (k.as_ref(), v.as_vec_ref()))
     }
 
+    /// Get dense vector by name, sparse vectors are never returned
     pub fn get(&self, key: &str) -> Option<&[VectorElementType]> {
-        self.map.get(key).map(|v| v.as_ref())
+        match self.map.get(key)? {
+            CowValue::Dense(v) => Some(v.as_ref()),
+            CowValue::Sparse(_) => None,
+        }
+    }
+
+    /// Get sparse vector by name, dense vectors are never returned
+    pub fn get_sparse(&self, key: &str) -> Option<&SparseVector> {
+        match self.map.get(key)? {
+            CowValue::Dense(_) => None,
+            CowValue::Sparse(v) => Some(v.as_ref()),
+        }
     }
 
     pub fn preprocess<F>(&mut self, distance_map: F)
@@ -101,8 +209,17 @@ impl<'a> NamedVectors<'a> {
     {
         for (name, vector) in self.map.iter_mut() {
             let distance = distance_map(name);
-            let preprocessed_vector = distance.preprocess_vector(vector.to_vec());
-            *vector = CowValue::Owned(preprocessed_vector);
+            match vector {
+                CowValue::Dense(dense) => {
+                    let preprocessed_vector = distance.preprocess_vector(dense.to_vec());
+                    *vector = CowValue::from(preprocessed_vector.into_inner());
+                }
+                CowValue::Sparse(sparse) => {
+                    let preprocessed_vector =
+                        distance.preprocess_sparse_vector(sparse.as_ref().clone());
+                    *vector = CowValue::from(preprocessed_vector);
+                }
+            }
         }
     }
 }
@@ -1,10 +1,17 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sparse::common::sparse_vector::SparseVector;
+use sparse::common::types::{DimId, DimWeight};
 
 use super::named_vectors::NamedVectors;
-use crate::common::utils::transpose_map_into_named_vector;
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::common::utils::{
+    transpose_map_into_named_sparse_vector, transpose_map_into_named_vector,
+};
+use crate::vector_storage::query::context_query::ContextQuery;
 use crate::vector_storage::query::reco_query::RecoQuery;
 
 /// Type of vector element.
@@ -15,6 +22,157 @@ pub const DEFAULT_VECTOR_NAME: &str = "";
 /// Type for vector
 pub type VectorType = Vec<VectorElementType>;
 
+/// Dense vector read from a storage, borrowed if the storage holds it as is, owned if it had to
+/// be decoded
+pub type CowVector<'a> = Cow<'a, [VectorElementType]>;
+
+/// Either a dense or a sparse vector
+///
+/// Dense vector is a plain array, sparse vector is an `{"indices": [...], "weights": [...]}`
+/// object. An empty array `[]` is always a dense vector.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum VectorOrSparse {
+    // Must go first, so untagged deserialization picks dense for arrays
+    Vector(VectorType),
+    Sparse(SparseVector),
+}
+
+pub const DENSE_TYPE_NAME: &str = "dense";
+pub const SPARSE_TYPE_NAME: &str = "sparse";
+
+impl VectorOrSparse {
+    /// Human readable vector type, used in error messages
+    pub fn type_name(&self) -> &'static str {
+        VectorOrSparseRef::from(self).type_name()
+    }
+
+    /// Same as [`VectorType::try_from`], the error names the offending vector
+    pub fn try_into_dense(self, vector_name: &str) -> OperationResult<VectorType> {
+        match self {
+            VectorOrSparse::Vector(v) => Ok(v),
+            VectorOrSparse::Sparse(_) => Err(OperationError::VectorTypeMismatch {
+                vector_name: vector_name.to_owned(),
+                expected: DENSE_TYPE_NAME,
+                got: SPARSE_TYPE_NAME,
+            }),
+        }
+    }
+
+    /// Same as [`SparseVector::try_from`], the error names the offending vector
+    pub fn try_into_sparse(self, vector_name: &str) -> OperationResult<SparseVector> {
+        match self {
+            VectorOrSparse::Vector(_) => Err(OperationError::VectorTypeMismatch {
+                vector_name: vector_name.to_owned(),
+                expected: SPARSE_TYPE_NAME,
+                got: DENSE_TYPE_NAME,
+            }),
+            VectorOrSparse::Sparse(v) => Ok(v),
+        }
+    }
+}
+
+impl From<VectorType> for VectorOrSparse {
+    fn from(v: VectorType) -> Self {
+        VectorOrSparse::Vector(v)
+    }
+}
+
+impl From<SparseVector> for VectorOrSparse {
+    fn from(v: SparseVector) -> Self {
+        VectorOrSparse::Sparse(v)
+    }
+}
+
+impl TryFrom<VectorOrSparse> for VectorType {
+    type Error = OperationError;
+
+    fn try_from(value: VectorOrSparse) -> Result<Self, Self::Error> {
+        match value {
+            VectorOrSparse::Vector(v) => Ok(v),
+            VectorOrSparse::Sparse(_) => Err(OperationError::WrongSparse),
+        }
+    }
+}
+
+impl TryFrom<VectorOrSparse> for SparseVector {
+    type Error = OperationError;
+
+    fn try_from(value: VectorOrSparse) -> Result<Self, Self::Error> {
+        match value {
+            VectorOrSparse::Vector(_) => Err(OperationError::WrongSparse),
+            VectorOrSparse::Sparse(v) => Ok(v),
+        }
+    }
+}
+
+/// Borrowed dense or sparse vector
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VectorOrSparseRef<'a> {
+    Vector(&'a [VectorElementType]),
+    Sparse(&'a SparseVector),
+}
+
+impl<'a> VectorOrSparseRef<'a> {
+    pub fn to_owned_vector(self) -> VectorOrSparse {
+        match self {
+            VectorOrSparseRef::Vector(v) => VectorOrSparse::Vector(v.to_vec()),
+            VectorOrSparseRef::Sparse(v) => VectorOrSparse::Sparse(v.clone()),
+        }
+    }
+
+    /// Human readable vector type, used in error messages
+    pub fn type_name(self) -> &'static str {
+        match self {
+            VectorOrSparseRef::Vector(_) => DENSE_TYPE_NAME,
+            VectorOrSparseRef::Sparse(_) => SPARSE_TYPE_NAME,
+        }
+    }
+}
+
+impl<'a> From<&'a [VectorElementType]> for VectorOrSparseRef<'a> {
+    fn from(v: &'a [VectorElementType]) -> Self {
+        VectorOrSparseRef::Vector(v)
+    }
+}
+
+impl<'a> From<&'a SparseVector> for VectorOrSparseRef<'a> {
+    fn from(v: &'a SparseVector) -> Self {
+        VectorOrSparseRef::Sparse(v)
+    }
+}
+
+impl<'a> From<&'a VectorOrSparse> for VectorOrSparseRef<'a> {
+    fn from(v: &'a VectorOrSparse) -> Self {
+        match v {
+            VectorOrSparse::Vector(v) => VectorOrSparseRef::Vector(v),
+            VectorOrSparse::Sparse(v) => VectorOrSparseRef::Sparse(v),
+        }
+    }
+}
+
+impl<'a> TryFrom<VectorOrSparseRef<'a>> for &'a [VectorElementType] {
+    type Error = OperationError;
+
+    fn try_from(value: VectorOrSparseRef<'a>) -> Result<Self, Self::Error> {
+        match value {
+            VectorOrSparseRef::Vector(v) => Ok(v),
+            VectorOrSparseRef::Sparse(_) => Err(OperationError::WrongSparse),
+        }
+    }
+}
+
+impl<'a> TryFrom<VectorOrSparseRef<'a>> for &'a SparseVector {
+    type Error = OperationError;
+
+    fn try_from(value: VectorOrSparseRef<'a>) -> Result<Self, Self::Error> {
+        match value {
+            VectorOrSparseRef::Vector(_) => Err(OperationError::WrongSparse),
+            VectorOrSparseRef::Sparse(v) => Ok(v),
+        }
+    }
+}
+
 pub fn default_vector(vec: Vec<VectorElementType>) -> NamedVectors<'static> {
     NamedVectors::from([(DEFAULT_VECTOR_NAME.to_owned(), vec)])
 }
@@ -23,12 +181,39 @@ pub fn only_default_vector(vec: &[VectorElementType]) -> NamedVectors {
     NamedVectors::from_ref(DEFAULT_VECTOR_NAME, vec)
 }
 
+/// Convert dense vector into sparse, keeping elements with absolute value above `threshold`
+pub fn sparse_from_dense(vec: &[VectorElementType], threshold: DimWeight) -> SparseVector {
+    let (indices, weights) = vec
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| value.abs() > threshold)
+        .map(|(idx, value)| (idx as DimId, *value))
+        .unzip();
+    SparseVector::new(indices, weights)
+}
+
+/// Convert sparse vector into dense vector of `dim` elements, missing dimensions are zero
+///
+/// Dimensions with index `dim` or above are dropped.
+pub fn dense_from_sparse(sparse: &SparseVector, dim: usize) -> VectorType {
+    let mut vec = vec![0.0; dim];
+    for (&idx, &weight) in sparse.indices.iter().zip(&sparse.weights) {
+        if let Some(value) = vec.get_mut(idx as usize) {
+            *value = weight;
+        }
+    }
+    vec
+}
+
 /// Full vector data per point separator with single and multiple vector modes
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
 #[serde(untagged, rename_all = "snake_case")]
 pub enum VectorStruct {
     Single(VectorType),
+    // Must go before `Multi`, otherwise sparse vector object is parsed as map of named vectors
+    Sparse(SparseVector),
     Multi(HashMap<String, VectorType>),
+    MultiSparse(HashMap<String, SparseVector>),
 }
 
 impl VectorStruct {
@@ -36,7 +221,9 @@ impl VectorStruct {
     pub fn is_empty(&self) -> bool {
         match self {
             VectorStruct::Single(vector) => vector.is_empty(),
+            VectorStruct::Sparse(vector) => vector.is_empty(),
             VectorStruct::Multi(vectors) => vectors.values().all(|v| v.is_empty()),
+            VectorStruct::MultiSparse(vectors) => vectors.values().all(|v| v.is_empty()),
         }
     }
 }
@@ -53,21 +240,60 @@ impl From<&[VectorElementType]> for VectorStruct {
     }
 }
 
-impl<'a> From<NamedVectors<'a>> for VectorStruct {
-    fn from(v: NamedVectors) -> Self {
+impl<'a> TryFrom<NamedVectors<'a>> for VectorStruct {
+    type Error = OperationError;
+
+    /// Dense and sparse vectors can not be mixed in a single `VectorStruct`,
+    /// so `NamedVectors` containing both is rejected.
+    fn try_from(v: NamedVectors) -> Result<Self, Self::Error> {
         if v.len() == 1 && v.contains_key(DEFAULT_VECTOR_NAME) {
-            VectorStruct::Single(v.into_default_vector().unwrap())
+            Ok(match v.into_default_vector().unwrap() {
+                VectorOrSparse::Vector(vector) => VectorStruct::Single(vector),
+                VectorOrSparse::Sparse(vector) => VectorStruct::Sparse(vector),
+            })
         } else {
-            VectorStruct::Multi(v.into_owned_map())
+            let mut dense = HashMap::new();
+            let mut sparse = HashMap::new();
+            for (name, vector) in v.into_owned_map() {
+                match vector {
+                    VectorOrSparse::Vector(vector) => {
+                        dense.insert(name, vector);
+                    }
+                    VectorOrSparse::Sparse(vector) => {
+                        sparse.insert(name, vector);
+                    }
+                }
+            }
+            match (dense.keys().min(), sparse.keys().min()) {
+                (Some(dense_name), Some(sparse_name)) => Err(OperationError::ValidationError {
+                    description: format!(
+                        "Dense and sparse vectors can't be mixed in a single vector struct, \
+                         got dense `{dense_name}` and sparse `{sparse_name}`"
+                    ),
+                }),
+                (None, Some(_)) => Ok(VectorStruct::MultiSparse(sparse)),
+                (_, None) => Ok(VectorStruct::Multi(dense)),
+            }
         }
     }
 }
 
 impl VectorStruct {
+    /// Get dense vector by name, sparse vectors are never returned
     pub fn get(&self, name: &str) -> Option<&VectorType> {
         match self {
             VectorStruct::Single(v) => (name == DEFAULT_VECTOR_NAME).then_some(v),
             VectorStruct::Multi(v) => v.get(name),
+            VectorStruct::Sparse(_) | VectorStruct::MultiSparse(_) => None,
+        }
+    }
+
+    /// Get sparse vector by name, dense vectors are never returned
+    pub fn get_sparse(&self, name: &str) -> Option<&SparseVector> {
+        match self {
+            VectorStruct::Sparse(v) => (name == DEFAULT_VECTOR_NAME).then_some(v),
+            VectorStruct::MultiSparse(v) => v.get(name),
+            VectorStruct::Single(_) | VectorStruct::Multi(_) => None,
         }
     }
 
@@ -75,6 +301,10 @@ impl VectorStruct {
         match self {
             VectorStruct::Single(v) => default_vector(v),
             VectorStruct::Multi(v) => NamedVectors::from_map(v),
+            VectorStruct::Sparse(v) => {
+                NamedVectors::from_sparse([(DEFAULT_VECTOR_NAME.to_owned(), v)])
+            }
+            VectorStruct::MultiSparse(v) => NamedVectors::from_sparse_map(v),
         }
     }
 }
@@ -89,6 +319,16 @@ pub struct NamedVector {
     pub vector: VectorType,
 }
 
+/// Sparse vector data with name
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NamedSparseVector {
+    /// Name of vector data
+    pub name: String,
+    /// Vector data
+    pub vector: SparseVector,
+}
+
 /// Vector data separator for named and unnamed modes
 /// Unnamed mode:
 ///
@@ -104,12 +344,22 @@ pub struct NamedVector {
 ///     "name": "image-embeddings"
 ///   }
 /// }
+///
+/// or named sparse mode:
+///
+/// {
+///   "vector": {
+///     "vector": { "indices": [1, 42], "weights": [0.5, 0.3] },
+///     "name": "text-embeddings"
+///   }
+/// }
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 #[serde(untagged)]
 pub enum NamedVectorStruct {
     Default(VectorType),
     Named(NamedVector),
+    Sparse(NamedSparseVector),
 }
 
 impl From<VectorType> for NamedVectorStruct {
@@ -118,14 +368,21 @@ impl From<VectorType> for NamedVectorStruct {
     }
 }
 
-impl From<NamedVectorStruct> for NamedVector {
-    fn from(v: NamedVectorStruct) -> Self {
+impl TryFrom<NamedVectorStruct> for NamedVector {
+    type Error = OperationError;
+
+    fn try_from(v: NamedVectorStruct) -> Result<Self, Self::Error> {
         match v {
-            NamedVectorStruct::Default(v) => NamedVector {
+            NamedVectorStruct::Default(v) => Ok(NamedVector {
                 name: DEFAULT_VECTOR_NAME.to_owned(),
                 vector: v,
-            },
-            NamedVectorStruct::Named(v) => v,
+            }),
+            NamedVectorStruct::Named(v) => Ok(v),
+            NamedVectorStruct::Sparse(v) => Err(OperationError::VectorTypeMismatch {
+                vector_name: v.name,
+                expected: DENSE_TYPE_NAME,
+                got: SPARSE_TYPE_NAME,
+            }),
         }
     }
 }
@@ -135,6 +392,13 @@ impl From<NamedVector> for NamedVectorStruct {
         NamedVectorStruct::Named(v)
     }
 }
+
+impl From<NamedSparseVector> for NamedVectorStruct {
+    fn from(v: NamedSparseVector) -> Self {
+        NamedVectorStruct::Sparse(v)
+    }
+}
+
 pub trait Named {
     fn get_name(&self) -> &str;
 }
@@ -144,21 +408,43 @@ impl Named for NamedVectorStruct {
         match self {
             NamedVectorStruct::Default(_) => DEFAULT_VECTOR_NAME,
             NamedVectorStruct::Named(v) => &v.name,
+            NamedVectorStruct::Sparse(v) => &v.name,
         }
     }
 }
 
 impl NamedVectorStruct {
-    pub fn get_vector(&self) -> &VectorType {
+    /// Dense vector data, `None` for sparse vector
+    pub fn get_vector(&self) -> Option<&VectorType> {
+        match self {
+            NamedVectorStruct::Default(v) => Some(v),
+            NamedVectorStruct::Named(v) => Some(&v.vector),
+            NamedVectorStruct::Sparse(_) => None,
+        }
+    }
+
+    /// Dense vector data, `None` for sparse vector
+    pub fn to_vector(self) -> Option<VectorType> {
         match self {
-            NamedVectorStruct::Default(v) => v,
-            NamedVectorStruct::Named(v) => &v.vector,
+            NamedVectorStruct::Default(v) => Some(v),
+            NamedVectorStruct::Named(v) => Some(v.vector),
+            NamedVectorStruct::Sparse(_) => None,
         }
     }
-    pub fn to_vector(self) -> VectorType {
+
+    pub fn get_vector_or_sparse(&self) -> VectorOrSparseRef {
         match self {
-            NamedVectorStruct::Default(v) => v,
-            NamedVectorStruct::Named(v) => v.vector,
+            NamedVectorStruct::Default(v) => VectorOrSparseRef::Vector(v),
+            NamedVectorStruct::Named(v) => VectorOrSparseRef::Vector(&v.vector),
+            NamedVectorStruct::Sparse(v) => VectorOrSparseRef::Sparse(&v.vector),
+        }
+    }
+
+    pub fn to_vector_or_sparse(self) -> VectorOrSparse {
+        match self {
+            NamedVectorStruct::Default(v) => VectorOrSparse::Vector(v),
+            NamedVectorStruct::Named(v) => VectorOrSparse::Vector(v.vector),
+            NamedVectorStruct::Sparse(v) => VectorOrSparse::Sparse(v.vector),
         }
     }
 }
@@ -169,6 +455,8 @@ impl NamedVectorStruct {
 pub enum BatchVectorStruct {
     Single(Vec<VectorType>),
     Multi(HashMap<String, Vec<VectorType>>),
+    SparseSingle(Vec<SparseVector>),
+    SparseMulti(HashMap<String, Vec<SparseVector>>),
 }
 
 impl From<Vec<VectorType>> for BatchVectorStruct {
@@ -187,11 +475,30 @@ impl From<HashMap<String, Vec<VectorType>>> for BatchVectorStruct {
     }
 }
 
+impl From<Vec<SparseVector>> for BatchVectorStruct {
+    fn from(v: Vec<SparseVector>) -> Self {
+        BatchVectorStruct::SparseSingle(v)
+    }
+}
+
+impl From<HashMap<String, Vec<SparseVector>>> for BatchVectorStruct {
+    fn from(v: HashMap<String, Vec<SparseVector>>) -> Self {
+        if v.len() == 1 && v.contains_key(DEFAULT_VECTOR_NAME) {
+            BatchVectorStruct::SparseSingle(v.into_iter().next().unwrap().1)
+        } else {
+            BatchVectorStruct::SparseMulti(v)
+        }
+    }
+}
+
 impl BatchVectorStruct {
     pub fn single(&mut self) -> &mut Vec<VectorType> {
         match self {
             BatchVectorStruct::Single(v) => v,
             BatchVectorStruct::Multi(v) => v.get_mut(DEFAULT_VECTOR_NAME).unwrap(),
+            BatchVectorStruct::SparseSingle(_) | BatchVectorStruct::SparseMulti(_) => {
+                panic!("BatchVectorStruct is sparse")
+            }
         }
     }
 
@@ -199,6 +506,29 @@ impl BatchVectorStruct {
         match self {
             BatchVectorStruct::Single(_) => panic!("BatchVectorStruct is not Single"),
             BatchVectorStruct::Multi(v) => v,
+            BatchVectorStruct::SparseSingle(_) | BatchVectorStruct::SparseMulti(_) => {
+                panic!("BatchVectorStruct is sparse")
+            }
+        }
+    }
+
+    pub fn sparse_single(&mut self) -> &mut Vec<SparseVector> {
+        match self {
+            BatchVectorStruct::SparseSingle(v) => v,
+            BatchVectorStruct::SparseMulti(v) => v.get_mut(DEFAULT_VECTOR_NAME).unwrap(),
+            BatchVectorStruct::Single(_) | BatchVectorStruct::Multi(_) => {
+                panic!("BatchVectorStruct is not sparse")
+            }
+        }
+    }
+
+    pub fn sparse_multi(&mut self) -> &mut HashMap<String, Vec<SparseVector>> {
+        match self {
+            BatchVectorStruct::SparseSingle(_) => panic!("BatchVectorStruct is not SparseSingle"),
+            BatchVectorStruct::SparseMulti(v) => v,
+            BatchVectorStruct::Single(_) | BatchVectorStruct::Multi(_) => {
+                panic!("BatchVectorStruct is not sparse")
+            }
         }
     }
 
@@ -212,6 +542,17 @@ impl BatchVectorStruct {
                     transpose_map_into_named_vector(named_vectors)
                 }
             }
+            BatchVectorStruct::SparseSingle(vectors) => vectors
+                .into_iter()
+                .map(|vector| NamedVectors::from_sparse([(DEFAULT_VECTOR_NAME.to_owned(), vector)]))
+                .collect(),
+            BatchVectorStruct::SparseMulti(named_vectors) => {
+                if named_vectors.is_empty() {
+                    vec![NamedVectors::default(); num_records]
+                } else {
+                    transpose_map_into_named_sparse_vector(named_vectors)
+                }
+            }
         }
     }
 }
@@ -228,26 +569,434 @@ impl Named for NamedRecoQuery {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Query to score stored vectors against
+///
+/// Serialized as an object with a single `nearest`, `recommend` or `context` key.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum QueryVector {
-    Nearest(VectorType),
-    Recommend(RecoQuery<VectorType>),
+    Nearest(VectorOrSparse),
+    Recommend(RecoQuery<VectorOrSparse>),
+    Context(ContextQuery<VectorOrSparse>),
+}
+
+impl QueryVector {
+    /// Dense vector of a nearest query
+    pub fn as_dense(&self) -> Option<&VectorType> {
+        match self {
+            QueryVector::Nearest(VectorOrSparse::Vector(vector)) => Some(vector),
+            _ => None,
+        }
+    }
+
+    /// Sparse vector of a nearest query
+    pub fn as_sparse(&self) -> Option<&SparseVector> {
+        match self {
+            QueryVector::Nearest(VectorOrSparse::Sparse(vector)) => Some(vector),
+            _ => None,
+        }
+    }
+
+    /// Dense vector of a nearest query, fails for sparse and non-nearest queries
+    pub fn try_into_dense(self) -> OperationResult<VectorType> {
+        match self {
+            QueryVector::Nearest(vector) => vector.try_into(),
+            QueryVector::Recommend(_) | QueryVector::Context(_) => Err(not_nearest_error()),
+        }
+    }
+
+    /// Sparse vector of a nearest query, fails for dense and non-nearest queries
+    pub fn try_into_sparse(self) -> OperationResult<SparseVector> {
+        match self {
+            QueryVector::Nearest(vector) => vector.try_into(),
+            QueryVector::Recommend(_) | QueryVector::Context(_) => Err(not_nearest_error()),
+        }
+    }
+}
+
+fn not_nearest_error() -> OperationError {
+    OperationError::ValidationError {
+        description: "Only nearest search query consists of a single vector".to_string(),
+    }
 }
 
 impl From<VectorType> for QueryVector {
     fn from(vec: VectorType) -> Self {
-        Self::Nearest(vec)
+        Self::Nearest(VectorOrSparse::Vector(vec))
     }
 }
 
 impl<'a> From<&'a [VectorElementType]> for QueryVector {
     fn from(vec: &'a [VectorElementType]) -> Self {
-        Self::Nearest(vec.to_vec())
+        Self::Nearest(VectorOrSparse::Vector(vec.to_vec()))
     }
 }
 
 impl<const N: usize> From<[VectorElementType; N]> for QueryVector {
     fn from(vec: [VectorElementType; N]) -> Self {
-        Self::Nearest(vec.to_vec())
+        Self::Nearest(VectorOrSparse::Vector(vec.to_vec()))
+    }
+}
+
+impl From<VectorOrSparse> for QueryVector {
+    fn from(vec: VectorOrSparse) -> Self {
+        Self::Nearest(vec)
+    }
+}
+
+impl From<SparseVector> for QueryVector {
+    fn from(vec: SparseVector) -> Self {
+        Self::Nearest(VectorOrSparse::Sparse(vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_dense_conversion() {
+        let dense = vec![0.0, 0.5, -0.01, 0.0, -2.0, 0.1];
+        let sparse = sparse_from_dense(&dense, 0.05);
+        assert_eq!(
+            sparse,
+            SparseVector::new(vec![1, 4, 5], vec![0.5, -2.0, 0.1])
+        );
+        assert_eq!(
+            dense_from_sparse(&sparse, dense.len()),
+            vec![0.0, 0.5, 0.0, 0.0, -2.0, 0.1],
+        );
+
+        // Without threshold only zeros are dropped, conversion is lossless
+        let sparse = sparse_from_dense(&dense, 0.0);
+        assert_eq!(sparse.indices, vec![1, 2, 4, 5]);
+        assert_eq!(dense_from_sparse(&sparse, dense.len()), dense);
+
+        let empty = sparse_from_dense(&[0.0; 8], 0.0);
+        assert!(empty.indices.is_empty());
+        assert!(empty.weights.is_empty());
+        assert_eq!(dense_from_sparse(&empty, 3), vec![0.0; 3]);
+
+        // Dimensions out of range are dropped
+        assert_eq!(dense_from_sparse(&sparse, 2), vec![0.0, 0.5]);
+    }
+
+    #[test]
+    fn test_named_vector_struct_serde() {
+        let dense: NamedVectorStruct =
+            serde_json::from_str(r#"{"name": "image", "vector": [1.0, 2.0]}"#).unwrap();
+        assert!(matches!(dense, NamedVectorStruct::Named(_)));
+        assert_eq!(dense.get_name(), "image");
+        assert_eq!(dense.get_vector(), Some(&vec![1.0, 2.0]));
+
+        let sparse: NamedVectorStruct = serde_json::from_str(
+            r#"{"name": "text", "vector": {"indices": [1, 42], "weights": [0.5, 0.3]}}"#,
+        )
+        .unwrap();
+        assert!(matches!(sparse, NamedVectorStruct::Sparse(_)));
+        assert_eq!(sparse.get_name(), "text");
+        assert_eq!(sparse.get_vector(), None);
+        assert_eq!(
+            sparse.get_vector_or_sparse(),
+            VectorOrSparseRef::Sparse(&SparseVector::new(vec![1, 42], vec![0.5, 0.3])),
+        );
+
+        for vector in [dense, sparse, NamedVectorStruct::Default(vec![0.5])] {
+            let json = serde_json::to_string(&vector).unwrap();
+            let parsed: NamedVectorStruct = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.get_name(), vector.get_name());
+            assert_eq!(parsed.to_vector_or_sparse(), vector.to_vector_or_sparse());
+        }
+    }
+
+    #[test]
+    fn test_query_vector_accessors() {
+        let sparse_vector = SparseVector::new(vec![1, 42], vec![0.5, 0.3]);
+        let sparse = QueryVector::from(sparse_vector.clone());
+        assert_eq!(sparse.as_sparse(), Some(&sparse_vector));
+        assert_eq!(sparse.as_dense(), None);
+        assert!(matches!(
+            sparse.clone().try_into_dense(),
+            Err(OperationError::WrongSparse)
+        ));
+        assert_eq!(sparse.try_into_sparse().unwrap(), sparse_vector);
+
+        let dense = QueryVector::from(vec![1.0, 2.0]);
+        assert_eq!(dense.as_dense(), Some(&vec![1.0, 2.0]));
+        assert_eq!(dense.as_sparse(), None);
+        assert!(matches!(
+            dense.clone().try_into_sparse(),
+            Err(OperationError::WrongSparse)
+        ));
+        assert_eq!(dense.try_into_dense().unwrap(), vec![1.0, 2.0]);
+
+        let recommend = QueryVector::from(RecoQuery::from_sparse(vec![sparse_vector], vec![]));
+        assert_eq!(recommend.as_sparse(), None);
+        assert_eq!(recommend.as_dense(), None);
+        assert!(matches!(
+            recommend.clone().try_into_sparse(),
+            Err(OperationError::ValidationError { .. })
+        ));
+        assert!(matches!(
+            recommend.try_into_dense(),
+            Err(OperationError::ValidationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_query_vector_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(QueryVector)).unwrap();
+        let definitions = &schema["definitions"];
+        let resolve = |schema: &serde_json::Value| match schema["$ref"].as_str() {
+            Some(reference) => definitions[reference.trim_start_matches("#/definitions/")].clone(),
+            None => schema.clone(),
+        };
+
+        let variants = schema["oneOf"].as_array().unwrap();
+        let variant = |name: &str| {
+            let variant = variants
+                .iter()
+                .find(|variant| variant["required"][0] == name)
+                .unwrap_or_else(|| panic!("no {name} variant in {schema}"));
+            resolve(&variant["properties"][name])
+        };
+
+        let recommend = variant("recommend");
+        for field in ["positives", "negatives"] {
+            let items = resolve(&recommend["properties"][field]["items"]);
+            // Examples are dense arrays or sparse objects
+            assert_eq!(items["anyOf"].as_array().unwrap().len(), 2, "{items}");
+        }
+        assert!(variant("context")["properties"]["pairs"].is_object());
+        assert!(variant("nearest")["anyOf"].is_array());
+
+        let query: QueryVector =
+            serde_json::from_str(r#"{"recommend": {"positives": [[1.0]], "negatives": []}}"#)
+                .unwrap();
+        assert!(matches!(query, QueryVector::Recommend(_)));
+    }
+
+    #[test]
+    fn test_vector_or_sparse_serde() {
+        let vectors = [
+            VectorOrSparse::Vector(vec![1.0, 2.0]),
+            VectorOrSparse::Vector(vec![]),
+            VectorOrSparse::Sparse(SparseVector::new(vec![1, 42], vec![0.5, 0.3])),
+            VectorOrSparse::Sparse(SparseVector::new(vec![], vec![])),
+        ];
+        for vector in vectors {
+            let json = serde_json::to_string(&vector).unwrap();
+            let parsed: VectorOrSparse = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, vector);
+        }
+
+        let empty: VectorOrSparse = serde_json::from_str("[]").unwrap();
+        assert_eq!(empty, VectorOrSparse::Vector(vec![]));
+        let empty_sparse: VectorOrSparse =
+            serde_json::from_str(r#"{"indices": [], "weights": []}"#).unwrap();
+        assert_eq!(
+            empty_sparse,
+            VectorOrSparse::Sparse(SparseVector::new(vec![], vec![])),
+        );
+    }
+
+    #[test]
+    fn test_vector_type_mismatch_names_vector() {
+        let sparse = VectorOrSparse::Sparse(SparseVector::new(vec![1], vec![0.5]));
+        let error = sparse.clone().try_into_dense("image").unwrap_err();
+        assert!(matches!(
+            error,
+            OperationError::VectorTypeMismatch {
+                expected: DENSE_TYPE_NAME,
+                got: SPARSE_TYPE_NAME,
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("`image`"));
+        assert!(sparse.try_into_sparse("image").is_ok());
+
+        let dense = VectorOrSparse::Vector(vec![1.0, 2.0]);
+        let error = dense.clone().try_into_sparse("text").unwrap_err();
+        assert!(error.to_string().contains("`text`"));
+        assert_eq!(dense.try_into_dense("text").unwrap(), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_vector_struct_get() {
+        let sparse = SparseVector {
+            indices: vec![1, 5],
+            weights: vec![0.5, 1.0],
+        };
+
+        let single = VectorStruct::Single(vec![1.0, 2.0]);
+        assert_eq!(single.get(DEFAULT_VECTOR_NAME), Some(&vec![1.0, 2.0]));
+        assert_eq!(single.get("other"), None);
+        assert_eq!(single.get_sparse(DEFAULT_VECTOR_NAME), None);
+
+        let multi = VectorStruct::Multi(HashMap::from([("image".to_string(), vec![3.0])]));
+        assert_eq!(multi.get("image"), Some(&vec![3.0]));
+        assert_eq!(multi.get(DEFAULT_VECTOR_NAME), None);
+        assert_eq!(multi.get_sparse("image"), None);
+
+        let single_sparse = VectorStruct::Sparse(sparse.clone());
+        assert_eq!(single_sparse.get_sparse(DEFAULT_VECTOR_NAME), Some(&sparse));
+        assert_eq!(single_sparse.get_sparse("other"), None);
+        assert_eq!(single_sparse.get(DEFAULT_VECTOR_NAME), None);
+
+        let multi_sparse =
+            VectorStruct::MultiSparse(HashMap::from([("text".to_string(), sparse.clone())]));
+        assert_eq!(multi_sparse.get_sparse("text"), Some(&sparse));
+        assert_eq!(multi_sparse.get_sparse(DEFAULT_VECTOR_NAME), None);
+        assert_eq!(multi_sparse.get("text"), None);
+    }
+
+    #[test]
+    fn test_vector_struct_sparse_into_all_vectors() {
+        let sparse = SparseVector {
+            indices: vec![1, 5],
+            weights: vec![0.5, 1.0],
+        };
+
+        let all_vectors = VectorStruct::Sparse(sparse.clone()).into_all_vectors();
+        assert_eq!(all_vectors.len(), 1);
+        assert_eq!(all_vectors.get_sparse(DEFAULT_VECTOR_NAME), Some(&sparse));
+        assert_eq!(all_vectors.get(DEFAULT_VECTOR_NAME), None);
+        assert_eq!(
+            VectorStruct::try_from(all_vectors).unwrap(),
+            VectorStruct::Sparse(sparse.clone()),
+        );
+
+        let other = SparseVector {
+            indices: vec![2],
+            weights: vec![0.1],
+        };
+        let multi_sparse = HashMap::from([
+            ("a".to_string(), sparse.clone()),
+            ("b".to_string(), other.clone()),
+        ]);
+        let all_vectors = VectorStruct::MultiSparse(multi_sparse.clone()).into_all_vectors();
+        assert_eq!(all_vectors.len(), 2);
+        assert_eq!(all_vectors.get_sparse("a"), Some(&sparse));
+        assert_eq!(all_vectors.get_sparse("b"), Some(&other));
+        assert_eq!(
+            VectorStruct::try_from(all_vectors).unwrap(),
+            VectorStruct::MultiSparse(multi_sparse),
+        );
+    }
+
+    #[test]
+    fn test_vector_struct_try_from_named_vectors() {
+        let sparse = SparseVector {
+            indices: vec![1, 5],
+            weights: vec![0.5, 1.0],
+        };
+
+        let dense = NamedVectors::from([
+            ("image".to_string(), vec![1.0, 2.0]),
+            ("audio".to_string(), vec![3.0]),
+        ]);
+        assert_eq!(
+            VectorStruct::try_from(dense).unwrap(),
+            VectorStruct::Multi(HashMap::from([
+                ("image".to_string(), vec![1.0, 2.0]),
+                ("audio".to_string(), vec![3.0]),
+            ])),
+        );
+        assert_eq!(
+            VectorStruct::try_from(only_default_vector(&[1.0, 2.0])).unwrap(),
+            VectorStruct::Single(vec![1.0, 2.0]),
+        );
+
+        let all_sparse = NamedVectors::from_sparse([
+            ("text".to_string(), sparse.clone()),
+            ("title".to_string(), sparse.clone()),
+        ]);
+        assert_eq!(
+            VectorStruct::try_from(all_sparse).unwrap(),
+            VectorStruct::MultiSparse(HashMap::from([
+                ("text".to_string(), sparse.clone()),
+                ("title".to_string(), sparse.clone()),
+            ])),
+        );
+
+        let mut mixed = NamedVectors::from([("image".to_string(), vec![1.0, 2.0])]);
+        mixed.insert_sparse("text".to_string(), sparse);
+        let error = VectorStruct::try_from(mixed).unwrap_err();
+        assert!(matches!(error, OperationError::ValidationError { .. }));
+        assert!(error.to_string().contains("`image`"));
+        assert!(error.to_string().contains("`text`"));
+
+        assert_eq!(
+            VectorStruct::try_from(NamedVectors::default()).unwrap(),
+            VectorStruct::Multi(HashMap::new()),
+        );
+    }
+
+    #[test]
+    fn test_batch_vector_struct_sparse_into_all_vectors() {
+        let vectors_a = vec![
+            SparseVector {
+                indices: vec![0, 3],
+                weights: vec![0.1, 0.2],
+            },
+            SparseVector {
+                indices: vec![7],
+                weights: vec![0.3],
+            },
+        ];
+        let vectors_b = vec![
+            SparseVector {
+                indices: vec![],
+                weights: vec![],
+            },
+            SparseVector {
+                indices: vec![1, 2],
+                weights: vec![0.4, 0.5],
+            },
+        ];
+        let batch = BatchVectorStruct::from(HashMap::from([
+            ("a".to_string(), vectors_a.clone()),
+            ("b".to_string(), vectors_b.clone()),
+        ]));
+        assert!(matches!(batch, BatchVectorStruct::SparseMulti(_)));
+
+        let all_vectors = batch.into_all_vectors(2);
+        assert_eq!(all_vectors.len(), 2);
+        for (i, named_vectors) in all_vectors.into_iter().enumerate() {
+            assert_eq!(named_vectors.get_sparse("a"), Some(&vectors_a[i]));
+            assert_eq!(named_vectors.get_sparse("b"), Some(&vectors_b[i]));
+            assert_eq!(
+                VectorStruct::try_from(named_vectors).unwrap(),
+                VectorStruct::MultiSparse(HashMap::from([
+                    ("a".to_string(), vectors_a[i].clone()),
+                    ("b".to_string(), vectors_b[i].clone()),
+                ])),
+            );
+        }
+
+        let mut batch = BatchVectorStruct::from(vectors_a.clone());
+        assert_eq!(batch.sparse_single(), &vectors_a);
+        let all_vectors = batch.into_all_vectors(2);
+        assert_eq!(
+            all_vectors[1].get_sparse(DEFAULT_VECTOR_NAME),
+            Some(&vectors_a[1])
+        );
+
+        let all_vectors = BatchVectorStruct::SparseMulti(HashMap::new()).into_all_vectors(3);
+        assert_eq!(all_vectors, vec![NamedVectors::default(); 3]);
+    }
+
+    #[test]
+    fn test_vector_struct_deserialize_sparse() {
+        let vector: VectorStruct =
+            serde_json::from_str(r#"{"indices": [1, 5], "weights": [0.5, 1.0]}"#).unwrap();
+        assert!(matches!(vector, VectorStruct::Sparse(_)));
+
+        let vector: VectorStruct =
+            serde_json::from_str(r#"{"text": {"indices": [1], "weights": [0.5]}}"#).unwrap();
+        assert!(matches!(vector, VectorStruct::MultiSparse(_)));
+
+        let vector: VectorStruct = serde_json::from_str(r#"{"image": [1.0, 2.0]}"#).unwrap();
+        assert!(matches!(vector, VectorStruct::Multi(_)));
     }
 }
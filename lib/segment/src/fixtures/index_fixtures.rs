@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
@@ -9,7 +10,7 @@ use rand::Rng;
 
 use crate::common::operation_error::OperationResult;
 use crate::common::Flusher;
-use crate::data_types::vectors::{VectorElementType, VectorType};
+use crate::data_types::vectors::{CowVector, VectorElementType, VectorType};
 use crate::payload_storage::FilterContext;
 use crate::spaces::metric::Metric;
 use crate::types::{Distance, QuantizationConfig};
@@ -51,8 +52,8 @@ impl<TMetric: Metric> VectorStorage for TestRawScorerProducer<TMetric> {
         self.vectors.len()
     }
 
-    fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
-        self.vectors.get(key)
+    fn get_vector(&self, key: PointOffsetType) -> CowVector<'_> {
+        Cow::Borrowed(self.vectors.get(key))
     }
 
     fn insert_vector(
@@ -124,14 +125,22 @@ where
     where
         R: Rng + ?Sized,
     {
-        let mut vectors = ChunkedVectors::new(dim);
-        for _ in 0..num_vectors {
-            let rnd_vec = random_vector(rng, dim);
-            let rnd_vec = TMetric::preprocess(rnd_vec);
-            vectors.push(&rnd_vec).unwrap();
+        let vectors = (0..num_vectors).map(|_| random_vector(rng, dim)).collect();
+        Self::from_vectors(dim, vectors)
+    }
+
+    /// Build producer from explicit vectors, e.g. to assert exact scores
+    ///
+    /// Vectors are preprocessed with the metric, same as random ones in [`Self::new`].
+    pub fn from_vectors(dim: usize, vectors: Vec<VectorType>) -> Self {
+        let num_vectors = vectors.len();
+        let mut chunked_vectors = ChunkedVectors::new(dim);
+        for vector in vectors {
+            let vector = TMetric::preprocess(vector);
+            chunked_vectors.push(vector.as_slice()).unwrap();
         }
         TestRawScorerProducer::<TMetric> {
-            vectors,
+            vectors: chunked_vectors,
             deleted_points: BitVec::repeat(false, num_vectors),
             deleted_vectors: BitVec::repeat(false, num_vectors),
             metric: PhantomData,
@@ -139,12 +148,14 @@ where
     }
 
     pub fn get_raw_scorer(&self, query: VectorType) -> Box<dyn RawScorer + '_> {
-        let query = TMetric::preprocess(query).into();
+        let query = TMetric::preprocess(query).into_inner().into();
         raw_scorer_impl(
             query,
             self,
             self.deleted_vector_bitslice(),
             &DEFAULT_STOPPED,
+            None,
         )
+        .unwrap()
     }
 }
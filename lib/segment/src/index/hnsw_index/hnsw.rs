@@ -188,19 +188,19 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
                 .try_for_each(|block_point_id| {
                     check_process_stopped(stopped)?;
 
-                    let vector = vector_storage.get_vector(block_point_id).into();
+                    let vector = vector_storage.get_vector(block_point_id).as_ref().into();
                     let raw_scorer = match vector_storage.quantized_storage() {
                         Some(quantized_storage) => quantized_storage.raw_scorer(
                             vector,
                             id_tracker.deleted_point_bitslice(),
                             deleted_bitslice,
                             stopped,
-                        ),
+                        )?,
                         None => new_raw_scorer(
                             vector,
                             &vector_storage,
                             id_tracker.deleted_point_bitslice(),
-                        ),
+                        )?,
                     };
                     let block_condition_checker = BuildConditionChecker {
                         filter_list: block_filter_list,
@@ -222,7 +222,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         top: usize,
         params: Option<&SearchParams>,
         is_stopped: &AtomicBool,
-    ) -> Vec<ScoredPointOffset> {
+    ) -> OperationResult<Vec<ScoredPointOffset>> {
         let ef = params
             .and_then(|params| params.hnsw_ef)
             .unwrap_or(self.config.ef);
@@ -239,7 +239,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
             id_tracker.deref(),
             params,
             is_stopped,
-        );
+        )?;
         let oversampled_top =
             Self::get_oversampled_top(vector_storage.quantized_storage(), params, top);
 
@@ -251,7 +251,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
                 let search_result = graph.search(oversampled_top, ef, points_scorer);
                 self.postprocess_search_result(search_result, vector, params, top, is_stopped)
             }
-            None => Default::default(),
+            None => Ok(Default::default()),
         }
     }
 
@@ -262,7 +262,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         top: usize,
         params: Option<&SearchParams>,
         is_stopped: &AtomicBool,
-    ) -> Vec<Vec<ScoredPointOffset>> {
+    ) -> OperationResult<Vec<Vec<ScoredPointOffset>>> {
         vectors
             .iter()
             .map(|vector| self.search_with_graph(vector, filter, top, params, is_stopped))
@@ -276,7 +276,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         top: usize,
         params: Option<&SearchParams>,
         is_stopped: &AtomicBool,
-    ) -> Vec<ScoredPointOffset> {
+    ) -> OperationResult<Vec<ScoredPointOffset>> {
         let id_tracker = self.id_tracker.borrow();
         let payload_index = self.payload_index.borrow();
         let vector_storage = self.vector_storage.borrow();
@@ -289,7 +289,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
             id_tracker.deref(),
             params,
             is_stopped,
-        );
+        )?;
         let oversampled_top =
             Self::get_oversampled_top(vector_storage.quantized_storage(), params, top);
 
@@ -307,7 +307,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         top: usize,
         params: Option<&SearchParams>,
         is_stopped: &AtomicBool,
-    ) -> Vec<Vec<ScoredPointOffset>> {
+    ) -> OperationResult<Vec<Vec<ScoredPointOffset>>> {
         vectors
             .iter()
             .map(|vector| self.search_plain(vector, filter, top, params, is_stopped))
@@ -332,7 +332,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         id_tracker: &'a dyn IdTracker,
         params: Option<&SearchParams>,
         is_stopped: &'a AtomicBool,
-    ) -> Box<dyn RawScorer + 'a> {
+    ) -> OperationResult<Box<dyn RawScorer + 'a>> {
         let quantization_enabled = Self::is_quantized_search(quantized_storage, params);
         match quantized_storage {
             Some(quantized_storage) if quantization_enabled => quantized_storage.raw_scorer(
@@ -377,7 +377,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         params: Option<&SearchParams>,
         top: usize,
         is_stopped: &AtomicBool,
-    ) -> Vec<ScoredPointOffset> {
+    ) -> OperationResult<Vec<ScoredPointOffset>> {
         let id_tracker = self.id_tracker.borrow();
         let vector_storage = self.vector_storage.borrow();
         let quantized_storage = vector_storage.quantized_storage();
@@ -399,7 +399,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
                 &vector_storage,
                 id_tracker.deleted_point_bitslice(),
                 is_stopped,
-            );
+            )?;
 
             let mut ids_iterator = search_result.iter().map(|x| x.idx);
             let mut re_scored = raw_scorer.score_points_unfiltered(&mut ids_iterator);
@@ -411,7 +411,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
             search_result
         };
         postprocess_result.truncate(top);
-        postprocess_result
+        Ok(postprocess_result)
     }
 }
 
@@ -429,7 +429,7 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
         top: usize,
         params: Option<&SearchParams>,
         is_stopped: &AtomicBool,
-    ) -> Vec<Vec<ScoredPointOffset>> {
+    ) -> OperationResult<Vec<Vec<ScoredPointOffset>>> {
         let exact = params.map(|params| params.exact).unwrap_or(false);
         match filter {
             None => {
@@ -459,7 +459,7 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
                                 id_tracker.deleted_point_bitslice(),
                                 is_stopped,
                             )
-                            .peek_top_all(top)
+                            .map(|scorer| scorer.peek_top_all(top))
                         })
                         .collect()
                 } else {
@@ -596,19 +596,22 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
             pool.install(|| {
                 ids.into_par_iter().try_for_each(|vector_id| {
                     check_process_stopped(stopped)?;
-                    let vector = vector_storage.get_vector(vector_id).into();
-                    let raw_scorer = if let Some(quantized_storage) =
-                        vector_storage.quantized_storage()
-                    {
-                        quantized_storage.raw_scorer(
-                            vector,
-                            id_tracker.deleted_point_bitslice(),
-                            vector_storage.deleted_vector_bitslice(),
-                            stopped,
-                        )
-                    } else {
-                        new_raw_scorer(vector, &vector_storage, id_tracker.deleted_point_bitslice())
-                    };
+                    let vector = vector_storage.get_vector(vector_id).as_ref().into();
+                    let raw_scorer =
+                        if let Some(quantized_storage) = vector_storage.quantized_storage() {
+                            quantized_storage.raw_scorer(
+                                vector,
+                                id_tracker.deleted_point_bitslice(),
+                                vector_storage.deleted_vector_bitslice(),
+                                stopped,
+                            )?
+                        } else {
+                            new_raw_scorer(
+                                vector,
+                                &vector_storage,
+                                id_tracker.deleted_point_bitslice(),
+                            )?
+                        };
                     let points_scorer = FilteredScorer::new(raw_scorer.as_ref(), None);
 
                     graph_layers_builder.link_new_point(vector_id, points_scorer);
@@ -21,7 +21,8 @@ use crate::common::operation_error::{
 };
 use crate::common::version::{StorageVersion, VERSION_FILE};
 use crate::common::{
-    check_named_vectors, check_query_vectors, check_stopped, check_vector, check_vector_name,
+    check_dense_named_vectors, check_named_vectors, check_query_vectors, check_stopped_with,
+    check_vector, check_vector_name, delete_point_vectors,
 };
 use crate::data_types::named_vectors::NamedVectors;
 use crate::data_types::vectors::{QueryVector, VectorElementType};
@@ -130,6 +131,7 @@ impl Segment {
     ) -> OperationResult<()> {
         debug_assert!(self.is_appendable());
         check_named_vectors(&vectors, &self.segment_config)?;
+        check_dense_named_vectors(&vectors)?;
         for (vector_name, vector_data) in self.vector_data.iter_mut() {
             let vector = vectors.get(vector_name);
             match vector {
@@ -167,12 +169,13 @@ impl Segment {
     ) -> OperationResult<()> {
         debug_assert!(self.is_appendable());
         check_named_vectors(&vectors, &self.segment_config)?;
+        check_dense_named_vectors(&vectors)?;
         for (vector_name, new_vector) in vectors {
             let vector_data = &self.vector_data[vector_name.as_ref()];
             vector_data
                 .vector_storage
                 .borrow_mut()
-                .insert_vector(internal_id, new_vector.as_ref())?;
+                .insert_vector(internal_id, new_vector.as_vec_ref().try_into()?)?;
         }
         Ok(())
     }
@@ -189,6 +192,7 @@ impl Segment {
     ) -> OperationResult<PointOffsetType> {
         debug_assert!(self.is_appendable());
         check_named_vectors(&vectors, &self.segment_config)?;
+        check_dense_named_vectors(&vectors)?;
         let new_index = self.id_tracker.borrow().total_point_count() as PointOffsetType;
         for (vector_name, vector_data) in self.vector_data.iter_mut() {
             let vector_opt = vectors.get(vector_name);
@@ -664,15 +668,22 @@ impl Segment {
                 internal_ids_to_delete.len(),
             );
 
+            let mut vector_storages: Vec<_> = self
+                .vector_data
+                .values()
+                .map(|vector_data| vector_data.vector_storage.borrow_mut())
+                .collect();
+            let mut vector_storages: Vec<&mut VectorStorageEnum> = vector_storages
+                .iter_mut()
+                .map(|storage| &mut **storage)
+                .collect();
+
             for internal_id in &internal_ids_to_delete {
                 // Drop removed points from payload index
                 self.payload_index.borrow_mut().drop(*internal_id)?;
 
                 // Drop removed points from vector storage
-                for vector_data in self.vector_data.values() {
-                    let mut vector_storage = vector_data.vector_storage.borrow_mut();
-                    vector_storage.delete_vector(*internal_id)?;
-                }
+                delete_point_vectors(&mut vector_storages, *internal_id)?;
             }
 
             // We do not drop version here, because it is already not loaded into memory.
@@ -744,13 +755,15 @@ impl SegmentEntry for Segment {
     ) -> OperationResult<Vec<ScoredPoint>> {
         check_vector(vector_name, vector, &self.segment_config)?;
         let vector_data = &self.vector_data[vector_name];
-        let internal_result =
-            &vector_data
-                .vector_index
-                .borrow()
-                .search(&[vector], filter, top, params, is_stopped)[0];
+        let internal_result = &vector_data.vector_index.borrow().search(
+            &[vector],
+            filter,
+            top,
+            params,
+            is_stopped,
+        )?[0];
 
-        check_stopped(is_stopped)?;
+        check_stopped_with(is_stopped, "Search")?;
         self.process_search_result(internal_result, with_payload, with_vector)
     }
 
@@ -773,9 +786,9 @@ impl SegmentEntry for Segment {
             top,
             params,
             is_stopped,
-        );
+        )?;
 
-        check_stopped(is_stopped)?;
+        check_stopped_with(is_stopped, "Batch search")?;
 
         let res = internal_results
             .iter()
@@ -795,6 +808,7 @@ impl SegmentEntry for Segment {
     ) -> OperationResult<bool> {
         debug_assert!(self.is_appendable());
         check_named_vectors(&vectors, &self.segment_config)?;
+        check_dense_named_vectors(&vectors)?;
         vectors.preprocess(|name| self.segment_config.vector_data[name].distance);
         let stored_internal_point = self.id_tracker.borrow().internal_id(point_id);
         self.handle_version_and_failure(op_num, stored_internal_point, |segment| {
@@ -848,6 +862,7 @@ impl SegmentEntry for Segment {
         mut vectors: NamedVectors,
     ) -> OperationResult<bool> {
         check_named_vectors(&vectors, &self.segment_config)?;
+        check_dense_named_vectors(&vectors)?;
         vectors.preprocess(|name| self.segment_config.vector_data[name].distance);
         let internal_id = self.id_tracker.borrow().internal_id(point_id);
         match internal_id {
@@ -1584,6 +1599,10 @@ mod tests {
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -1656,6 +1675,10 @@ mod tests {
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -1747,6 +1770,10 @@ mod tests {
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -1838,6 +1865,10 @@ mod tests {
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -1869,6 +1900,10 @@ mod tests {
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -1963,6 +1998,10 @@ mod tests {
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -2015,6 +2054,10 @@ mod tests {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
                 (
@@ -2025,6 +2068,10 @@ mod tests {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
             ]),
@@ -2120,6 +2167,10 @@ mod tests {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
                 (
@@ -2130,6 +2181,10 @@ mod tests {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
             ]),
@@ -2195,7 +2250,7 @@ mod tests {
         let wrong_names = vec!["aa", "bb", ""];
 
         for (vector_name, vector) in wrong_vectors_single.iter() {
-            let query_vector = vector.to_owned().into();
+            let query_vector = vector.to_owned_vector().into();
             check_vector(vector_name, &query_vector, &config)
                 .err()
                 .unwrap();
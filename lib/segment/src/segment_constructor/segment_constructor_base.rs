@@ -30,7 +30,9 @@ use crate::types::{
     VectorStorageType,
 };
 use crate::vector_storage::appendable_mmap_vector_storage::open_appendable_memmap_vector_storage;
-use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage;
+use crate::vector_storage::memmap_vector_storage::{
+    open_memmap_vector_storage_with_cache, open_memmap_vector_storage_with_datatype,
+};
 use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
 use crate::vector_storage::VectorStorage;
 
@@ -112,11 +114,21 @@ fn create_segment(
                 )?
             }
             // Mmap on disk, not appendable
-            VectorStorageType::Mmap => open_memmap_vector_storage(
-                &vector_storage_path,
-                vector_config.size,
-                vector_config.distance,
-            )?,
+            VectorStorageType::Mmap => match vector_config.vector_cache_size {
+                Some(cache_capacity) => open_memmap_vector_storage_with_cache(
+                    &vector_storage_path,
+                    vector_config.size,
+                    vector_config.distance,
+                    vector_config.datatype,
+                    cache_capacity,
+                )?,
+                None => open_memmap_vector_storage_with_datatype(
+                    &vector_storage_path,
+                    vector_config.size,
+                    vector_config.distance,
+                    vector_config.datatype,
+                )?,
+            },
             // Chunked mmap on disk, appendable
             VectorStorageType::ChunkedMmap => open_appendable_memmap_vector_storage(
                 &vector_storage_path,
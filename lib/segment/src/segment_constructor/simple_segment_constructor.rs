@@ -29,6 +29,10 @@ pub fn build_simple_segment(
                     storage_type: VectorStorageType::Memory,
                     index: Indexes::Plain {},
                     quantization_config: None,
+                    max_sparse_dimension: None,
+                    datatype: Default::default(),
+                    vector_cache_size: None,
+                    strict_vector_checks: false,
                 },
             )]),
             payload_storage_type: Default::default(),
@@ -52,6 +56,10 @@ pub fn build_multivec_segment(
             storage_type: VectorStorageType::Memory,
             index: Indexes::Plain {},
             quantization_config: None,
+            max_sparse_dimension: None,
+            datatype: Default::default(),
+            vector_cache_size: None,
+            strict_vector_checks: false,
         },
     );
     vectors_config.insert(
@@ -62,6 +70,10 @@ pub fn build_multivec_segment(
             storage_type: VectorStorageType::Memory,
             index: Indexes::Plain {},
             quantization_config: None,
+            max_sparse_dimension: None,
+            datatype: Default::default(),
+            vector_cache_size: None,
+            strict_vector_checks: false,
         },
     );
 
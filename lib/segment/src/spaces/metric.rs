@@ -1,4 +1,7 @@
+use std::ops::Deref;
+
 use common::types::ScoreType;
+use sparse::common::sparse_vector::SparseVector;
 
 use crate::data_types::vectors::{VectorElementType, VectorType};
 use crate::types::Distance;
@@ -12,8 +15,51 @@ pub trait Metric {
 
     /// Necessary vector transformations performed before adding it to the collection (like normalization)
     /// If no transformation is needed - returns the same vector
-    fn preprocess(vector: VectorType) -> VectorType;
+    fn preprocess(vector: VectorType) -> PreprocessedVector;
 
     /// correct metric score for displaying
     fn postprocess(score: ScoreType) -> ScoreType;
 }
+
+/// Extends [`Metric`] to sparse vectors
+pub trait SparseMetric: Metric {
+    /// Same as [`Metric::preprocess`], performed before adding sparse vector to the storage
+    fn preprocess_sparse(vector: SparseVector) -> SparseVector;
+}
+
+/// Vector transformed with [`Metric::preprocess`], ready to be compared with stored vectors
+///
+/// Can only be obtained by preprocessing, so a raw query can't be passed where scorers
+/// expect a preprocessed one:
+///
+/// ```compile_fail
+/// use segment::spaces::metric::PreprocessedVector;
+///
+/// let raw: PreprocessedVector = vec![1.0, 2.0];
+/// ```
+///
+/// ```compile_fail
+/// use segment::spaces::metric::PreprocessedVector;
+///
+/// let raw = PreprocessedVector(vec![1.0, 2.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreprocessedVector(pub(super) VectorType);
+
+impl PreprocessedVector {
+    pub fn as_slice(&self) -> &[VectorElementType] {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> VectorType {
+        self.0
+    }
+}
+
+impl Deref for PreprocessedVector {
+    type Target = [VectorElementType];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -1,6 +1,7 @@
 use common::types::ScoreType;
+use sparse::common::sparse_vector::SparseVector;
 
-use super::metric::Metric;
+use super::metric::{Metric, PreprocessedVector, SparseMetric};
 #[cfg(target_arch = "x86_64")]
 use super::simple_avx::*;
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
@@ -29,6 +30,19 @@ pub struct CosineMetric;
 #[derive(Clone)]
 pub struct EuclidMetric;
 
+#[derive(Clone)]
+pub struct L1Metric;
+
+/// Number of positions in which vectors differ, when treated as bit vectors
+///
+/// Element is a set bit if it is greater than zero, same as in binary quantization.
+#[derive(Clone)]
+pub struct HammingMetric;
+
+/// Largest absolute difference of coordinates, L-infinity distance
+#[derive(Clone)]
+pub struct ChebyshevMetric;
+
 impl Metric for EuclidMetric {
     fn distance() -> Distance {
         Distance::Euclid
@@ -62,8 +76,8 @@ impl Metric for EuclidMetric {
         euclid_similarity(v1, v2)
     }
 
-    fn preprocess(vector: VectorType) -> VectorType {
-        vector
+    fn preprocess(vector: VectorType) -> PreprocessedVector {
+        PreprocessedVector(vector)
     }
 
     fn postprocess(score: ScoreType) -> ScoreType {
@@ -71,6 +85,60 @@ impl Metric for EuclidMetric {
     }
 }
 
+impl Metric for L1Metric {
+    fn distance() -> Distance {
+        Distance::L1
+    }
+
+    fn similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+        l1_similarity(v1, v2)
+    }
+
+    fn preprocess(vector: VectorType) -> PreprocessedVector {
+        PreprocessedVector(vector)
+    }
+
+    fn postprocess(score: ScoreType) -> ScoreType {
+        score.abs()
+    }
+}
+
+impl Metric for HammingMetric {
+    fn distance() -> Distance {
+        Distance::Hamming
+    }
+
+    fn similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+        hamming_similarity(v1, v2)
+    }
+
+    fn preprocess(vector: VectorType) -> PreprocessedVector {
+        PreprocessedVector(vector)
+    }
+
+    fn postprocess(score: ScoreType) -> ScoreType {
+        score.abs()
+    }
+}
+
+impl Metric for ChebyshevMetric {
+    fn distance() -> Distance {
+        Distance::Chebyshev
+    }
+
+    fn similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+        chebyshev_similarity(v1, v2)
+    }
+
+    fn preprocess(vector: VectorType) -> PreprocessedVector {
+        PreprocessedVector(vector)
+    }
+
+    fn postprocess(score: ScoreType) -> ScoreType {
+        score.abs()
+    }
+}
+
 impl Metric for DotProductMetric {
     fn distance() -> Distance {
         Distance::Dot
@@ -104,8 +172,8 @@ impl Metric for DotProductMetric {
         dot_similarity(v1, v2)
     }
 
-    fn preprocess(vector: VectorType) -> VectorType {
-        vector
+    fn preprocess(vector: VectorType) -> PreprocessedVector {
+        PreprocessedVector(vector)
     }
 
     fn postprocess(score: ScoreType) -> ScoreType {
@@ -113,6 +181,12 @@ impl Metric for DotProductMetric {
     }
 }
 
+impl SparseMetric for DotProductMetric {
+    fn preprocess_sparse(vector: SparseVector) -> SparseVector {
+        vector
+    }
+}
+
 impl Metric for CosineMetric {
     fn distance() -> Distance {
         Distance::Cosine
@@ -146,21 +220,21 @@ impl Metric for CosineMetric {
         dot_similarity(v1, v2)
     }
 
-    fn preprocess(vector: VectorType) -> VectorType {
+    fn preprocess(vector: VectorType) -> PreprocessedVector {
         #[cfg(target_arch = "x86_64")]
         {
             if is_x86_feature_detected!("avx")
                 && is_x86_feature_detected!("fma")
                 && vector.len() >= MIN_DIM_SIZE_AVX
             {
-                return unsafe { cosine_preprocess_avx(vector) };
+                return PreprocessedVector(unsafe { cosine_preprocess_avx(vector) });
             }
         }
 
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
         {
             if is_x86_feature_detected!("sse") && vector.len() >= MIN_DIM_SIZE_SIMD {
-                return unsafe { cosine_preprocess_sse(vector) };
+                return PreprocessedVector(unsafe { cosine_preprocess_sse(vector) });
             }
         }
 
@@ -168,11 +242,11 @@ impl Metric for CosineMetric {
         {
             if std::arch::is_aarch64_feature_detected!("neon") && vector.len() >= MIN_DIM_SIZE_SIMD
             {
-                return unsafe { cosine_preprocess_neon(vector) };
+                return PreprocessedVector(unsafe { cosine_preprocess_neon(vector) });
             }
         }
 
-        cosine_preprocess(vector)
+        PreprocessedVector(cosine_preprocess(vector))
     }
 
     fn postprocess(score: ScoreType) -> ScoreType {
@@ -180,6 +254,13 @@ impl Metric for CosineMetric {
     }
 }
 
+impl SparseMetric for CosineMetric {
+    fn preprocess_sparse(mut vector: SparseVector) -> SparseVector {
+        vector.normalize();
+        vector
+    }
+}
+
 pub fn euclid_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
     let s: ScoreType = v1
         .iter()
@@ -190,6 +271,35 @@ pub fn euclid_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) ->
     -s
 }
 
+pub fn l1_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+    let s: ScoreType = v1
+        .iter()
+        .copied()
+        .zip(v2.iter().copied())
+        .map(|(a, b)| (a - b).abs())
+        .sum();
+    -s
+}
+
+pub fn hamming_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+    let differences = v1
+        .iter()
+        .zip(v2)
+        .filter(|(a, b)| (**a > 0.0) != (**b > 0.0))
+        .count();
+    -(differences as ScoreType)
+}
+
+pub fn chebyshev_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+    let s: ScoreType = v1
+        .iter()
+        .copied()
+        .zip(v2.iter().copied())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, ScoreType::max);
+    -s
+}
+
 pub fn cosine_preprocess(vector: VectorType) -> VectorType {
     let mut length: f32 = vector.iter().map(|x| x * x).sum();
     if length < f32::EPSILON {
@@ -206,10 +316,111 @@ pub fn dot_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> Sco
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::index_fixtures::TestRawScorerProducer;
 
     #[test]
     fn test_cosine_preprocessing() {
         let res = CosineMetric::preprocess(vec![0.0, 0.0, 0.0, 0.0]);
-        assert_eq!(res, vec![0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(res.into_inner(), vec![0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_sparse_preprocess() {
+        let vector = SparseVector::new(vec![1, 4, 9], vec![3.0, 0.0, 4.0]);
+        assert_eq!(DotProductMetric::preprocess_sparse(vector.clone()), vector);
+
+        let normalized = CosineMetric::preprocess_sparse(vector.clone());
+        assert_eq!(normalized.indices, vector.indices);
+        assert_eq!(normalized.weights, vec![0.6, 0.0, 0.8]);
+
+        // Zero vector can't be normalized
+        let zero = SparseVector::new(vec![2], vec![0.0]);
+        assert_eq!(CosineMetric::preprocess_sparse(zero.clone()), zero);
+
+        assert_eq!(
+            Distance::Cosine.preprocess_sparse_vector(vector.clone()),
+            normalized
+        );
+        assert_eq!(
+            Distance::Dot.preprocess_sparse_vector(vector.clone()),
+            vector
+        );
+    }
+
+    #[test]
+    fn test_l1_metric() {
+        let cases: [(&[VectorElementType], &[VectorElementType], ScoreType); 4] = [
+            (&[0.0, 0.0], &[0.0, 0.0], 0.0),
+            (&[1.0, 2.0, 3.0], &[4.0, 0.0, 3.0], 5.0),
+            (&[-1.0, 0.5], &[1.0, -0.5], 3.0),
+            (&[0.25, -2.0, 8.0, 1.0], &[0.0, 2.0, 6.0, 1.0], 6.25),
+        ];
+        for (v1, v2, expected) in cases {
+            let similarity = L1Metric::similarity(v1, v2);
+            assert_eq!(similarity, -expected);
+            assert_eq!(L1Metric::similarity(v2, v1), similarity);
+            assert_eq!(L1Metric::postprocess(similarity), expected);
+        }
+
+        let vector = vec![1.0, -2.0, 3.0];
+        assert_eq!(L1Metric::preprocess(vector.clone()).into_inner(), vector);
+    }
+
+    #[test]
+    fn test_hamming_metric() {
+        let cases: [(&[VectorElementType], &[VectorElementType], ScoreType); 4] = [
+            (&[0.0, 0.0], &[0.0, 0.0], 0.0),
+            (&[1.0, 0.0, 1.0, 1.0], &[1.0, 1.0, 0.0, 1.0], 2.0),
+            (&[1.0, 1.0, 1.0], &[0.0, 0.0, 0.0], 3.0),
+            // Only the sign matters
+            (&[0.5, -2.0, 8.0], &[3.0, 0.0, 0.1], 0.0),
+        ];
+        for (v1, v2, expected) in cases {
+            let similarity = HammingMetric::similarity(v1, v2);
+            assert_eq!(similarity, -expected);
+            assert_eq!(HammingMetric::similarity(v2, v1), similarity);
+            assert_eq!(HammingMetric::postprocess(similarity), expected);
+        }
+    }
+
+    #[test]
+    fn test_chebyshev_metric() {
+        let cases: [(&[VectorElementType], &[VectorElementType], ScoreType); 5] = [
+            (&[0.0, 0.0], &[0.0, 0.0], 0.0),
+            (&[1.0, 2.0, 3.0], &[4.0, 0.0, 3.0], 3.0),
+            (&[-1.0, 0.5], &[1.0, -0.5], 2.0),
+            (&[0.25, -2.0, 8.0, 1.0], &[0.0, 2.0, 6.0, 1.0], 4.0),
+            (&[5.0], &[-2.5], 7.5),
+        ];
+        for (v1, v2, expected) in cases {
+            let similarity = ChebyshevMetric::similarity(v1, v2);
+            assert_eq!(similarity, -expected);
+            assert_eq!(ChebyshevMetric::similarity(v2, v1), similarity);
+            assert_eq!(ChebyshevMetric::postprocess(similarity), expected);
+        }
+
+        let vector = vec![1.0, -2.0, 3.0];
+        assert_eq!(
+            ChebyshevMetric::preprocess(vector.clone()).into_inner(),
+            vector
+        );
+    }
+
+    #[test]
+    fn test_raw_scorer_known_scores() {
+        let vectors = vec![vec![1.0, 0.0], vec![0.0, 2.0], vec![1.0, 1.0]];
+        let producer = TestRawScorerProducer::<DotProductMetric>::from_vectors(2, vectors);
+        let scorer = producer.get_raw_scorer(vec![1.0, 2.0]);
+        assert_eq!(scorer.score_point(0), 1.0);
+        assert_eq!(scorer.score_point(1), 4.0);
+        assert_eq!(scorer.score_point(2), 3.0);
+
+        // Stored vectors are normalized for cosine
+        let vectors = vec![vec![3.0, 4.0], vec![0.0, 5.0]];
+        let producer = TestRawScorerProducer::<CosineMetric>::from_vectors(2, vectors);
+        assert_eq!(producer.vectors.get(0), &[0.6, 0.8]);
+        let scorer = producer.get_raw_scorer(vec![2.0, 0.0]);
+        assert!((scorer.score_point(0) - 0.6).abs() < 1e-6);
+        assert!(scorer.score_point(1).abs() < 1e-6);
     }
 }
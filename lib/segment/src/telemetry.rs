@@ -1,5 +1,6 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sparse::common::sparse_vector::SparseVector;
 
 use crate::common::anonymize::Anonymize;
 use crate::common::operation_time_statistics::OperationDurationStatistics;
@@ -38,6 +39,43 @@ impl PayloadIndexTelemetry {
     }
 }
 
+/// Shape of sparse vectors, without revealing any of their indices or weights
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default, PartialEq)]
+pub struct SparseVectorsTelemetry {
+    /// Number of vectors by count of non-zero elements, bucketed by powers of ten:
+    /// `0..=10`, `11..=100`, `101..=1000` and so on
+    pub nnz_histogram: Vec<usize>,
+}
+
+impl SparseVectorsTelemetry {
+    pub fn from_vectors<'a>(vectors: impl IntoIterator<Item = &'a SparseVector>) -> Self {
+        let mut nnz_histogram = Vec::new();
+        for vector in vectors {
+            let nnz = vector
+                .weights
+                .iter()
+                .filter(|&&weight| weight != 0.0)
+                .count();
+            let bucket = Self::nnz_bucket(nnz);
+            if nnz_histogram.len() <= bucket {
+                nnz_histogram.resize(bucket + 1, 0);
+            }
+            nnz_histogram[bucket] += 1;
+        }
+        Self { nnz_histogram }
+    }
+
+    fn nnz_bucket(nnz: usize) -> usize {
+        let mut bucket = 0;
+        let mut upper_bound = 10;
+        while nnz > upper_bound {
+            upper_bound = upper_bound.saturating_mul(10);
+            bucket += 1;
+        }
+        bucket
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default)]
 pub struct VectorIndexSearchesTelemetry {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -130,6 +168,10 @@ impl Anonymize for VectorDataConfig {
             storage_type: self.storage_type,
             index: self.index.clone(),
             quantization_config: None,
+            max_sparse_dimension: self.max_sparse_dimension,
+            datatype: self.datatype,
+            vector_cache_size: self.vector_cache_size,
+            strict_vector_checks: self.strict_vector_checks,
         }
     }
 }
@@ -159,3 +201,43 @@ impl Anonymize for PayloadIndexTelemetry {
         }
     }
 }
+
+impl Anonymize for SparseVectorsTelemetry {
+    fn anonymize(&self) -> Self {
+        SparseVectorsTelemetry {
+            nnz_histogram: self.nnz_histogram.anonymize(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_vectors_telemetry_buckets() {
+        let sparse_with_nnz = |nnz: usize| {
+            let indices = (0..nnz as u32).collect();
+            SparseVector::new(indices, vec![0.5; nnz])
+        };
+        let vectors = vec![
+            sparse_with_nnz(0),
+            sparse_with_nnz(3),
+            sparse_with_nnz(10),
+            sparse_with_nnz(11),
+            sparse_with_nnz(1001),
+            // explicit zeros are not counted
+            SparseVector::new(vec![1, 2, 3], vec![0.0, 0.0, 0.5]),
+        ];
+
+        let telemetry = SparseVectorsTelemetry::from_vectors(&vectors);
+        assert_eq!(telemetry.nnz_histogram, vec![4, 1, 0, 1]);
+
+        // Small counts are preserved by anonymization
+        assert_eq!(telemetry.anonymize(), telemetry);
+        assert_eq!(
+            SparseVectorsTelemetry::from_vectors(&[]),
+            SparseVectorsTelemetry::default()
+        );
+    }
+}
@@ -16,6 +16,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use smol_str::SmolStr;
+use sparse::common::sparse_vector::SparseVector;
+use sparse::common::types::DimId;
 use uuid::Uuid;
 use validator::{Validate, ValidationError, ValidationErrors};
 
@@ -27,8 +29,10 @@ use crate::common::utils::{
 };
 use crate::data_types::text_index::TextIndexParams;
 use crate::data_types::vectors::{VectorElementType, VectorStruct, VectorType};
-use crate::spaces::metric::Metric;
-use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::spaces::metric::{Metric, PreprocessedVector, SparseMetric};
+use crate::spaces::simple::{
+    ChebyshevMetric, CosineMetric, DotProductMetric, EuclidMetric, HammingMetric, L1Metric,
+};
 
 pub type PayloadKeyType = String;
 pub type PayloadKeyTypeRef<'a> = &'a str;
@@ -122,14 +126,37 @@ pub enum Distance {
     Euclid,
     // <https://en.wikipedia.org/wiki/Dot_product>
     Dot,
+    // <https://en.wikipedia.org/wiki/Taxicab_geometry>
+    L1,
+    // <https://en.wikipedia.org/wiki/Hamming_distance>
+    Hamming,
+    // <https://en.wikipedia.org/wiki/Chebyshev_distance>
+    Chebyshev,
 }
 
 impl Distance {
-    pub fn preprocess_vector(&self, vector: VectorType) -> VectorType {
+    pub fn preprocess_vector(&self, vector: VectorType) -> PreprocessedVector {
         match self {
             Distance::Cosine => CosineMetric::preprocess(vector),
             Distance::Euclid => EuclidMetric::preprocess(vector),
             Distance::Dot => DotProductMetric::preprocess(vector),
+            Distance::L1 => L1Metric::preprocess(vector),
+            Distance::Hamming => HammingMetric::preprocess(vector),
+            Distance::Chebyshev => ChebyshevMetric::preprocess(vector),
+        }
+    }
+
+    /// Same as [`Distance::preprocess_vector`] for sparse vectors
+    ///
+    /// Only [`Distance::Cosine`] changes sparse vectors, other distances keep them as is.
+    pub fn preprocess_sparse_vector(&self, vector: SparseVector) -> SparseVector {
+        match self {
+            Distance::Cosine => CosineMetric::preprocess_sparse(vector),
+            Distance::Dot
+            | Distance::Euclid
+            | Distance::L1
+            | Distance::Hamming
+            | Distance::Chebyshev => DotProductMetric::preprocess_sparse(vector),
         }
     }
 
@@ -138,24 +165,52 @@ impl Distance {
             Distance::Cosine => CosineMetric::postprocess(score),
             Distance::Euclid => EuclidMetric::postprocess(score),
             Distance::Dot => DotProductMetric::postprocess(score),
+            Distance::L1 => L1Metric::postprocess(score),
+            Distance::Hamming => HammingMetric::postprocess(score),
+            Distance::Chebyshev => ChebyshevMetric::postprocess(score),
         }
     }
 
     pub fn distance_order(&self) -> Order {
         match self {
             Distance::Cosine | Distance::Dot => Order::LargeBetter,
-            Distance::Euclid => Order::SmallBetter,
+            Distance::Euclid | Distance::L1 | Distance::Hamming | Distance::Chebyshev => {
+                Order::SmallBetter
+            }
         }
     }
 
-    /// Checks if score satisfies threshold condition
-    pub fn check_threshold(&self, score: ScoreType, threshold: ScoreType) -> bool {
+    /// Checks if score `a` is strictly better than score `b`
+    ///
+    /// Applies to scores as returned to the user, i.e. after [`Distance::postprocess_score`].
+    /// Internal similarities are always larger-is-better.
+    pub fn better(&self, a: ScoreType, b: ScoreType) -> bool {
         match self.distance_order() {
-            Order::LargeBetter => score > threshold,
-            Order::SmallBetter => score < threshold,
+            Order::LargeBetter => a > b,
+            Order::SmallBetter => a < b,
+        }
+    }
+
+    /// Map a score, as returned to the user, into `[0, 1]` range, larger is better
+    ///
+    /// Cosine is shifted from `[-1, 1]`, unbounded dot product goes through a sigmoid, so that
+    /// the order of scores is kept, distances are inverted as `1 / (1 + distance)`.
+    /// Meant for the response boundary only, internal scores stay raw.
+    pub fn normalize_score(&self, score: ScoreType) -> ScoreType {
+        match self {
+            Distance::Cosine => ((score + 1.0) / 2.0).clamp(0.0, 1.0),
+            Distance::Dot => 1.0 / (1.0 + (-score).exp()),
+            Distance::Euclid | Distance::L1 | Distance::Hamming | Distance::Chebyshev => {
+                1.0 / (1.0 + score.max(0.0))
+            }
         }
     }
 
+    /// Checks if score satisfies threshold condition
+    pub fn check_threshold(&self, score: ScoreType, threshold: ScoreType) -> bool {
+        self.better(score, threshold)
+    }
+
     /// Calculates distance between two vectors
     ///
     /// Warn: prefer compile-time generics with `Metric` trait
@@ -164,10 +219,14 @@ impl Distance {
             Distance::Cosine => CosineMetric::similarity(v1, v2),
             Distance::Euclid => EuclidMetric::similarity(v1, v2),
             Distance::Dot => DotProductMetric::similarity(v1, v2),
+            Distance::L1 => L1Metric::similarity(v1, v2),
+            Distance::Hamming => HammingMetric::similarity(v1, v2),
+            Distance::Chebyshev => ChebyshevMetric::similarity(v1, v2),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Order {
     LargeBetter,
     SmallBetter,
@@ -331,6 +390,11 @@ pub struct SearchParams {
     /// guarantee that all uploaded vectors will be included in search results
     #[serde(default)]
     pub indexed_only: bool,
+
+    /// If enabled, returned scores are mapped into `[0, 1]` range, larger is better,
+    /// see [`Distance::normalize_score`]. `score_threshold` still applies to raw scores.
+    #[serde(default)]
+    pub normalize_score: bool,
 }
 
 /// Vector index configuration
@@ -678,6 +742,30 @@ impl VectorStorageType {
     }
 }
 
+/// Element types to store dense vectors with
+#[derive(Default, Debug, Deserialize, Serialize, JsonSchema, Eq, PartialEq, Hash, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorStorageDatatype {
+    /// Single precision floats, vectors are stored as they are
+    #[default]
+    Float32,
+    /// Half precision floats, taking half the space at the cost of precision
+    ///
+    /// Elements are converted to single precision when read. Only applies to mmap storage,
+    /// appendable storages keep full precision.
+    Float16,
+}
+
+impl VectorStorageDatatype {
+    /// Size of a single stored vector element in bytes
+    pub fn element_size(&self) -> usize {
+        match self {
+            Self::Float32 => size_of::<f32>(),
+            Self::Float16 => size_of::<u16>(),
+        }
+    }
+}
+
 /// Config of single vector data storage
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -692,6 +780,18 @@ pub struct VectorDataConfig {
     pub index: Indexes,
     /// Vector specific quantization config that overrides collection config
     pub quantization_config: Option<QuantizationConfig>,
+    /// Upper bound for indices of sparse vectors, not checked if absent
+    pub max_sparse_dimension: Option<DimId>,
+    /// Element type dense vectors are stored with, see [`VectorStorageDatatype`]
+    #[serde(default)]
+    pub datatype: VectorStorageDatatype,
+    /// Number of vectors to keep cached in memory for repeated reads from mmap storage,
+    /// no cache if absent
+    pub vector_cache_size: Option<usize>,
+    /// Also reject vectors which can't be scored with the configured distance, e.g. all-zero
+    /// vectors with Cosine distance
+    #[serde(default)]
+    pub strict_vector_checks: bool,
 }
 
 impl VectorDataConfig {
@@ -1984,8 +2084,7 @@ pub(crate) mod test_utils {
 #[cfg(test)]
 mod tests {
     use serde::de::DeserializeOwned;
-    use serde_json;
-    use serde_json::json;
+    use serde_json::{self, json};
 
     use super::test_utils::build_polygon_with_interiors;
     use super::*;
@@ -3004,6 +3103,67 @@ mod tests {
         });
         assert_eq!(payload, expected.into());
     }
+
+    #[test]
+    fn test_distance_order() {
+        for (distance, order) in [
+            (Distance::Cosine, Order::LargeBetter),
+            (Distance::Dot, Order::LargeBetter),
+            (Distance::Euclid, Order::SmallBetter),
+            (Distance::L1, Order::SmallBetter),
+            (Distance::Hamming, Order::SmallBetter),
+            (Distance::Chebyshev, Order::SmallBetter),
+        ] {
+            assert_eq!(distance.distance_order(), order);
+            assert_eq!(distance.better(2.0, 1.0), order == Order::LargeBetter);
+            assert_eq!(distance.better(1.0, 2.0), order == Order::SmallBetter);
+            assert!(!distance.better(1.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn test_normalize_score() {
+        let cases: [(Distance, &[(ScoreType, ScoreType)]); 6] = [
+            (
+                Distance::Cosine,
+                &[
+                    (-1.0, 0.0),
+                    (0.0, 0.5),
+                    (0.5, 0.75),
+                    (1.0, 1.0),
+                    (1.0001, 1.0),
+                ],
+            ),
+            (Distance::Dot, &[(0.0, 0.5)]),
+            (Distance::Euclid, &[(0.0, 1.0), (1.0, 0.5), (3.0, 0.25)]),
+            (Distance::L1, &[(0.0, 1.0), (4.0, 0.2)]),
+            (Distance::Hamming, &[(0.0, 1.0), (1.0, 0.5), (9.0, 0.1)]),
+            (Distance::Chebyshev, &[(0.0, 1.0), (0.25, 0.8)]),
+        ];
+        for (distance, values) in cases {
+            for &(score, expected) in values {
+                assert_eq!(
+                    distance.normalize_score(score),
+                    expected,
+                    "{distance:?} {score}"
+                );
+            }
+            // Normalization keeps the order of scores
+            let (worse, better) = match distance.distance_order() {
+                Order::LargeBetter => (0.1, 0.2),
+                Order::SmallBetter => (0.2, 0.1),
+            };
+            assert!(distance.normalize_score(better) > distance.normalize_score(worse));
+        }
+
+        // Dot product is unbounded, order must be kept for scores above 1.0 too
+        let dot_scores = [-3.0, -0.5, 0.25, 1.0, 1.5, 2.0, 5.0]
+            .map(|score| Distance::Dot.normalize_score(score));
+        for pair in dot_scores.windows(2) {
+            assert!(pair[0] < pair[1], "{pair:?}");
+        }
+        assert!(dot_scores.iter().all(|score| (0.0..=1.0).contains(score)));
+    }
 }
 
 pub type TheMap<K, V> = BTreeMap<K, V>;
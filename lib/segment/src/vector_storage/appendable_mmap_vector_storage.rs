@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fs::create_dir_all;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
@@ -7,17 +8,19 @@ use std::sync::Arc;
 use atomic_refcell::AtomicRefCell;
 use bitvec::prelude::BitSlice;
 use common::types::PointOffsetType;
+use memory::madvise::Advice;
 
-use crate::common::operation_error::{check_process_stopped, OperationResult};
-use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
+use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::{Flusher, ProgressFlusher};
+use crate::data_types::vectors::{CowVector, VectorElementType, VectorOrSparseRef};
 use crate::types::{Distance, QuantizationConfig};
 use crate::vector_storage::chunked_mmap_vectors::ChunkedMmapVectors;
+use crate::vector_storage::common::{files_size_in_bytes, STOP_CHECK_INTERVAL};
 use crate::vector_storage::dynamic_mmap_flags::DynamicMmapFlags;
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
-use crate::vector_storage::{VectorStorage, VectorStorageEnum};
+use crate::vector_storage::{Checkpoint, CheckpointJournals, VectorStorage, VectorStorageEnum};
 
-const VECTORS_DIR_PATH: &str = "vectors";
+pub(crate) const VECTORS_DIR_PATH: &str = "vectors";
 const DELETED_DIR_PATH: &str = "deleted";
 
 pub struct AppendableMmapVectorStorage {
@@ -26,6 +29,7 @@ pub struct AppendableMmapVectorStorage {
     distance: Distance,
     deleted_count: usize,
     quantized_vectors: Option<QuantizedVectors>,
+    checkpoints: CheckpointJournals,
 }
 
 pub fn open_appendable_memmap_vector_storage(
@@ -33,6 +37,18 @@ pub fn open_appendable_memmap_vector_storage(
     dim: usize,
     distance: Distance,
 ) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
+    let storage = open_appendable_memmap_vector_storage_impl(path, dim, distance)?;
+
+    Ok(Arc::new(AtomicRefCell::new(
+        VectorStorageEnum::AppendableMemmap(Box::new(storage)),
+    )))
+}
+
+pub(crate) fn open_appendable_memmap_vector_storage_impl(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+) -> OperationResult<AppendableMmapVectorStorage> {
     create_dir_all(path)?;
 
     let vectors_path = path.join(VECTORS_DIR_PATH);
@@ -58,11 +74,10 @@ pub fn open_appendable_memmap_vector_storage(
         distance,
         deleted_count,
         quantized_vectors: None,
+        checkpoints: CheckpointJournals::default(),
     };
 
-    Ok(Arc::new(AtomicRefCell::new(
-        VectorStorageEnum::AppendableMemmap(Box::new(storage)),
-    )))
+    Ok(storage)
 }
 
 impl AppendableMmapVectorStorage {
@@ -99,8 +114,12 @@ impl VectorStorage for AppendableMmapVectorStorage {
         self.vectors.len()
     }
 
-    fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
-        self.vectors.get(key)
+    fn size_of_available_vectors_in_bytes(&self) -> usize {
+        files_size_in_bytes(&self.files())
+    }
+
+    fn get_vector(&self, key: PointOffsetType) -> CowVector<'_> {
+        Cow::Borrowed(self.vectors.get(key))
     }
 
     fn insert_vector(
@@ -108,11 +127,43 @@ impl VectorStorage for AppendableMmapVectorStorage {
         key: PointOffsetType,
         vector: &[VectorElementType],
     ) -> OperationResult<()> {
+        if (key as usize) < self.vectors.len() {
+            self.checkpoints
+                .record_overwrite(key, self.vectors.get(key));
+        }
         self.vectors.insert(key, vector)?;
         self.set_deleted(key, false)?;
         Ok(())
     }
 
+    fn insert_vectors(
+        &mut self,
+        start_key: PointOffsetType,
+        vectors: &[VectorOrSparseRef],
+    ) -> OperationResult<()> {
+        let vectors: Vec<&[VectorElementType]> = vectors
+            .iter()
+            .map(|&vector| vector.try_into())
+            .collect::<Result<_, _>>()?;
+
+        // Grow vector chunks and deleted flags once, instead of on every insert
+        let end = start_key as usize + vectors.len();
+        self.vectors.ensure_capacity(end)?;
+        if self.deleted.len() < end {
+            self.deleted.set_len(end)?;
+        }
+
+        for (key, vector) in (start_key..).zip(vectors) {
+            if (key as usize) < self.vectors.len() {
+                self.checkpoints
+                    .record_overwrite(key, self.vectors.get(key));
+            }
+            self.vectors.insert(key, vector)?;
+            self.set_deleted(key, false)?;
+        }
+        Ok(())
+    }
+
     fn update_from(
         &mut self,
         other: &VectorStorageEnum,
@@ -120,12 +171,14 @@ impl VectorStorage for AppendableMmapVectorStorage {
         stopped: &AtomicBool,
     ) -> OperationResult<Range<PointOffsetType>> {
         let start_index = self.vectors.len() as PointOffsetType;
-        for point_id in other_ids {
-            check_process_stopped(stopped)?;
+        for (i, point_id) in other_ids.enumerate() {
+            if i % STOP_CHECK_INTERVAL == 0 {
+                check_process_stopped(stopped)?;
+            }
             // Do not perform preprocessing - vectors should be already processed
             let other_deleted = other.is_deleted_vector(point_id);
             let other_vector = other.get_vector(point_id);
-            let new_id = self.vectors.push(other_vector)?;
+            let new_id = self.vectors.push(&other_vector)?;
             self.set_deleted(new_id, other_deleted)?;
         }
         let end_index = self.vectors.len() as PointOffsetType;
@@ -144,6 +197,28 @@ impl VectorStorage for AppendableMmapVectorStorage {
         })
     }
 
+    fn mmap_advise(&self, advice: Advice) -> OperationResult<()> {
+        self.vectors.madvise(advice)
+    }
+
+    fn progress_flusher(&self) -> ProgressFlusher {
+        Box::new({
+            let vectors_flusher = self.vectors.progress_flusher();
+            let deleted_flusher = self.deleted.flusher();
+            move |progress| {
+                // Same ordering as in `flush_with_progress`
+                deleted_flusher()?;
+                vectors_flusher(progress)
+            }
+        })
+    }
+
+    fn flush_with_progress(&self, progress: impl Fn(u64, u64)) -> OperationResult<()> {
+        // Deleted flags are small, flush them upfront so the last progress report means done
+        self.deleted.flusher()()?;
+        self.vectors.flush_with_progress(progress)
+    }
+
     fn quantize(
         &mut self,
         path: &Path,
@@ -190,6 +265,28 @@ impl VectorStorage for AppendableMmapVectorStorage {
         self.set_deleted(key, true)
     }
 
+    fn delete_vectors(&mut self, keys: &[PointOffsetType]) -> OperationResult<usize> {
+        let num_vectors = self.vectors.len();
+        let in_range = || {
+            keys.iter()
+                .copied()
+                .filter(|&key| (key as usize) < num_vectors)
+        };
+
+        // Grow flags once for the whole batch
+        if let Some(max_key) = in_range().max() {
+            if self.deleted.len() <= max_key as usize {
+                self.deleted.set_len(max_key as usize + 1)?;
+            }
+        }
+
+        let newly_deleted = in_range()
+            .filter(|&key| !self.deleted.set(key, true))
+            .count();
+        self.deleted_count += newly_deleted;
+        Ok(newly_deleted)
+    }
+
     fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
         self.deleted.get(key)
     }
@@ -198,7 +295,58 @@ impl VectorStorage for AppendableMmapVectorStorage {
         self.deleted_count
     }
 
+    fn recount_deleted_vectors(&mut self) -> usize {
+        self.deleted_count = (0..self.vectors.len())
+            .filter(|&key| self.deleted.get(key))
+            .count();
+        self.deleted_count
+    }
+
     fn deleted_vector_bitslice(&self) -> &BitSlice {
         self.deleted.get_bitslice()
     }
+
+    fn assert_contiguous(&self) -> OperationResult<()> {
+        match self.vectors.first_unbacked_key() {
+            Some(key) => Err(OperationError::InconsistentStorage {
+                description: format!(
+                    "Vector storage has {} vectors, but no chunk for offset {key}",
+                    self.vectors.len(),
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.checkpoints
+            .checkpoint(self.vectors.len(), self.deleted.get_bitslice())
+    }
+
+    fn restore_checkpoint(&mut self, checkpoint: Checkpoint) -> OperationResult<()> {
+        let total_vector_count = checkpoint.total_vector_count;
+        if total_vector_count > self.vectors.len() {
+            return Err(OperationError::service_error(format!(
+                "Can't restore checkpoint with {total_vector_count} vectors, storage has only {}",
+                self.vectors.len(),
+            )));
+        }
+        let overwritten = self.checkpoints.restore(&checkpoint)?;
+        let Checkpoint { deleted, .. } = checkpoint;
+
+        self.vectors.truncate(total_vector_count);
+        for (key, vector) in overwritten {
+            self.vectors.insert(key, &vector)?;
+        }
+
+        // Flags past the checkpoint must be reset, to be counted properly on reinsertion
+        let mut deleted_count = 0;
+        for key in 0..self.deleted.len() {
+            let is_deleted = key < total_vector_count && deleted.get(key).is_some_and(|bit| *bit);
+            self.deleted.set(key, is_deleted);
+            deleted_count += usize::from(is_deleted);
+        }
+        self.deleted_count = deleted_count;
+        Ok(())
+    }
 }
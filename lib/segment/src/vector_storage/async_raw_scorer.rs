@@ -4,11 +4,14 @@ use bitvec::prelude::BitSlice;
 use common::fixed_length_priority_queue::FixedLengthPriorityQueue;
 use common::types::{PointOffsetType, ScoreType, ScoredPointOffset};
 
+use super::query_scorer::context_query_scorer::ContextQueryScorer;
 use super::query_scorer::reco_query_scorer::RecoQueryScorer;
 use crate::common::operation_error::OperationResult;
 use crate::data_types::vectors::QueryVector;
 use crate::spaces::metric::Metric;
-use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::spaces::simple::{
+    ChebyshevMetric, CosineMetric, DotProductMetric, EuclidMetric, HammingMetric, L1Metric,
+};
 use crate::types::Distance;
 use crate::vector_storage::memmap_vector_storage::MemmapVectorStorage;
 use crate::vector_storage::mmap_vectors::MmapVectors;
@@ -24,7 +27,7 @@ pub fn new<'a>(
 ) -> OperationResult<Box<dyn RawScorer + 'a>> {
     Ok(AsyncRawScorerBuilder::new(query, storage, point_deleted)?
         .with_is_stopped(is_stopped)
-        .build())
+        .build()?)
 }
 
 pub struct AsyncRawScorerImpl<'a, TQueryScorer: QueryScorer> {
@@ -236,11 +239,14 @@ impl<'a> AsyncRawScorerBuilder<'a> {
         Ok(builder)
     }
 
-    pub fn build(self) -> Box<dyn RawScorer + 'a> {
+    pub fn build(self) -> OperationResult<Box<dyn RawScorer + 'a>> {
         match self.distance {
             Distance::Cosine => self._build_with_metric::<CosineMetric>(),
             Distance::Euclid => self._build_with_metric::<EuclidMetric>(),
             Distance::Dot => self._build_with_metric::<DotProductMetric>(),
+            Distance::L1 => self._build_with_metric::<L1Metric>(),
+            Distance::Hamming => self._build_with_metric::<HammingMetric>(),
+            Distance::Chebyshev => self._build_with_metric::<ChebyshevMetric>(),
         }
     }
 
@@ -249,7 +255,7 @@ impl<'a> AsyncRawScorerBuilder<'a> {
         self
     }
 
-    fn _build_with_metric<TMetric: Metric + 'a>(self) -> Box<dyn RawScorer + 'a> {
+    fn _build_with_metric<TMetric: Metric + 'a>(self) -> OperationResult<Box<dyn RawScorer + 'a>> {
         let Self {
             points_count,
             query,
@@ -262,26 +268,39 @@ impl<'a> AsyncRawScorerBuilder<'a> {
 
         match query {
             QueryVector::Nearest(vector) => {
-                let query_scorer = MetricQueryScorer::<TMetric, _>::new(vector, storage);
-                Box::new(AsyncRawScorerImpl::new(
+                let query_scorer =
+                    MetricQueryScorer::<TMetric, _>::new(vector.try_into()?, storage);
+                Ok(Box::new(AsyncRawScorerImpl::new(
                     points_count,
                     query_scorer,
                     storage.get_mmap_vectors(),
                     point_deleted,
                     vec_deleted,
                     is_stopped.unwrap_or(&DEFAULT_STOPPED),
-                ))
+                )))
             }
             QueryVector::Recommend(query) => {
-                let query_scorer = RecoQueryScorer::<TMetric, _>::new(query, storage);
-                Box::new(AsyncRawScorerImpl::new(
+                let query_scorer = RecoQueryScorer::<TMetric, _>::new(query.try_into()?, storage);
+                Ok(Box::new(AsyncRawScorerImpl::new(
                     points_count,
                     query_scorer,
                     storage.get_mmap_vectors(),
                     point_deleted,
                     vec_deleted,
                     is_stopped.unwrap_or(&DEFAULT_STOPPED),
-                ))
+                )))
+            }
+            QueryVector::Context(query) => {
+                let query_scorer =
+                    ContextQueryScorer::<TMetric, _>::new(query.try_into()?, storage);
+                Ok(Box::new(AsyncRawScorerImpl::new(
+                    points_count,
+                    query_scorer,
+                    storage.get_mmap_vectors(),
+                    point_deleted,
+                    vec_deleted,
+                    is_stopped.unwrap_or(&DEFAULT_STOPPED),
+                )))
             }
         }
     }
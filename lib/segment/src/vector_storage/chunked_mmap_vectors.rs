@@ -5,14 +5,16 @@ use std::path::{Path, PathBuf};
 
 use common::types::PointOffsetType;
 use memmap2::MmapMut;
+use memory::madvise::Advice;
 use memory::mmap_ops::{create_and_ensure_length, open_write_mmap};
 use serde::{Deserialize, Serialize};
 
 use crate::common::mmap_type::MmapType;
 use crate::common::operation_error::{OperationError, OperationResult};
-use crate::common::Flusher;
+use crate::common::{Flusher, ProgressFlusher};
 use crate::data_types::vectors::VectorElementType;
 use crate::vector_storage::chunked_utils::{chunk_name, create_chunk, read_mmaps, MmapChunk};
+use crate::vector_storage::div_ceil;
 
 #[cfg(debug_assertions)]
 const DEFAULT_CHUNK_SIZE: usize = 512 * 1024; // 512Kb
@@ -149,6 +151,15 @@ impl ChunkedMmapVectors {
         Ok(())
     }
 
+    /// Allocate chunks to store at least `len` vectors
+    pub fn ensure_capacity(&mut self, len: usize) -> OperationResult<()> {
+        let required_chunks = div_ceil(len, self.config.chunk_size_vectors);
+        while self.chunks.len() < required_chunks {
+            self.add_chunk()?;
+        }
+        Ok(())
+    }
+
     pub fn insert(
         &mut self,
         key: PointOffsetType,
@@ -158,10 +169,7 @@ impl ChunkedMmapVectors {
         let chunk_idx = self.get_chunk_index(key);
         let chunk_offset = self.get_chunk_offset(key);
 
-        // Ensure capacity
-        while chunk_idx >= self.chunks.len() {
-            self.add_chunk()?;
-        }
+        self.ensure_capacity(key + 1)?;
 
         let chunk = &mut self.chunks[chunk_idx];
 
@@ -175,6 +183,23 @@ impl ChunkedMmapVectors {
         Ok(())
     }
 
+    /// First key which is counted in length, but has no chunk to be stored in
+    ///
+    /// Can happen if trailing chunk files are lost, e.g. by a partial restore.
+    pub fn first_unbacked_key(&self) -> Option<usize> {
+        let capacity = self.chunks.len() * self.config.chunk_size_vectors;
+        (self.status.len > capacity).then_some(capacity)
+    }
+
+    /// Shrink to `len` vectors, does nothing if there are less vectors already
+    ///
+    /// Allocated chunks are kept, to be reused by further inserts.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.status.len {
+            self.status.len = len;
+        }
+    }
+
     pub fn push(&mut self, vector: &[VectorElementType]) -> OperationResult<PointOffsetType> {
         let new_id = self.status.len as PointOffsetType;
         self.insert(new_id, vector)?;
@@ -206,6 +231,49 @@ impl ChunkedMmapVectors {
         })
     }
 
+    /// Advise OS how the vector chunks will be accessed
+    pub fn madvise(&self, advice: Advice) -> OperationResult<()> {
+        for chunk in &self.chunks {
+            chunk.madvise(advice)?;
+        }
+        Ok(())
+    }
+
+    /// Same as [`ChunkedMmapVectors::flusher`], reporting progress after each chunk
+    pub fn progress_flusher(&self) -> ProgressFlusher {
+        Box::new({
+            let status_flusher = self.status.flusher();
+            let chunks_flushers: Vec<_> = self.chunks.iter().map(|chunk| chunk.flusher()).collect();
+            move |progress| {
+                let total = chunks_flushers.len();
+                progress(0.0);
+                for (chunk_idx, flusher) in chunks_flushers.into_iter().enumerate() {
+                    flusher()?;
+                    // Last report is left for the status flush
+                    if chunk_idx + 1 < total {
+                        progress((chunk_idx + 1) as f32 / total as f32);
+                    }
+                }
+                status_flusher()?;
+                progress(1.0);
+                Ok(())
+            }
+        })
+    }
+
+    /// Flush chunks one by one, reporting flushed bytes of total chunks size after each chunk
+    pub fn flush_with_progress(&self, progress: impl Fn(u64, u64)) -> OperationResult<()> {
+        let chunk_size_bytes = self.config.chunk_size_bytes as u64;
+        let total_bytes = chunk_size_bytes * self.chunks.len() as u64;
+        progress(0, total_bytes);
+        for (chunk_idx, chunk) in self.chunks.iter().enumerate() {
+            chunk.flusher()()?;
+            progress(chunk_size_bytes * (chunk_idx as u64 + 1), total_bytes);
+        }
+        self.status.flusher()()?;
+        Ok(())
+    }
+
     pub fn files(&self) -> Vec<PathBuf> {
         let mut files = Vec::new();
         files.push(Self::config_file(&self.directory));
@@ -8,6 +8,7 @@ use std::path::Path;
 use common::types::PointOffsetType;
 
 use super::div_ceil;
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::vector_utils::{TrySetCapacity, TrySetCapacityExact};
 
 // chunk size in bytes
@@ -54,17 +55,26 @@ impl<T: Copy + Clone + Default> ChunkedVectors<T> {
         &chunk_data[idx..idx + self.dim]
     }
 
-    pub fn push(&mut self, vector: &[T]) -> Result<PointOffsetType, TryReserveError> {
+    pub fn push(&mut self, vector: &[T]) -> OperationResult<PointOffsetType> {
         let new_id = self.len as PointOffsetType;
         self.insert(new_id, vector)?;
         Ok(new_id)
     }
 
-    pub fn insert(&mut self, key: PointOffsetType, vector: &[T]) -> Result<(), TryReserveError> {
+    pub fn insert(&mut self, key: PointOffsetType, vector: &[T]) -> OperationResult<()> {
+        if vector.len() != self.dim {
+            return Err(OperationError::WrongVector {
+                expected_dim: self.dim,
+                received_dim: vector.len(),
+            });
+        }
         let key = key as usize;
         self.len = max(self.len, key + 1);
-        self.chunks
-            .resize(div_ceil(self.len, self.chunk_capacity), vec![]);
+        // Only grow, chunks may be allocated ahead with `reserve`
+        let num_chunks = div_ceil(self.len, self.chunk_capacity);
+        if self.chunks.len() < num_chunks {
+            self.chunks.resize(num_chunks, vec![]);
+        }
 
         let chunk_idx = key / self.chunk_capacity;
         let chunk_data = &mut self.chunks[chunk_idx];
@@ -87,6 +97,21 @@ impl<T: Copy + Clone + Default> ChunkedVectors<T> {
         data.copy_from_slice(vector);
         Ok(())
     }
+
+    /// Shrink to `len` vectors, does nothing if there are less vectors already
+    ///
+    /// Chunks past the new length are freed.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+            self.chunks.truncate(div_ceil(len, self.chunk_capacity));
+        }
+    }
+
+    /// Allocate memory for at least `additional` more vectors
+    pub fn reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_set_capacity_exact(self.len + additional)
+    }
 }
 
 impl quantization::EncodedStorage for ChunkedVectors<u8> {
@@ -171,3 +196,35 @@ impl quantization::EncodedStorageBuilder<ChunkedVectors<u8>> for ChunkedVectors<
         self.push(other).unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_wrong_dim() {
+        let mut vectors = ChunkedVectors::<f32>::new(3);
+        vectors.push(&[1.0, 2.0, 3.0]).unwrap();
+
+        let error = vectors.insert(1, &[1.0, 2.0]).unwrap_err();
+        assert!(matches!(
+            error,
+            OperationError::WrongVector {
+                expected_dim: 3,
+                received_dim: 2,
+            }
+        ));
+        let error = vectors.push(&[1.0, 2.0, 3.0, 4.0]).unwrap_err();
+        assert!(matches!(
+            error,
+            OperationError::WrongVector {
+                expected_dim: 3,
+                received_dim: 4,
+            }
+        ));
+
+        // Rejected vectors are not stored
+        assert_eq!(vectors.len(), 1);
+        assert_eq!(vectors.get(0), &[1.0, 2.0, 3.0]);
+    }
+}
@@ -1,7 +1,11 @@
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 static ASYNC_SCORER: AtomicBool = AtomicBool::new(false);
 
+/// Long copying loops check for cancellation once per this many vectors
+pub const STOP_CHECK_INTERVAL: usize = 1000;
+
 pub fn set_async_scorer(async_scorer: bool) {
     ASYNC_SCORER.store(async_scorer, Ordering::Relaxed);
 }
@@ -9,3 +13,12 @@ pub fn set_async_scorer(async_scorer: bool) {
 pub fn get_async_scorer() -> bool {
     ASYNC_SCORER.load(Ordering::Relaxed)
 }
+
+/// Total size of existing `files` on disk, missing files are not counted
+pub fn files_size_in_bytes(files: &[PathBuf]) -> usize {
+    files
+        .iter()
+        .filter_map(|file| file.metadata().ok())
+        .map(|metadata| metadata.len() as usize)
+        .sum()
+}
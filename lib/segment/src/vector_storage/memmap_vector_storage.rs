@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::{self, Write};
 use std::ops::Range;
@@ -8,19 +9,23 @@ use std::sync::Arc;
 use atomic_refcell::AtomicRefCell;
 use bitvec::prelude::BitSlice;
 use common::types::PointOffsetType;
+use memory::madvise::Advice;
 use memory::mmap_ops;
+use parking_lot::Mutex;
 
 use super::quantized::quantized_vectors::QuantizedVectors;
+use super::vector_cache::VectorCache;
+use super::vector_storage_base::check_merge_sources;
 use super::VectorStorageEnum;
-use crate::common::operation_error::{check_process_stopped, OperationResult};
+use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
 use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
-use crate::types::{Distance, QuantizationConfig};
-use crate::vector_storage::common::get_async_scorer;
-use crate::vector_storage::mmap_vectors::MmapVectors;
+use crate::data_types::vectors::{CowVector, VectorElementType};
+use crate::types::{Distance, QuantizationConfig, VectorStorageDatatype};
+use crate::vector_storage::common::{files_size_in_bytes, get_async_scorer, STOP_CHECK_INTERVAL};
+use crate::vector_storage::mmap_vectors::{encode_vector, MmapVectors};
 use crate::vector_storage::VectorStorage;
 
-const VECTORS_PATH: &str = "matrix.dat";
+pub(crate) const VECTORS_PATH: &str = "matrix.dat";
 const DELETED_PATH: &str = "deleted.dat";
 
 /// Stores all vectors in mem-mapped file
@@ -34,6 +39,10 @@ pub struct MemmapVectorStorage {
     deleted_path: PathBuf,
     mmap_store: Option<MmapVectors>,
     distance: Distance,
+    /// Reject all modifications, files are never written to
+    read_only: bool,
+    /// Optional cache of vectors served by [`VectorStorage::get_vector`]
+    vector_cache: Option<Mutex<VectorCache>>,
 }
 
 pub fn open_memmap_vector_storage(
@@ -50,11 +59,99 @@ pub fn open_memmap_vector_storage_with_async_io(
     distance: Distance,
     with_async_io: bool,
 ) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
-    create_dir_all(path)?;
+    open_memmap_vector_storage_impl(
+        path,
+        dim,
+        distance,
+        VectorStorageDatatype::Float32,
+        with_async_io,
+        false,
+        None,
+    )
+}
+
+/// Same as [`open_memmap_vector_storage`], storing vector elements with the given `datatype`
+///
+/// Vectors are converted to and from the datatype on write and read, async IO is only used for
+/// f32 storages. Existing files must be stored with the same datatype.
+pub fn open_memmap_vector_storage_with_datatype(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    datatype: VectorStorageDatatype,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
+    open_memmap_vector_storage_impl(
+        path,
+        dim,
+        distance,
+        datatype,
+        get_async_scorer(),
+        false,
+        None,
+    )
+}
+
+/// Same as [`open_memmap_vector_storage_with_datatype`], keeping up to `cache_capacity` of the
+/// most recently read vectors in memory
+///
+/// Vectors are served from the cache by [`VectorStorage::get_vector`], and therefore by the
+/// scorers. Async IO is not used for cached storages.
+pub fn open_memmap_vector_storage_with_cache(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    datatype: VectorStorageDatatype,
+    cache_capacity: usize,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
+    open_memmap_vector_storage_impl(
+        path,
+        dim,
+        distance,
+        datatype,
+        get_async_scorer(),
+        false,
+        Some(cache_capacity),
+    )
+}
+
+/// Open existing mmap storage which rejects any modification
+///
+/// Intended for serving replicas, where writes are not expected to happen. Vectors are read with
+/// the datatype they are stored with.
+pub fn open_memmap_vector_storage_read_only(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
+    open_memmap_vector_storage_impl(
+        path,
+        dim,
+        distance,
+        VectorStorageDatatype::default(),
+        get_async_scorer(),
+        true,
+        None,
+    )
+}
 
+/// `datatype` is ignored for `read_only` storages, it is taken from the existing files instead
+fn open_memmap_vector_storage_impl(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    datatype: VectorStorageDatatype,
+    with_async_io: bool,
+    read_only: bool,
+    cache_capacity: Option<usize>,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
     let vectors_path = path.join(VECTORS_PATH);
     let deleted_path = path.join(DELETED_PATH);
-    let mmap_store = MmapVectors::open(&vectors_path, &deleted_path, dim, with_async_io)?;
+    let mmap_store = if read_only {
+        MmapVectors::open_read_only(&vectors_path, &deleted_path, dim, with_async_io)?
+    } else {
+        create_dir_all(path)?;
+        MmapVectors::open(&vectors_path, &deleted_path, dim, datatype, with_async_io)?
+    };
 
     Ok(Arc::new(AtomicRefCell::new(VectorStorageEnum::Memmap(
         Box::new(MemmapVectorStorage {
@@ -62,6 +159,8 @@ pub fn open_memmap_vector_storage_with_async_io(
             deleted_path,
             mmap_store: Some(mmap_store),
             distance,
+            read_only,
+            vector_cache: cache_capacity.map(|capacity| Mutex::new(VectorCache::new(capacity))),
         }),
     ))))
 }
@@ -79,46 +178,52 @@ impl MemmapVectorStorage {
         self.mmap_store.as_ref().unwrap()
     }
 
-    pub fn has_async_reader(&self) -> bool {
-        self.mmap_store
-            .as_ref()
-            .map(|x| x.has_async_reader())
-            .unwrap_or(false)
-    }
-}
-
-impl VectorStorage for MemmapVectorStorage {
-    fn vector_dim(&self) -> usize {
-        self.mmap_store.as_ref().unwrap().dim
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
-    fn distance(&self) -> Distance {
-        self.distance
+    fn check_writable(&self) -> OperationResult<()> {
+        if self.read_only {
+            return Err(OperationError::service_error(format!(
+                "Can't modify mmap vector storage at {:?}, it is opened in read-only mode",
+                self.vectors_path,
+            )));
+        }
+        Ok(())
     }
 
-    fn total_vector_count(&self) -> usize {
-        self.mmap_store.as_ref().unwrap().num_vectors
+    pub fn has_vector_cache(&self) -> bool {
+        self.vector_cache.is_some()
     }
 
-    fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
-        self.mmap_store.as_ref().unwrap().get_vector(key)
+    fn evict_cached(&self, keys: &[PointOffsetType]) {
+        if let Some(cache) = &self.vector_cache {
+            let mut cache = cache.lock();
+            keys.iter().for_each(|&key| {
+                cache.remove(key);
+            });
+        }
     }
 
-    fn insert_vector(
-        &mut self,
-        _key: PointOffsetType,
-        _vector: &[VectorElementType],
-    ) -> OperationResult<()> {
-        panic!("Can't directly update vector in mmap storage")
+    pub fn has_async_reader(&self) -> bool {
+        self.mmap_store
+            .as_ref()
+            .map(|x| x.has_async_reader())
+            .unwrap_or(false)
     }
 
-    fn update_from(
+    /// Append vectors of all sources to the vectors file, remapping the storage only once
+    fn append_from_sources(
         &mut self,
-        other: &VectorStorageEnum,
-        other_ids: &mut dyn Iterator<Item = PointOffsetType>,
+        sources: &mut [(
+            &VectorStorageEnum,
+            &mut dyn Iterator<Item = PointOffsetType>,
+        )],
         stopped: &AtomicBool,
-    ) -> OperationResult<Range<PointOffsetType>> {
+    ) -> OperationResult<Vec<Range<PointOffsetType>>> {
+        self.check_writable()?;
         let dim = self.vector_dim();
+        let datatype = self.mmap_store.as_ref().unwrap().datatype;
         let start_index = self.mmap_store.as_ref().unwrap().num_vectors as PointOffsetType;
         let mut end_index = start_index;
 
@@ -131,17 +236,23 @@ impl VectorStorage for MemmapVectorStorage {
         // Extend vectors file, write other vectors into it
         let mut vectors_file = open_append(&self.vectors_path)?;
         let mut deleted_ids = vec![];
-        for id in other_ids {
-            check_process_stopped(stopped)?;
-            let vector = other.get_vector(id);
-            let raw_bites = mmap_ops::transmute_to_u8_slice(vector);
-            vectors_file.write_all(raw_bites)?;
-            end_index += 1;
-
-            // Remember deleted IDs so we can propagate deletions later
-            if other.is_deleted_vector(id) {
-                deleted_ids.push((start_index + id) as PointOffsetType);
+        let mut ranges = Vec::with_capacity(sources.len());
+        for (other, other_ids) in sources.iter_mut() {
+            let source_start = end_index;
+            for (i, id) in other_ids.enumerate() {
+                if i % STOP_CHECK_INTERVAL == 0 {
+                    check_process_stopped(stopped)?;
+                }
+                let vector = other.get_vector(id);
+                vectors_file.write_all(&encode_vector(&vector, datatype))?;
+
+                // Remember deleted IDs so we can propagate deletions later
+                if other.is_deleted_vector(id) {
+                    deleted_ids.push(end_index);
+                }
+                end_index += 1;
             }
+            ranges.push(source_start..end_index);
         }
         vectors_file.flush()?;
         drop(vectors_file);
@@ -151,6 +262,7 @@ impl VectorStorage for MemmapVectorStorage {
             &self.vectors_path,
             &self.deleted_path,
             dim,
+            datatype,
             with_async_io,
         )?);
 
@@ -164,14 +276,101 @@ impl VectorStorage for MemmapVectorStorage {
             store.delete(id);
         }
 
-        Ok(start_index..end_index)
+        Ok(ranges)
+    }
+}
+
+impl VectorStorage for MemmapVectorStorage {
+    fn vector_dim(&self) -> usize {
+        self.mmap_store.as_ref().unwrap().dim
+    }
+
+    fn distance(&self) -> Distance {
+        self.distance
+    }
+
+    fn total_vector_count(&self) -> usize {
+        self.mmap_store.as_ref().unwrap().num_vectors
+    }
+
+    fn size_of_available_vectors_in_bytes(&self) -> usize {
+        files_size_in_bytes(&self.files())
+    }
+
+    fn get_vector(&self, key: PointOffsetType) -> CowVector<'_> {
+        let mmap_store = self.mmap_store.as_ref().unwrap();
+        match &self.vector_cache {
+            Some(cache) => {
+                let vector = cache
+                    .lock()
+                    .get_or_insert_with(key, || Arc::from(&*mmap_store.get_vector(key)));
+                Cow::Owned(vector.to_vec())
+            }
+            None => mmap_store.get_vector(key),
+        }
+    }
+
+    fn insert_vector(
+        &mut self,
+        _key: PointOffsetType,
+        _vector: &[VectorElementType],
+    ) -> OperationResult<()> {
+        self.check_writable()?;
+        Err(OperationError::service_error(
+            "Can't directly update vector in mmap storage",
+        ))
+    }
+
+    fn update_from(
+        &mut self,
+        other: &VectorStorageEnum,
+        other_ids: &mut dyn Iterator<Item = PointOffsetType>,
+        stopped: &AtomicBool,
+    ) -> OperationResult<Range<PointOffsetType>> {
+        let mut ranges = self.append_from_sources(&mut [(other, other_ids)], stopped)?;
+        Ok(ranges.pop().unwrap())
+    }
+
+    fn merge_many(
+        &mut self,
+        sources: &[&VectorStorageEnum],
+        stopped: &AtomicBool,
+    ) -> OperationResult<Vec<Range<PointOffsetType>>> {
+        check_merge_sources(self.vector_dim(), self.distance, sources)?;
+        let mut source_ids: Vec<_> = sources
+            .iter()
+            .map(|source| 0..source.total_vector_count() as PointOffsetType)
+            .collect();
+        let mut sources: Vec<_> = sources
+            .iter()
+            .zip(source_ids.iter_mut())
+            .map(|(&source, ids)| (source, ids as &mut dyn Iterator<Item = PointOffsetType>))
+            .collect();
+        self.append_from_sources(&mut sources, stopped)
     }
 
     fn flusher(&self) -> Flusher {
         match &self.mmap_store {
-            Some(mmap_store) => mmap_store.flusher(),
-            None => Box::new(|| Ok(())),
+            Some(mmap_store) if !self.read_only => mmap_store.flusher(),
+            _ => Box::new(|| Ok(())),
+        }
+    }
+
+    fn flush_with_progress(&self, progress: impl Fn(u64, u64)) -> OperationResult<()> {
+        // Vector data is written once on creation and never modified, only deleted flags are
+        // flushed here. Report just those.
+        let deleted_bytes = files_size_in_bytes(&[self.deleted_path.clone()]) as u64;
+        progress(0, deleted_bytes);
+        self.flusher()()?;
+        progress(deleted_bytes, deleted_bytes);
+        Ok(())
+    }
+
+    fn mmap_advise(&self, advice: Advice) -> OperationResult<()> {
+        if let Some(mmap_store) = &self.mmap_store {
+            mmap_store.madvise(advice)?;
         }
+        Ok(())
     }
 
     fn quantize(
@@ -212,9 +411,17 @@ impl VectorStorage for MemmapVectorStorage {
     }
 
     fn delete_vector(&mut self, key: PointOffsetType) -> OperationResult<bool> {
+        self.check_writable()?;
+        self.evict_cached(&[key]);
         Ok(self.mmap_store.as_mut().unwrap().delete(key))
     }
 
+    fn delete_vectors(&mut self, keys: &[PointOffsetType]) -> OperationResult<usize> {
+        self.check_writable()?;
+        self.evict_cached(keys);
+        Ok(self.mmap_store.as_mut().unwrap().delete_many(keys))
+    }
+
     fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
         self.mmap_store.as_ref().unwrap().is_deleted_vector(key)
     }
@@ -223,6 +430,10 @@ impl VectorStorage for MemmapVectorStorage {
         self.mmap_store.as_ref().unwrap().deleted_count
     }
 
+    fn recount_deleted_vectors(&mut self) -> usize {
+        self.mmap_store.as_mut().unwrap().recount_deleted()
+    }
+
     fn deleted_vector_bitslice(&self) -> &BitSlice {
         self.mmap_store.as_ref().unwrap().deleted_vector_bitslice()
     }
@@ -240,6 +451,7 @@ fn open_append<P: AsRef<Path>>(path: P) -> io::Result<File> {
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
     use std::mem::transmute;
 
     use common::types::ScoredPointOffset;
@@ -327,7 +539,8 @@ mod tests {
             points[2].as_slice().into(),
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
-        );
+        )
+        .unwrap();
         let res = raw_scorer.peek_top_all(2);
 
         assert_eq!(res.len(), 2);
@@ -340,6 +553,161 @@ mod tests {
         assert_ne!(res[0].idx, 2);
     }
 
+    #[test]
+    fn test_recount_deleted_vectors() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+            for i in 0..5 {
+                storage2
+                    .borrow_mut()
+                    .insert_vector(i, &[1.0, 0.0, 1.0, 1.0])
+                    .unwrap();
+            }
+            borrowed_storage
+                .update_from(&storage2.borrow(), &mut (0..5), &Default::default())
+                .unwrap();
+        }
+        borrowed_storage.delete_vector(1).unwrap();
+        borrowed_storage.delete_vector(3).unwrap();
+        assert_eq!(borrowed_storage.recount_deleted_vectors(), 2);
+
+        // Simulate a stale cached count, as after a failed flush
+        let VectorStorageEnum::Memmap(memmap_storage) = &mut *borrowed_storage else {
+            panic!("memmap storage expected");
+        };
+        memmap_storage.mmap_store.as_mut().unwrap().deleted_count = 0;
+        assert_eq!(borrowed_storage.deleted_vector_count(), 0);
+
+        assert_eq!(borrowed_storage.recount_deleted_vectors(), 2);
+        assert_eq!(borrowed_storage.deleted_vector_count(), 2);
+        assert_eq!(borrowed_storage.available_vector_count(), 3);
+    }
+
+    #[test]
+    fn test_get_vector_with_cache() {
+        let points = [
+            [1.0, 0.0, 1.0, 1.0],
+            [1.0, 0.0, 1.0, 0.0],
+            [1.0, 1.0, 1.0, 1.0],
+        ];
+        let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+        let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+        let source = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        for (i, point) in points.iter().enumerate() {
+            source
+                .borrow_mut()
+                .insert_vector(i as PointOffsetType, point)
+                .unwrap();
+        }
+
+        let cached_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let uncached_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let cached = open_memmap_vector_storage_with_cache(
+            cached_dir.path(),
+            4,
+            Distance::Dot,
+            VectorStorageDatatype::Float32,
+            2,
+        )
+        .unwrap();
+        let uncached = open_memmap_vector_storage(uncached_dir.path(), 4, Distance::Dot).unwrap();
+        let mut cached_storage = cached.borrow_mut();
+        let mut uncached_storage = uncached.borrow_mut();
+        for storage in [&mut cached_storage, &mut uncached_storage] {
+            storage
+                .update_from(&source.borrow(), &mut (0..3), &Default::default())
+                .unwrap();
+        }
+        let (VectorStorageEnum::Memmap(cached), VectorStorageEnum::Memmap(uncached)) =
+            (&mut *cached_storage, &*uncached_storage)
+        else {
+            panic!("memmap storages expected");
+        };
+
+        for _ in 0..2 {
+            for (i, point) in points.iter().enumerate() {
+                let i = i as PointOffsetType;
+                assert_eq!(cached.get_vector(i).as_ref(), point);
+                assert_eq!(cached.get_vector(i), uncached.get_vector(i));
+            }
+        }
+        let is_cached = |storage: &MemmapVectorStorage, key| {
+            storage.vector_cache.as_ref().unwrap().lock().contains(key)
+        };
+        assert_eq!(cached.vector_cache.as_ref().unwrap().lock().len(), 2);
+        assert!(uncached.vector_cache.is_none());
+
+        // Deletion evicts the entry, vector data stays readable
+        assert!(is_cached(cached, 2));
+        cached.delete_vector(2).unwrap();
+        assert!(!is_cached(cached, 2));
+        assert_eq!(cached.get_vector(2).as_ref(), &points[2]);
+        cached.delete_vectors(&[1, 2]).unwrap();
+        assert!(cached.vector_cache.as_ref().unwrap().lock().is_empty());
+
+        // Scorers read vectors through the cache
+        let raw_scorer = new_raw_scorer(
+            points[0].as_slice().into(),
+            &cached_storage,
+            cached_storage.deleted_vector_bitslice(),
+        )
+        .unwrap();
+        raw_scorer.score_point(0);
+        drop(raw_scorer);
+        let VectorStorageEnum::Memmap(cached) = &*cached_storage else {
+            panic!("memmap storage expected");
+        };
+        assert!(cached.vector_cache.as_ref().unwrap().lock().contains(0));
+    }
+
+    #[test]
+    fn test_validate_counts() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        borrowed_storage.validate_counts().unwrap();
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+            for i in 0..5 {
+                storage2
+                    .borrow_mut()
+                    .insert_vector(i, &[1.0, 0.0, 1.0, 1.0])
+                    .unwrap();
+            }
+            borrowed_storage
+                .update_from(&storage2.borrow(), &mut (0..5), &Default::default())
+                .unwrap();
+        }
+        borrowed_storage.delete_vector(1).unwrap();
+        borrowed_storage.delete_vector(3).unwrap();
+        borrowed_storage.validate_counts().unwrap();
+
+        // Inject a stale cached count
+        let VectorStorageEnum::Memmap(memmap_storage) = &mut *borrowed_storage else {
+            panic!("memmap storage expected");
+        };
+        memmap_storage.mmap_store.as_mut().unwrap().deleted_count = 1;
+        let Err(OperationError::InconsistentStorage { description }) =
+            borrowed_storage.validate_counts()
+        else {
+            panic!("inconsistency is expected to be detected");
+        };
+        assert!(
+            description.contains("count 1 does not match 2"),
+            "{description}"
+        );
+
+        borrowed_storage.recount_deleted_vectors();
+        borrowed_storage.validate_counts().unwrap();
+    }
+
     #[test]
     fn test_delete_points() {
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
@@ -404,6 +772,7 @@ mod tests {
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
         )
+        .unwrap()
         .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 5);
         assert_eq!(closest.len(), 3, "must have 3 vectors, 2 are deleted");
         assert_eq!(closest[0].idx, 0);
@@ -431,6 +800,7 @@ mod tests {
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
         )
+        .unwrap()
         .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 5);
         assert_eq!(closest.len(), 2, "must have 2 vectors, 3 are deleted");
         assert_eq!(closest[0].idx, 4);
@@ -456,6 +826,7 @@ mod tests {
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
         )
+        .unwrap()
         .peek_top_all(5);
         assert!(closest.is_empty(), "must have no results, all deleted");
     }
@@ -517,6 +888,7 @@ mod tests {
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
         )
+        .unwrap()
         .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 5);
         assert_eq!(closest.len(), 3, "must have 3 vectors, 2 are deleted");
         assert_eq!(closest[0].idx, 0);
@@ -540,6 +912,146 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mmap_advise_sequential() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+
+        let points = vec![
+            vec![1.0, 0.0, 1.0, 1.0],
+            vec![1.0, 0.0, 1.0, 0.0],
+            vec![1.0, 1.0, 1.0, 1.0],
+            vec![1.0, 1.0, 0.0, 1.0],
+            vec![1.0, 0.0, 0.0, 0.0],
+        ];
+        let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(points.len())));
+        let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+
+        // Advice on an empty storage without mmap is a no-op
+        borrowed_storage.mmap_advise_sequential(|| ()).unwrap();
+
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+            {
+                let mut borrowed_storage2 = storage2.borrow_mut();
+                points.iter().enumerate().for_each(|(i, vec)| {
+                    borrowed_storage2
+                        .insert_vector(i as PointOffsetType, vec)
+                        .unwrap();
+                });
+            }
+            borrowed_storage
+                .update_from(
+                    &storage2.borrow(),
+                    &mut Box::new(0..points.len() as u32),
+                    &Default::default(),
+                )
+                .unwrap();
+        }
+
+        let full_scan = |storage: &VectorStorageEnum| {
+            let query = vec![0.0, 1.0, 1.1, 1.0];
+            new_raw_scorer(
+                query.into(),
+                storage,
+                id_tracker.borrow().deleted_point_bitslice(),
+            )
+            .unwrap()
+            .peek_top_all(points.len())
+        };
+
+        let sequential = borrowed_storage
+            .mmap_advise_sequential(|| full_scan(&borrowed_storage))
+            .unwrap();
+        let regular = full_scan(&borrowed_storage);
+
+        assert_eq!(sequential.len(), points.len());
+        assert_eq!(sequential, regular);
+    }
+
+    #[test]
+    fn test_read_only_storage() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+
+        // Opening non existing storage in read-only mode must not create it
+        assert!(open_memmap_vector_storage_read_only(
+            &dir.path().join("missing"),
+            4,
+            Distance::Dot
+        )
+        .is_err());
+        assert!(!dir.path().join("missing").exists());
+
+        let points = vec![
+            vec![1.0, 0.0, 1.0, 1.0],
+            vec![1.0, 0.0, 1.0, 0.0],
+            vec![1.0, 1.0, 1.0, 1.0],
+        ];
+        let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+        let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        {
+            let mut borrowed_storage2 = storage2.borrow_mut();
+            for (i, vec) in points.iter().enumerate() {
+                borrowed_storage2
+                    .insert_vector(i as PointOffsetType, vec)
+                    .unwrap();
+            }
+        }
+
+        {
+            let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+            let mut borrowed_storage = storage.borrow_mut();
+            borrowed_storage
+                .update_from(
+                    &storage2.borrow(),
+                    &mut Box::new(0..points.len() as u32),
+                    &Default::default(),
+                )
+                .unwrap();
+            borrowed_storage.delete_vector(1).unwrap();
+            // Vectors can't be updated in place, also not in writable storages
+            assert!(borrowed_storage.insert_vector(0, &points[1]).is_err());
+            assert_eq!(
+                borrowed_storage.get_vector(0).as_ref(),
+                points[0].as_slice()
+            );
+            borrowed_storage.flusher()().unwrap();
+        }
+
+        {
+            let storage =
+                open_memmap_vector_storage_read_only(dir.path(), 4, Distance::Dot).unwrap();
+            let mut borrowed_storage = storage.borrow_mut();
+            assert_eq!(borrowed_storage.total_vector_count(), points.len());
+            assert_eq!(&*borrowed_storage.get_vector(2), points[2].as_slice());
+            assert!(borrowed_storage.is_deleted_vector(1));
+
+            assert!(borrowed_storage.delete_vector(0).is_err());
+            assert!(borrowed_storage.insert_vector(0, &points[0]).is_err());
+            assert!(borrowed_storage
+                .update_from(
+                    &storage2.borrow(),
+                    &mut Box::new(0..points.len() as u32),
+                    &Default::default(),
+                )
+                .is_err());
+
+            assert_eq!(borrowed_storage.total_vector_count(), points.len());
+            assert_eq!(borrowed_storage.deleted_vector_count(), 1);
+            borrowed_storage.flusher()().unwrap();
+        }
+
+        // Nothing is changed on disk
+        let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.total_vector_count(), points.len());
+        assert!(!borrowed_storage.is_deleted_vector(0));
+        assert!(borrowed_storage.is_deleted_vector(1));
+    }
+
     #[test]
     fn test_mmap_raw_scorer() {
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
@@ -585,7 +1097,8 @@ mod tests {
             query,
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
-        );
+        )
+        .unwrap();
 
         let mut res = vec![ScoredPointOffset { idx: 0, score: 0. }; query_points.len()];
         let res_count = scorer.score_points(&query_points, &mut res);
@@ -599,6 +1112,79 @@ mod tests {
         assert_eq!(res[2].score, -1.0);
     }
 
+    #[test]
+    fn test_f16_storage_round_trip() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let points = vec![
+            vec![0.1, -0.25, 1.0 / 3.0, 42.42],
+            vec![1e-3, 0.5, -7.77, 1000.1],
+            vec![3.0, -1e6, 1e6, 0.0],
+        ];
+        let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+        let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+        let source = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        for (i, point) in points.iter().enumerate() {
+            source
+                .borrow_mut()
+                .insert_vector(i as PointOffsetType, point)
+                .unwrap();
+        }
+        source.borrow_mut().delete_vector(1).unwrap();
+
+        {
+            let storage = open_memmap_vector_storage_with_datatype(
+                dir.path(),
+                4,
+                Distance::Dot,
+                VectorStorageDatatype::Float16,
+            )
+            .unwrap();
+            let mut borrowed_storage = storage.borrow_mut();
+            borrowed_storage
+                .update_from(&source.borrow(), &mut (0..3), &Default::default())
+                .unwrap();
+            borrowed_storage.flusher()().unwrap();
+        }
+
+        // Elements take two bytes on disk
+        let file_size = dir.path().join(VECTORS_PATH).metadata().unwrap().len();
+        assert_eq!(file_size, 4 + 3 * 4 * 2);
+
+        let storage = open_memmap_vector_storage_read_only(dir.path(), 4, Distance::Dot).unwrap();
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.total_vector_count(), 3);
+        assert!(borrowed_storage.is_deleted_vector(1));
+        for (i, point) in points.iter().enumerate() {
+            let vector = borrowed_storage.get_vector(i as PointOffsetType);
+            assert!(matches!(vector, Cow::Owned(_)));
+            for (&decoded, &original) in vector.iter().zip(point) {
+                // Out of range elements saturate, others keep 11 significant bits
+                let expected = original.clamp(-65504.0, 65504.0);
+                assert!(
+                    (decoded - expected).abs() <= expected.abs() * 2f32.powi(-11),
+                    "{decoded} too far from {original}",
+                );
+            }
+        }
+
+        let query = vec![1.0, 0.5, -1.0, 0.1];
+        let scorer = new_raw_scorer(
+            query.as_slice().into(),
+            &borrowed_storage,
+            borrowed_storage.deleted_vector_bitslice(),
+        )
+        .unwrap();
+        let exact = query
+            .iter()
+            .zip(&points[0])
+            .map(|(a, b)| a * b)
+            .sum::<f32>();
+        assert!((scorer.score_point(0) - exact).abs() < 0.01);
+
+        // Existing vectors must be opened with the datatype they are stored with
+        assert!(open_memmap_vector_storage(dir.path(), 4, Distance::Dot).is_err());
+    }
+
     #[test]
     fn test_casts() {
         let data: Vec<VectorElementType> = vec![0.42, 0.069, 333.1, 100500.];
@@ -670,17 +1256,22 @@ mod tests {
         let query: QueryVector = [0.5, 0.5, 0.5, 0.5].into();
 
         {
-            let scorer_quant = borrowed_storage.quantized_storage().unwrap().raw_scorer(
-                query.clone(),
-                borrowed_id_tracker.deleted_point_bitslice(),
-                borrowed_storage.deleted_vector_bitslice(),
-                &stopped,
-            );
+            let scorer_quant = borrowed_storage
+                .quantized_storage()
+                .unwrap()
+                .raw_scorer(
+                    query.clone(),
+                    borrowed_id_tracker.deleted_point_bitslice(),
+                    borrowed_storage.deleted_vector_bitslice(),
+                    &stopped,
+                )
+                .unwrap();
             let scorer_orig = new_raw_scorer(
                 query.clone(),
                 &borrowed_storage,
                 borrowed_id_tracker.deleted_point_bitslice(),
-            );
+            )
+            .unwrap();
             for i in 0..5 {
                 let quant = scorer_quant.score_point(i);
                 let orig = scorer_orig.score_point(i);
@@ -695,17 +1286,22 @@ mod tests {
         // test save-load
         borrowed_storage.load_quantization(dir.path()).unwrap();
 
-        let scorer_quant = borrowed_storage.quantized_storage().unwrap().raw_scorer(
-            query.clone(),
-            borrowed_id_tracker.deleted_point_bitslice(),
-            borrowed_storage.deleted_vector_bitslice(),
-            &stopped,
-        );
+        let scorer_quant = borrowed_storage
+            .quantized_storage()
+            .unwrap()
+            .raw_scorer(
+                query.clone(),
+                borrowed_id_tracker.deleted_point_bitslice(),
+                borrowed_storage.deleted_vector_bitslice(),
+                &stopped,
+            )
+            .unwrap();
         let scorer_orig = new_raw_scorer(
             query,
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
-        );
+        )
+        .unwrap();
 
         for i in 0..5 {
             let quant = scorer_quant.score_point(i);
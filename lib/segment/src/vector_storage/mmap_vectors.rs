@@ -1,5 +1,6 @@
+use std::borrow::Cow;
 use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::io::{self, Write};
 use std::mem::{self, size_of, transmute};
 use std::path::Path;
 use std::sync::atomic::AtomicBool;
@@ -7,31 +8,38 @@ use std::sync::Arc;
 
 use bitvec::prelude::BitSlice;
 use common::types::PointOffsetType;
+use half::f16;
 use memmap2::Mmap;
+use memory::madvise::{self, Advice};
 use memory::mmap_ops;
 use parking_lot::Mutex;
 
+use super::chunked_vectors::ChunkedVectors;
 use super::div_ceil;
 use crate::common::error_logging::LogError;
 use crate::common::mmap_type::MmapBitSlice;
-use crate::common::operation_error::OperationResult;
+use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
 use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
-use crate::types::{Distance, QuantizationConfig};
+use crate::data_types::vectors::{CowVector, VectorElementType};
+use crate::types::{Distance, QuantizationConfig, VectorStorageDatatype};
 #[cfg(target_os = "linux")]
 use crate::vector_storage::async_io::UringReader;
 #[cfg(not(target_os = "linux"))]
 use crate::vector_storage::async_io_mock::UringReader;
+use crate::vector_storage::common::STOP_CHECK_INTERVAL;
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
 
 const HEADER_SIZE: usize = 4;
 const VECTORS_HEADER: &[u8; HEADER_SIZE] = b"data";
+const F16_VECTORS_HEADER: &[u8; HEADER_SIZE] = b"dh16";
 const DELETED_HEADER: &[u8; HEADER_SIZE] = b"drop";
 
 /// Mem-mapped file
 pub struct MmapVectors {
     pub dim: usize,
     pub num_vectors: usize,
+    /// Element type vectors are stored with, told apart by the header of the vectors file
+    pub datatype: VectorStorageDatatype,
     /// Memory mapped file for vector data
     ///
     /// Has an exact size to fit a header and `num_vectors` of vectors.
@@ -47,24 +55,80 @@ pub struct MmapVectors {
 }
 
 impl MmapVectors {
+    /// Open or create files, existing vectors must be stored with the given `datatype`
     pub fn open(
         vectors_path: &Path,
         deleted_path: &Path,
         dim: usize,
+        datatype: VectorStorageDatatype,
         with_async_io: bool,
     ) -> OperationResult<Self> {
+        Self::open_impl(
+            vectors_path,
+            deleted_path,
+            dim,
+            Some(datatype),
+            with_async_io,
+        )
+    }
+
+    /// Open existing files without ever writing to them
+    ///
+    /// Deletion flags are mapped copy-on-write, so no change can reach the disk. The datatype is
+    /// taken from the existing vectors file.
+    pub fn open_read_only(
+        vectors_path: &Path,
+        deleted_path: &Path,
+        dim: usize,
+        with_async_io: bool,
+    ) -> OperationResult<Self> {
+        Self::open_impl(vectors_path, deleted_path, dim, None, with_async_io)
+    }
+
+    /// Files are opened read-only if no `datatype` is given
+    fn open_impl(
+        vectors_path: &Path,
+        deleted_path: &Path,
+        dim: usize,
+        datatype: Option<VectorStorageDatatype>,
+        with_async_io: bool,
+    ) -> OperationResult<Self> {
+        let read_only = datatype.is_none();
+
         // Allocate/open vectors mmap
-        ensure_mmap_file_size(vectors_path, VECTORS_HEADER, None)
-            .describe("Create mmap data file")?;
+        match datatype {
+            None => ensure_mmap_file_exists(vectors_path, HEADER_SIZE as u64)?,
+            Some(datatype) => ensure_mmap_file_size(vectors_path, vectors_header(datatype), None)
+                .describe("Create mmap data file")?,
+        }
         let mmap = mmap_ops::open_read_mmap(vectors_path).describe("Open mmap for reading")?;
-        let num_vectors = (mmap.len() - HEADER_SIZE) / dim / size_of::<VectorElementType>();
+        let stored_datatype = datatype_from_header(&mmap[..HEADER_SIZE.min(mmap.len())])
+            .ok_or_else(|| {
+                OperationError::service_error(format!(
+                    "Mmap data file {vectors_path:?} has an unknown header"
+                ))
+            })?;
+        if let Some(datatype) = datatype {
+            if datatype != stored_datatype {
+                return Err(OperationError::service_error(format!(
+                    "Mmap data file {vectors_path:?} stores {stored_datatype:?} vectors, expected {datatype:?}",
+                )));
+            }
+        }
+        let datatype = stored_datatype;
+        let num_vectors = (mmap.len() - HEADER_SIZE) / dim / datatype.element_size();
 
         // Allocate/open deleted mmap
         let deleted_mmap_size = deleted_mmap_size(num_vectors);
-        ensure_mmap_file_size(deleted_path, DELETED_HEADER, Some(deleted_mmap_size as u64))
-            .describe("Create mmap deleted file")?;
-        let deleted_mmap =
-            mmap_ops::open_write_mmap(deleted_path).describe("Open mmap deleted for writing")?;
+        let deleted_mmap = if read_only {
+            ensure_mmap_file_exists(deleted_path, deleted_mmap_size as u64)?;
+            mmap_ops::open_copy_on_write_mmap(deleted_path)
+                .describe("Open mmap deleted for reading")?
+        } else {
+            ensure_mmap_file_size(deleted_path, DELETED_HEADER, Some(deleted_mmap_size as u64))
+                .describe("Create mmap deleted file")?;
+            mmap_ops::open_write_mmap(deleted_path).describe("Open mmap deleted for writing")?
+        };
 
         // Advise kernel that we'll need this page soon so the kernel can prepare
         #[cfg(unix)]
@@ -76,7 +140,8 @@ impl MmapVectors {
         let deleted = MmapBitSlice::try_from(deleted_mmap, deleted_mmap_data_start())?;
         let deleted_count = deleted.count_ones();
 
-        let uring_reader = if with_async_io {
+        // Async IO hands out stored vectors as they are, which is only possible for f32
+        let uring_reader = if with_async_io && datatype == VectorStorageDatatype::Float32 {
             // Keep file handle open for async IO
             let vectors_file = File::open(vectors_path)?;
             let raw_size = dim * size_of::<VectorElementType>();
@@ -88,6 +153,7 @@ impl MmapVectors {
         Ok(MmapVectors {
             dim,
             num_vectors,
+            datatype,
             mmap: mmap.into(),
             uring_reader: Mutex::new(uring_reader),
             deleted,
@@ -104,6 +170,11 @@ impl MmapVectors {
         self.deleted.flusher()
     }
 
+    /// Advise OS how the vector data will be accessed
+    pub fn madvise(&self, advice: Advice) -> io::Result<()> {
+        madvise::madvise(self.mmap.as_ref(), advice)
+    }
+
     pub fn quantize(
         &mut self,
         distance: Distance,
@@ -119,21 +190,50 @@ impl MmapVectors {
         // speedup is not measured explicitly.
         // See <https://github.com/qdrant/qdrant/pull/1885#issuecomment-1547408116>
 
-        let vector_data_iterator = (0..self.num_vectors as u32).map(|i| {
-            let offset = self.data_offset(i as PointOffsetType).unwrap_or_default();
-            self.raw_vector_offset(offset)
-        });
-        self.quantized_vectors = Some(QuantizedVectors::create(
-            vector_data_iterator,
-            quantization_config,
-            distance,
-            self.dim,
-            self.num_vectors,
-            data_path,
-            true,
-            max_threads,
-            stopped,
-        )?);
+        let quantized_vectors = match self.datatype {
+            VectorStorageDatatype::Float32 => {
+                let vector_data_iterator = (0..self.num_vectors as u32).map(|i| {
+                    let offset = self.data_offset(i as PointOffsetType).unwrap_or_default();
+                    self.raw_vector_offset(offset)
+                });
+                QuantizedVectors::create(
+                    vector_data_iterator,
+                    quantization_config,
+                    distance,
+                    self.dim,
+                    self.num_vectors,
+                    data_path,
+                    true,
+                    max_threads,
+                    stopped,
+                )?
+            }
+            // Quantization reads vectors as f32 slices and passes over them more than once,
+            // decode them vector by vector into chunked buffers instead of one contiguous `Vec`
+            VectorStorageDatatype::Float16 => {
+                let mut decoded = ChunkedVectors::<VectorElementType>::new(self.dim);
+                for key in 0..self.num_vectors as PointOffsetType {
+                    if key as usize % STOP_CHECK_INTERVAL == 0 {
+                        check_process_stopped(stopped)?;
+                    }
+                    decoded.push(&self.get_vector(key))?;
+                }
+                let vector_data_iterator =
+                    (0..self.num_vectors as PointOffsetType).map(|key| decoded.get(key));
+                QuantizedVectors::create(
+                    vector_data_iterator,
+                    quantization_config,
+                    distance,
+                    self.dim,
+                    self.num_vectors,
+                    data_path,
+                    true,
+                    max_threads,
+                    stopped,
+                )?
+            }
+        };
+        self.quantized_vectors = Some(quantized_vectors);
         Ok(())
     }
 
@@ -156,7 +256,7 @@ impl MmapVectors {
     }
 
     pub fn data_offset(&self, key: PointOffsetType) -> Option<usize> {
-        let vector_data_length = self.dim * size_of::<VectorElementType>();
+        let vector_data_length = self.raw_size();
         let offset = (key as usize) * vector_data_length + HEADER_SIZE;
         if key >= (self.num_vectors as PointOffsetType) {
             return None;
@@ -165,19 +265,26 @@ impl MmapVectors {
     }
 
     pub fn raw_size(&self) -> usize {
-        self.dim * size_of::<VectorElementType>()
+        self.dim * self.datatype.element_size()
     }
 
+    /// Stored vector at the given byte offset, only valid for f32 vectors
     pub fn raw_vector_offset(&self, offset: usize) -> &[VectorElementType] {
+        debug_assert_eq!(self.datatype, VectorStorageDatatype::Float32);
         let byte_slice = &self.mmap[offset..(offset + self.raw_size())];
         let arr: &[VectorElementType] = unsafe { transmute(byte_slice) };
         &arr[0..self.dim]
     }
 
-    /// Returns reference to vector data by key
-    pub fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
+    /// Returns vector data by key, borrowed for f32 and decoded for f16 vectors
+    pub fn get_vector(&self, key: PointOffsetType) -> CowVector<'_> {
         let offset = self.data_offset(key).unwrap();
-        self.raw_vector_offset(offset)
+        match self.datatype {
+            VectorStorageDatatype::Float32 => Cow::Borrowed(self.raw_vector_offset(offset)),
+            VectorStorageDatatype::Float16 => Cow::Owned(decode_f16_vector(
+                &self.mmap[offset..(offset + self.raw_size())],
+            )),
+        }
     }
 
     pub fn delete(&mut self, key: PointOffsetType) -> bool {
@@ -192,6 +299,26 @@ impl MmapVectors {
         is_deleted
     }
 
+    /// Same as [`MmapVectors::delete`] for many keys, updating deleted count once
+    ///
+    /// Returns the number of newly deleted vectors.
+    pub fn delete_many(&mut self, keys: &[PointOffsetType]) -> usize {
+        let mut newly_deleted = 0;
+        for &key in keys {
+            if (key as usize) < self.num_vectors && !self.deleted.replace(key as usize, true) {
+                newly_deleted += 1;
+            }
+        }
+        self.deleted_count += newly_deleted;
+        newly_deleted
+    }
+
+    /// Recompute cached deleted count from deletion flags
+    pub fn recount_deleted(&mut self) -> usize {
+        self.deleted_count = self.deleted.count_ones();
+        self.deleted_count
+    }
+
     pub fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
         self.deleted[key as usize]
     }
@@ -229,7 +356,7 @@ impl MmapVectors {
     ) -> OperationResult<()> {
         for (idx, point) in points.enumerate() {
             let vector = self.get_vector(point);
-            callback(idx, point, vector);
+            callback(idx, point, &vector);
         }
         Ok(())
     }
@@ -254,6 +381,51 @@ impl MmapVectors {
     }
 }
 
+fn vectors_header(datatype: VectorStorageDatatype) -> &'static [u8; HEADER_SIZE] {
+    match datatype {
+        VectorStorageDatatype::Float32 => VECTORS_HEADER,
+        VectorStorageDatatype::Float16 => F16_VECTORS_HEADER,
+    }
+}
+
+fn datatype_from_header(header: &[u8]) -> Option<VectorStorageDatatype> {
+    [
+        VectorStorageDatatype::Float32,
+        VectorStorageDatatype::Float16,
+    ]
+    .into_iter()
+    .find(|&datatype| header == vectors_header(datatype))
+}
+
+/// Encode vector elements as they are stored in a vectors file with the given `datatype`
+///
+/// Elements out of the f16 range are saturated to its largest finite value.
+pub fn encode_vector(vector: &[VectorElementType], datatype: VectorStorageDatatype) -> Cow<[u8]> {
+    match datatype {
+        VectorStorageDatatype::Float32 => Cow::Borrowed(mmap_ops::transmute_to_u8_slice(vector)),
+        VectorStorageDatatype::Float16 => {
+            let max = f16::MAX.to_f32();
+            Cow::Owned(
+                vector
+                    .iter()
+                    .flat_map(|&element| {
+                        f16::from_f32(element.clamp(-max, max))
+                            .to_bits()
+                            .to_le_bytes()
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+fn decode_f16_vector(bytes: &[u8]) -> Vec<VectorElementType> {
+    bytes
+        .chunks_exact(size_of::<u16>())
+        .map(|element| f16::from_bits(u16::from_le_bytes([element[0], element[1]])).to_f32())
+        .collect()
+}
+
 /// Ensure the given mmap file exists and is the given size
 ///
 /// # Arguments
@@ -281,6 +453,24 @@ fn ensure_mmap_file_size(path: &Path, header: &[u8], size: Option<u64>) -> Opera
     Ok(())
 }
 
+/// Check that file exists and has at least `min_size` bytes, without modifying it
+fn ensure_mmap_file_exists(path: &Path, min_size: u64) -> OperationResult<()> {
+    let size = match path.metadata() {
+        Ok(metadata) => metadata.len(),
+        Err(err) => {
+            return Err(OperationError::service_error(format!(
+                "Can't open {path:?} in read-only mode: {err}"
+            )))
+        }
+    };
+    if size < min_size {
+        return Err(OperationError::service_error(format!(
+            "Can't open {path:?} in read-only mode: expected at least {min_size} bytes, got {size}"
+        )));
+    }
+    Ok(())
+}
+
 /// Get start position of flags `BitSlice` in deleted mmap.
 #[inline]
 const fn deleted_mmap_data_start() -> usize {
@@ -1,3 +1,4 @@
+mod quantized_context_query_scorer;
 mod quantized_mmap_storage;
 mod quantized_query_scorer;
 mod quantized_reco_query_scorer;
@@ -0,0 +1,74 @@
+use common::types::{PointOffsetType, ScoreType};
+
+use super::quantized_query_scorer::to_similarity;
+use crate::data_types::vectors::{VectorElementType, VectorType};
+use crate::spaces::metric::PreprocessedVector;
+use crate::types::Distance;
+use crate::vector_storage::query::context_query::ContextQuery;
+use crate::vector_storage::query_scorer::QueryScorer;
+
+pub struct QuantizedContextQueryScorer<'a, TEncodedQuery, TEncodedVectors>
+where
+    TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
+{
+    original_query: ContextQuery<PreprocessedVector>,
+    query: ContextQuery<TEncodedQuery>,
+    quantized_storage: &'a TEncodedVectors,
+    distance: Distance,
+    dim: usize,
+}
+
+impl<'a, TEncodedQuery, TEncodedVectors>
+    QuantizedContextQueryScorer<'a, TEncodedQuery, TEncodedVectors>
+where
+    TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
+{
+    pub fn new(
+        raw_query: ContextQuery<VectorType>,
+        quantized_storage: &'a TEncodedVectors,
+        distance: Distance,
+    ) -> Self {
+        let original_query = raw_query.transform(|v| distance.preprocess_vector(v));
+        let query = original_query
+            .clone()
+            .transform(|v| quantized_storage.encode_query(&v));
+        let dim = original_query.iter_all().next().map_or(0, |v| v.len());
+
+        Self {
+            original_query,
+            query,
+            quantized_storage,
+            distance,
+            dim,
+        }
+    }
+}
+
+impl<TEncodedQuery, TEncodedVectors> QueryScorer
+    for QuantizedContextQueryScorer<'_, TEncodedQuery, TEncodedVectors>
+where
+    TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
+{
+    fn score_stored(&self, idx: PointOffsetType) -> ScoreType {
+        self.query.score_by(|this| {
+            let score = self.quantized_storage.score_point(this, idx);
+            to_similarity(self.distance, self.dim, score)
+        })
+    }
+
+    fn score(&self, v2: &[VectorElementType]) -> ScoreType {
+        debug_assert!(
+            false,
+            "This method is not expected to be called for quantized scorer"
+        );
+        self.original_query
+            .score_by(|this| self.distance.similarity(this, v2))
+    }
+
+    /// Query examples are not involved in comparing two stored points, so this falls back to
+    /// plain quantized similarity, same as for nearest search
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let score = self.quantized_storage.score_internal(point_a, point_b);
+        to_similarity(self.distance, self.dim, score)
+    }
+}
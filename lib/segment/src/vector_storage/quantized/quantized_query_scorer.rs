@@ -1,6 +1,9 @@
+use std::ops::Deref;
+
 use common::types::{PointOffsetType, ScoreType};
 
 use crate::data_types::vectors::{VectorElementType, VectorType};
+use crate::spaces::metric::PreprocessedVector;
 use crate::types::Distance;
 use crate::vector_storage::query_scorer::QueryScorer;
 
@@ -8,12 +11,29 @@ pub struct QuantizedQueryScorer<'a, TEncodedQuery, TEncodedVectors>
 where
     TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
 {
-    original_query: VectorType,
-    query: TEncodedQuery,
+    original_query: MaybeOwned<'a, PreprocessedVector>,
+    query: MaybeOwned<'a, TEncodedQuery>,
     quantized_data: &'a TEncodedVectors,
     distance: Distance,
 }
 
+/// Query owned by the scorer, or shared by many scorers
+enum MaybeOwned<'a, T> {
+    Owned(T),
+    Borrowed(&'a T),
+}
+
+impl<T> Deref for MaybeOwned<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            MaybeOwned::Owned(value) => value,
+            MaybeOwned::Borrowed(value) => value,
+        }
+    }
+}
+
 impl<'a, TEncodedQuery, TEncodedVectors> QuantizedQueryScorer<'a, TEncodedQuery, TEncodedVectors>
 where
     TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
@@ -27,21 +47,48 @@ where
         let query = quantized_data.encode_query(&original_query);
 
         Self {
-            original_query,
-            query,
+            original_query: MaybeOwned::Owned(original_query),
+            query: MaybeOwned::Owned(query),
+            quantized_data,
+            distance,
+        }
+    }
+
+    /// Build scorer from a query, which is already preprocessed and encoded for `quantized_data`
+    pub fn new_encoded(
+        original_query: &'a PreprocessedVector,
+        query: &'a TEncodedQuery,
+        quantized_data: &'a TEncodedVectors,
+        distance: Distance,
+    ) -> Self {
+        Self {
+            original_query: MaybeOwned::Borrowed(original_query),
+            query: MaybeOwned::Borrowed(query),
             quantized_data,
             distance,
         }
     }
 }
 
+/// Convert score of quantized storage into similarity of `distance`
+///
+/// Binary quantized vectors are scored by dot product of `±1` bits, which is
+/// `dim - 2 * hamming`. Convert it into Hamming similarity, same as `HammingMetric`.
+pub(super) fn to_similarity(distance: Distance, dim: usize, score: ScoreType) -> ScoreType {
+    match distance {
+        Distance::Hamming => -(dim as ScoreType - score) / 2.0,
+        _ => score,
+    }
+}
+
 impl<TEncodedQuery, TEncodedVectors> QueryScorer
     for QuantizedQueryScorer<'_, TEncodedQuery, TEncodedVectors>
 where
     TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
 {
     fn score_stored(&self, idx: PointOffsetType) -> ScoreType {
-        self.quantized_data.score_point(&self.query, idx)
+        let score = self.quantized_data.score_point(&self.query, idx);
+        to_similarity(self.distance, self.original_query.len(), score)
     }
 
     fn score(&self, v2: &[VectorElementType]) -> ScoreType {
@@ -53,6 +100,7 @@ where
     }
 
     fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
-        self.quantized_data.score_internal(point_a, point_b)
+        let score = self.quantized_data.score_internal(point_a, point_b);
+        to_similarity(self.distance, self.original_query.len(), score)
     }
 }
@@ -1,6 +1,8 @@
 use common::types::{PointOffsetType, ScoreType};
 
+use super::quantized_query_scorer::to_similarity;
 use crate::data_types::vectors::{VectorElementType, VectorType};
+use crate::spaces::metric::PreprocessedVector;
 use crate::types::Distance;
 use crate::vector_storage::query::reco_query::RecoQuery;
 use crate::vector_storage::query_scorer::QueryScorer;
@@ -9,10 +11,11 @@ pub struct QuantizedRecoQueryScorer<'a, TEncodedQuery, TEncodedVectors>
 where
     TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
 {
-    original_query: RecoQuery<VectorType>,
+    original_query: RecoQuery<PreprocessedVector>,
     query: RecoQuery<TEncodedQuery>,
     quantized_storage: &'a TEncodedVectors,
     distance: Distance,
+    dim: usize,
 }
 
 impl<'a, TEncodedQuery, TEncodedVectors>
@@ -29,12 +32,14 @@ where
         let query = original_query
             .clone()
             .transform(|v| quantized_storage.encode_query(&v));
+        let dim = original_query.iter_all().next().map_or(0, |v| v.len());
 
         Self {
             original_query,
             query,
             quantized_storage,
             distance,
+            dim,
         }
     }
 }
@@ -45,8 +50,10 @@ where
     TEncodedVectors: quantization::EncodedVectors<TEncodedQuery>,
 {
     fn score_stored(&self, idx: PointOffsetType) -> ScoreType {
-        self.query
-            .score_by(|this| self.quantized_storage.score_point(this, idx))
+        self.query.score_by(|this| {
+            let score = self.quantized_storage.score_point(this, idx);
+            to_similarity(self.distance, self.dim, score)
+        })
     }
 
     fn score(&self, v2: &[VectorElementType]) -> ScoreType {
@@ -58,7 +65,10 @@ where
             .score_by(|this| self.distance.similarity(this, v2))
     }
 
-    fn score_internal(&self, _point_a: PointOffsetType, _point_b: PointOffsetType) -> ScoreType {
-        unimplemented!("Recommendation scorer compares against multiple vectors, not just one")
+    /// Query examples are not involved in comparing two stored points, so this falls back to
+    /// plain quantized similarity, same as for nearest search
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let score = self.quantized_storage.score_internal(point_a, point_b);
+        to_similarity(self.distance, self.dim, score)
     }
 }
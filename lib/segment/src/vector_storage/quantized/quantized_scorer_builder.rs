@@ -3,22 +3,29 @@ use std::sync::atomic::AtomicBool;
 use bitvec::slice::BitSlice;
 use quantization::EncodedVectors;
 
+use super::quantized_context_query_scorer::QuantizedContextQueryScorer;
 use super::quantized_query_scorer::QuantizedQueryScorer;
 use super::quantized_reco_query_scorer::QuantizedRecoQueryScorer;
-use super::quantized_vectors::QuantizedVectorStorage;
+use super::quantized_vectors::{EncodedQuery, QuantizedVectorStorage};
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::data_types::vectors::QueryVector;
 use crate::types::Distance;
 use crate::vector_storage::{raw_scorer_from_query_scorer, RawScorer};
 
 pub(super) struct QuantizedScorerBuilder<'a> {
     quantized_storage: &'a QuantizedVectorStorage,
-    query: QueryVector,
+    query: BuilderQuery<'a>,
     point_deleted: &'a BitSlice,
     vec_deleted: &'a BitSlice,
     is_stopped: &'a AtomicBool,
     distance: &'a Distance,
 }
 
+enum BuilderQuery<'a> {
+    Raw(QueryVector),
+    Encoded(&'a EncodedQuery),
+}
+
 impl<'a> QuantizedScorerBuilder<'a> {
     pub fn new(
         quantized_storage: &'a QuantizedVectorStorage,
@@ -30,7 +37,25 @@ impl<'a> QuantizedScorerBuilder<'a> {
     ) -> Self {
         Self {
             quantized_storage,
-            query,
+            query: BuilderQuery::Raw(query),
+            point_deleted,
+            vec_deleted,
+            is_stopped,
+            distance,
+        }
+    }
+
+    pub fn new_encoded(
+        quantized_storage: &'a QuantizedVectorStorage,
+        query: &'a EncodedQuery,
+        point_deleted: &'a BitSlice,
+        vec_deleted: &'a BitSlice,
+        is_stopped: &'a AtomicBool,
+        distance: &'a Distance,
+    ) -> Self {
+        Self {
+            quantized_storage,
+            query: BuilderQuery::Encoded(query),
             point_deleted,
             vec_deleted,
             is_stopped,
@@ -38,7 +63,7 @@ impl<'a> QuantizedScorerBuilder<'a> {
         }
     }
 
-    pub fn build(self) -> Box<dyn RawScorer + 'a> {
+    pub fn build(self) -> OperationResult<Box<dyn RawScorer + 'a>> {
         match self.quantized_storage {
             QuantizedVectorStorage::ScalarRam(storage) => self.new_quantized_scorer(storage),
             QuantizedVectorStorage::ScalarMmap(storage) => self.new_quantized_scorer(storage),
@@ -50,10 +75,10 @@ impl<'a> QuantizedScorerBuilder<'a> {
     }
 
     #[inline]
-    fn new_quantized_scorer<TEncodedQuery: 'a>(
+    fn new_quantized_scorer<TEncodedQuery: Send + Sync + 'static>(
         self,
         quantized_storage: &'a impl EncodedVectors<TEncodedQuery>,
-    ) -> Box<dyn RawScorer + 'a> {
+    ) -> OperationResult<Box<dyn RawScorer + 'a>> {
         let Self {
             quantized_storage: _same_as_quantized_storage_in_args,
             query,
@@ -63,16 +88,74 @@ impl<'a> QuantizedScorerBuilder<'a> {
             distance,
         } = self;
 
-        match query {
+        let query = match query {
+            BuilderQuery::Raw(query) => query,
+            BuilderQuery::Encoded(encoded_query) => {
+                let encoded = encoded_query
+                    .encoded
+                    .downcast_ref::<TEncodedQuery>()
+                    .ok_or_else(|| {
+                        OperationError::service_error(
+                            "Query is encoded for another kind of quantization",
+                        )
+                    })?;
+                let query_scorer = QuantizedQueryScorer::new_encoded(
+                    &encoded_query.original_query,
+                    encoded,
+                    quantized_storage,
+                    *distance,
+                );
+                return Ok(raw_scorer_from_query_scorer(
+                    query_scorer,
+                    point_deleted,
+                    vec_deleted,
+                    is_stopped,
+                    None,
+                ));
+            }
+        };
+
+        let raw_scorer = match query {
             QueryVector::Nearest(vector) => {
-                let query_scorer = QuantizedQueryScorer::new(vector, quantized_storage, *distance);
-                raw_scorer_from_query_scorer(query_scorer, point_deleted, vec_deleted, is_stopped)
+                let query_scorer =
+                    QuantizedQueryScorer::new(vector.try_into()?, quantized_storage, *distance);
+                raw_scorer_from_query_scorer(
+                    query_scorer,
+                    point_deleted,
+                    vec_deleted,
+                    is_stopped,
+                    None,
+                )
             }
             QueryVector::Recommend(reco_query) => {
-                let query_scorer =
-                    QuantizedRecoQueryScorer::new(reco_query, quantized_storage, *distance);
-                raw_scorer_from_query_scorer(query_scorer, point_deleted, vec_deleted, is_stopped)
+                let query_scorer = QuantizedRecoQueryScorer::new(
+                    reco_query.try_into()?,
+                    quantized_storage,
+                    *distance,
+                );
+                raw_scorer_from_query_scorer(
+                    query_scorer,
+                    point_deleted,
+                    vec_deleted,
+                    is_stopped,
+                    None,
+                )
             }
-        }
+            QueryVector::Context(context_query) => {
+                let query_scorer = QuantizedContextQueryScorer::new(
+                    context_query.try_into()?,
+                    quantized_storage,
+                    *distance,
+                );
+                raw_scorer_from_query_scorer(
+                    query_scorer,
+                    point_deleted,
+                    vec_deleted,
+                    is_stopped,
+                    None,
+                )
+            }
+        };
+        Ok(raw_scorer)
     }
 }
@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -8,9 +9,10 @@ use quantization::{EncodedVectors, EncodedVectorsPQ, EncodedVectorsU8};
 use serde::{Deserialize, Serialize};
 
 use super::quantized_scorer_builder::QuantizedScorerBuilder;
-use crate::common::operation_error::OperationResult;
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::vector_utils::TrySetCapacityExact;
 use crate::data_types::vectors::{QueryVector, VectorElementType};
+use crate::spaces::metric::PreprocessedVector;
 use crate::types::{
     BinaryQuantization, BinaryQuantizationConfig, CompressionRatio, Distance, ProductQuantization,
     ProductQuantizationConfig, QuantizationConfig, ScalarQuantization, ScalarQuantizationConfig,
@@ -40,6 +42,16 @@ pub enum QuantizedVectorStorage {
     BinaryMmap(EncodedVectorsBin<QuantizedMmapStorage>),
 }
 
+/// Nearest query, preprocessed and encoded for specific quantized vectors
+///
+/// Built by [`QuantizedVectors::encode_query`], may be reused for many scorers over the same
+/// quantized vectors with [`QuantizedVectors::raw_scorer_encoded`].
+pub struct EncodedQuery {
+    pub(super) original_query: PreprocessedVector,
+    /// Query type depends on the kind of quantization
+    pub(super) encoded: Box<dyn Any + Send + Sync>,
+}
+
 pub struct QuantizedVectors {
     storage_impl: QuantizedVectorStorage,
     config: QuantizedVectorsConfig,
@@ -61,7 +73,7 @@ impl QuantizedVectors {
         point_deleted: &'a BitSlice,
         vec_deleted: &'a BitSlice,
         is_stopped: &'a AtomicBool,
-    ) -> Box<dyn RawScorer + 'a> {
+    ) -> OperationResult<Box<dyn RawScorer + 'a>> {
         QuantizedScorerBuilder::new(
             &self.storage_impl,
             query,
@@ -73,6 +85,56 @@ impl QuantizedVectors {
         .build()
     }
 
+    /// Preprocess and encode nearest query once, to build many scorers with it
+    pub fn encode_query(&self, query: &[VectorElementType]) -> EncodedQuery {
+        let original_query = self.distance.preprocess_vector(query.to_vec());
+        let encoded: Box<dyn Any + Send + Sync> = match &self.storage_impl {
+            QuantizedVectorStorage::ScalarRam(storage) => {
+                Box::new(storage.encode_query(&original_query))
+            }
+            QuantizedVectorStorage::ScalarMmap(storage) => {
+                Box::new(storage.encode_query(&original_query))
+            }
+            QuantizedVectorStorage::PQRam(storage) => {
+                Box::new(storage.encode_query(&original_query))
+            }
+            QuantizedVectorStorage::PQMmap(storage) => {
+                Box::new(storage.encode_query(&original_query))
+            }
+            QuantizedVectorStorage::BinaryRam(storage) => {
+                Box::new(storage.encode_query(&original_query))
+            }
+            QuantizedVectorStorage::BinaryMmap(storage) => {
+                Box::new(storage.encode_query(&original_query))
+            }
+        };
+        EncodedQuery {
+            original_query,
+            encoded,
+        }
+    }
+
+    /// Same as [`QuantizedVectors::raw_scorer`] for nearest query, without encoding it again
+    ///
+    /// Fails if `query` was encoded for another kind of quantization.
+    pub fn raw_scorer_encoded<'a>(
+        &'a self,
+        query: &'a EncodedQuery,
+        point_deleted: &'a BitSlice,
+        vec_deleted: &'a BitSlice,
+        is_stopped: &'a AtomicBool,
+    ) -> OperationResult<Box<dyn RawScorer + 'a>> {
+        QuantizedScorerBuilder::new_encoded(
+            &self.storage_impl,
+            query,
+            point_deleted,
+            vec_deleted,
+            is_stopped,
+            &self.distance,
+        )
+        .build()
+    }
+
     pub fn save_to(&self, path: &Path) -> OperationResult<()> {
         let data_path = path.join(QUANTIZED_DATA_PATH);
         let meta_path = path.join(QUANTIZED_META_PATH);
@@ -110,6 +172,19 @@ impl QuantizedVectors {
         max_threads: usize,
         stopped: &AtomicBool,
     ) -> OperationResult<Self> {
+        if distance == Distance::Chebyshev {
+            return Err(OperationError::ValidationError {
+                description: "unsupported quantization for Chebyshev".to_string(),
+            });
+        }
+        if distance == Distance::Hamming
+            && !matches!(quantization_config, QuantizationConfig::Binary(_))
+        {
+            return Err(OperationError::ValidationError {
+                description: "Hamming distance only supports binary quantization".to_string(),
+            });
+        }
+
         let vector_parameters = Self::construct_vector_parameters(distance, dim, count);
 
         let quantized_storage = match quantization_config {
@@ -375,8 +450,13 @@ impl QuantizedVectors {
                 Distance::Cosine => quantization::DistanceType::Dot,
                 Distance::Euclid => quantization::DistanceType::L2,
                 Distance::Dot => quantization::DistanceType::Dot,
+                Distance::L1 => quantization::DistanceType::L1,
+                // Binary dot product is `dim - 2 * hamming`, see `QuantizedQueryScorer`
+                Distance::Hamming => quantization::DistanceType::Dot,
+                // Not quantized, rejected in `create`
+                Distance::Chebyshev => quantization::DistanceType::L1,
             },
-            invert: distance == Distance::Euclid,
+            invert: distance == Distance::Euclid || distance == Distance::L1,
         }
     }
 
@@ -0,0 +1,184 @@
+use common::types::ScoreType;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::common::operation_error::OperationError;
+use crate::data_types::vectors::{QueryVector, VectorOrSparse, VectorType};
+
+/// Candidates should be closer to `positive` than to `negative`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ContextPair<T> {
+    pub positive: T,
+    pub negative: T,
+}
+
+impl<T> ContextPair<T> {
+    pub fn new(positive: T, negative: T) -> Self {
+        Self { positive, negative }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        [&self.positive, &self.negative].into_iter()
+    }
+
+    pub fn transform<F, U>(self, mut f: F) -> ContextPair<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        ContextPair::new(f(self.positive), f(self.negative))
+    }
+
+    /// Triplet loss of the pair: zero if the candidate is closer to the positive,
+    /// otherwise the (negative) difference of similarities.
+    pub fn loss_by(&self, similarity: impl Fn(&T) -> ScoreType) -> ScoreType {
+        // a candidate equally similar to both sides is still penalized
+        const MARGIN: ScoreType = ScoreType::EPSILON;
+
+        let difference = similarity(&self.positive) - similarity(&self.negative);
+        (difference - MARGIN).min(0.0)
+    }
+}
+
+/// Discovery query: steers the search with context pairs, without a single target
+///
+/// Best candidates are on the positive side of all pairs, and score 0.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContextQuery<T> {
+    pub pairs: Vec<ContextPair<T>>,
+}
+
+impl<T> ContextQuery<T> {
+    pub fn new(pairs: Vec<ContextPair<T>>) -> Self {
+        Self { pairs }
+    }
+
+    pub fn iter_all(&self) -> impl Iterator<Item = &T> {
+        self.pairs.iter().flat_map(|pair| pair.iter())
+    }
+
+    pub fn transform<F, U>(self, mut f: F) -> ContextQuery<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        ContextQuery::new(
+            self.pairs
+                .into_iter()
+                .map(|pair| pair.transform(&mut f))
+                .collect(),
+        )
+    }
+
+    /// Compares all vectors of the query against a single vector via a similarity function,
+    /// then sums the losses of all pairs into a single score.
+    pub fn score_by(&self, similarity: impl Fn(&T) -> ScoreType) -> ScoreType {
+        self.pairs
+            .iter()
+            .map(|pair| pair.loss_by(&similarity))
+            .sum()
+    }
+}
+
+impl TryFrom<ContextQuery<VectorOrSparse>> for ContextQuery<VectorType> {
+    type Error = OperationError;
+
+    fn try_from(query: ContextQuery<VectorOrSparse>) -> Result<Self, Self::Error> {
+        let pairs = query
+            .pairs
+            .into_iter()
+            .map(|pair| {
+                Ok(ContextPair::new(
+                    VectorType::try_from(pair.positive)?,
+                    VectorType::try_from(pair.negative)?,
+                ))
+            })
+            .collect::<Result<_, OperationError>>()?;
+        Ok(ContextQuery::new(pairs))
+    }
+}
+
+impl From<ContextQuery<VectorType>> for QueryVector {
+    fn from(query: ContextQuery<VectorType>) -> Self {
+        QueryVector::Context(query.transform(VectorOrSparse::Vector))
+    }
+}
+
+impl From<ContextQuery<VectorOrSparse>> for QueryVector {
+    fn from(query: ContextQuery<VectorOrSparse>) -> Self {
+        QueryVector::Context(query)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::ScoreType;
+    use rstest::rstest;
+    use sparse::common::sparse_vector::SparseVector;
+
+    use super::{ContextPair, ContextQuery};
+    use crate::data_types::vectors::{QueryVector, VectorOrSparse, VectorType};
+
+    fn dummy_similarity(x: &isize) -> ScoreType {
+        *x as ScoreType
+    }
+
+    #[rstest]
+    #[case::positive_side(10, 2, 0.0)]
+    #[case::negative_side(2, 10, -8.0)]
+    #[case::equal(5, 5, -ScoreType::EPSILON)]
+    #[case::under_zero(-2, -10, 0.0)]
+    fn pair_loss(#[case] positive: isize, #[case] negative: isize, #[case] expected: ScoreType) {
+        let pair = ContextPair::new(positive, negative);
+
+        let loss = pair.loss_by(dummy_similarity);
+
+        assert!((loss - expected).abs() <= ScoreType::EPSILON);
+        assert!(loss <= 0.0);
+    }
+
+    #[rstest]
+    #[case::all_positive(vec![(3, 1), (5, 4)], 0.0)]
+    #[case::one_negative(vec![(3, 1), (4, 9)], -5.0)]
+    #[case::all_negative(vec![(1, 3), (4, 9)], -7.0)]
+    #[case::no_input(vec![], 0.0)]
+    fn score_query(#[case] pairs: Vec<(isize, isize)>, #[case] expected: ScoreType) {
+        let pairs = pairs
+            .into_iter()
+            .map(|(positive, negative)| ContextPair::new(positive, negative))
+            .collect();
+        let query = ContextQuery::new(pairs);
+
+        let score = query.score_by(dummy_similarity);
+
+        assert!((score - expected).abs() <= 2.0 * ScoreType::EPSILON);
+    }
+
+    #[test]
+    fn iter_all_and_transform() {
+        let query = ContextQuery::new(vec![ContextPair::new(1, 2), ContextPair::new(3, 4)]);
+        assert_eq!(
+            query.iter_all().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        let query = query.transform(|x| x * 10);
+        assert_eq!(query.pairs[1], ContextPair::new(30, 40));
+    }
+
+    #[test]
+    fn dense_query_conversion() {
+        let query = ContextQuery::new(vec![ContextPair::new(vec![1.0, 0.0], vec![0.0, 1.0])]);
+        let QueryVector::Context(query) = QueryVector::from(query) else {
+            panic!("expected context query");
+        };
+
+        let dense = ContextQuery::<VectorType>::try_from(query).unwrap();
+        assert_eq!(dense.pairs[0].positive, vec![1.0, 0.0]);
+
+        let sparse = SparseVector::new(vec![0], vec![1.0]);
+        let query = ContextQuery::new(vec![ContextPair::new(
+            VectorOrSparse::Vector(vec![1.0, 0.0]),
+            VectorOrSparse::Sparse(sparse),
+        )]);
+        assert!(ContextQuery::<VectorType>::try_from(query).is_err());
+    }
+}
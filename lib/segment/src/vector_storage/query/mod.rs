@@ -1 +1,2 @@
+pub mod context_query;
 pub mod reco_query;
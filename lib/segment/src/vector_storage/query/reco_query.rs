@@ -1,11 +1,62 @@
+use std::collections::BTreeMap;
+
 use common::types::ScoreType;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sparse::common::sparse_vector::SparseVector;
+use sparse::common::types::DimWeight;
+
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::data_types::vectors::{QueryVector, VectorElementType, VectorOrSparse, VectorType};
 
-use crate::data_types::vectors::{QueryVector, VectorType};
+/// How similarities to positive and negative examples are folded into a single score
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoStrategy {
+    /// Average similarity to positives minus average similarity to negatives.
+    /// For dot product this is the same as scoring against `avg(positives) - avg(negatives)`.
+    AverageVector,
+    /// Best positive similarity, penalized if one of the negatives is more similar
+    #[default]
+    BestScore,
+}
+
+/// Which similarity to negatives is compared against the best positive one
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeAggregation {
+    /// Most similar negative, penalizes candidates close to any of the negatives
+    #[default]
+    Max,
+    /// Least similar negative, penalizes only candidates close to all of the negatives
+    Min,
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RecoQuery<T> {
     pub positives: Vec<T>,
     pub negatives: Vec<T>,
+    #[serde(default)]
+    pub strategy: RecoStrategy,
+    /// Only used by [`RecoStrategy::BestScore`]
+    #[serde(default)]
+    pub negative_aggregation: NegativeAggregation,
+    /// Weights of positives by position, missing weights are 1.0
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub positive_weights: Vec<ScoreType>,
+    /// Weights of negatives by position, missing weights are 1.0
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negative_weights: Vec<ScoreType>,
+    /// Exponent of the penalty applied when a negative beats the best positive,
+    /// only used by [`RecoStrategy::BestScore`]
+    #[serde(default = "default_negative_penalty")]
+    pub negative_penalty: ScoreType,
+}
+
+pub const DEFAULT_NEGATIVE_PENALTY: ScoreType = 2.0;
+
+fn default_negative_penalty() -> ScoreType {
+    DEFAULT_NEGATIVE_PENALTY
 }
 
 impl<T> RecoQuery<T> {
@@ -13,9 +64,41 @@ impl<T> RecoQuery<T> {
         Self {
             positives,
             negatives,
+            strategy: RecoStrategy::default(),
+            negative_aggregation: NegativeAggregation::default(),
+            positive_weights: Vec::new(),
+            negative_weights: Vec::new(),
+            negative_penalty: DEFAULT_NEGATIVE_PENALTY,
         }
     }
 
+    pub fn with_strategy(mut self, strategy: RecoStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_negative_aggregation(mut self, negative_aggregation: NegativeAggregation) -> Self {
+        self.negative_aggregation = negative_aggregation;
+        self
+    }
+
+    /// Similarity to each example is multiplied by its weight before being merged
+    pub fn with_weights(
+        mut self,
+        positive_weights: Vec<ScoreType>,
+        negative_weights: Vec<ScoreType>,
+    ) -> Self {
+        self.positive_weights = positive_weights;
+        self.negative_weights = negative_weights;
+        self
+    }
+
+    /// Score of a candidate closer to a negative is `-|negative|^negative_penalty`
+    pub fn with_negative_penalty(mut self, negative_penalty: ScoreType) -> Self {
+        self.negative_penalty = negative_penalty;
+        self
+    }
+
     pub fn iter_all(&self) -> impl Iterator<Item = &T> {
         self.positives.iter().chain(self.negatives.iter())
     }
@@ -24,47 +107,215 @@ impl<T> RecoQuery<T> {
     where
         F: FnMut(T) -> U,
     {
+        let RecoQuery {
+            positives,
+            negatives,
+            strategy,
+            negative_aggregation,
+            positive_weights,
+            negative_weights,
+            negative_penalty,
+        } = self;
         RecoQuery::new(
-            self.positives.into_iter().map(&mut f).collect(),
-            self.negatives.into_iter().map(&mut f).collect(),
+            positives.into_iter().map(&mut f).collect(),
+            negatives.into_iter().map(&mut f).collect(),
         )
+        .with_strategy(strategy)
+        .with_negative_aggregation(negative_aggregation)
+        .with_weights(positive_weights, negative_weights)
+        .with_negative_penalty(negative_penalty)
     }
 
     /// Compares all vectors of the query against a single vector via a similarity function,
     /// then folds the similarites into a single score.
     pub fn score_by(&self, similarity: impl Fn(&T) -> ScoreType) -> ScoreType {
-        // get similarities to all positives
-        let positive_similarities = self.positives.iter().map(&similarity);
+        // get weighted similarities to all positives
+        let positive_similarities = self
+            .positives
+            .iter()
+            .enumerate()
+            .map(|(i, example)| similarity(example) * weight(&self.positive_weights, i));
 
         // and all negatives
-        let negative_similarities = self.negatives.iter().map(&similarity);
+        let negative_similarities = self
+            .negatives
+            .iter()
+            .enumerate()
+            .map(|(i, example)| similarity(example) * weight(&self.negative_weights, i));
 
-        merge_similarities(positive_similarities, negative_similarities)
+        match self.strategy {
+            RecoStrategy::AverageVector => {
+                average_similarities(positive_similarities, negative_similarities)
+            }
+            RecoStrategy::BestScore => merge_similarities(
+                positive_similarities,
+                negative_similarities,
+                self.negative_aggregation,
+                self.negative_penalty,
+            ),
+        }
     }
 }
 
-fn merge_similarities(
+fn weight(weights: &[ScoreType], i: usize) -> ScoreType {
+    weights.get(i).copied().unwrap_or(1.0)
+}
+
+fn average_similarities(
     positives: impl Iterator<Item = ScoreType>,
     negatives: impl Iterator<Item = ScoreType>,
 ) -> ScoreType {
-    // get max similarity to positives and max to negatives
+    // without positives there is nothing to be similar to
+    let Some(avg_positive) = mean(positives) else {
+        return ScoreType::NEG_INFINITY;
+    };
+    let avg_negative = mean(negatives).unwrap_or(0.0);
+
+    avg_positive - avg_negative
+}
+
+fn mean(similarities: impl Iterator<Item = ScoreType>) -> Option<ScoreType> {
+    let (sum, count) = similarities.fold((0.0, 0usize), |(sum, count), similarity| {
+        (sum + similarity, count + 1)
+    });
+    if count > 0 {
+        Some(sum / count as ScoreType)
+    } else {
+        None
+    }
+}
+
+fn merge_similarities(
+    positives: impl Iterator<Item = ScoreType>,
+    mut negatives: impl Iterator<Item = ScoreType>,
+    negative_aggregation: NegativeAggregation,
+    negative_penalty: ScoreType,
+) -> ScoreType {
+    // Raw similarities are larger-is-better for every distance, so no `Distance::better` here.
+    // get max similarity to positives and max (or min) to negatives
     let max_positive = positives
         .max_by(|a, b| a.total_cmp(b))
         .unwrap_or(ScoreType::NEG_INFINITY);
 
-    let max_negative = negatives
-        .max_by(|a, b| a.total_cmp(b))
-        .unwrap_or(ScoreType::NEG_INFINITY);
+    let negative = match negative_aggregation {
+        NegativeAggregation::Max => negatives.max_by(|a, b| a.total_cmp(b)),
+        NegativeAggregation::Min => negatives.min_by(|a, b| a.total_cmp(b)),
+    }
+    .unwrap_or(ScoreType::NEG_INFINITY);
 
-    if max_positive > max_negative {
+    if max_positive > negative {
         max_positive
     } else {
-        -(max_negative * max_negative)
+        -(negative.abs().powf(negative_penalty))
+    }
+}
+
+impl RecoQuery<VectorType> {
+    /// Merge all examples into a single query vector: `avg(positives) - avg(negatives)`
+    ///
+    /// Searching with this vector by dot product gives the same ranking as scoring with
+    /// [`RecoStrategy::AverageVector`], but compares each candidate against a single vector.
+    /// All examples must have `dim` elements. Without positives, a zero vector is returned.
+    pub fn into_average_vector(self, dim: usize) -> OperationResult<VectorType> {
+        if let Some(vector) = self.iter_all().find(|vector| vector.len() != dim) {
+            return Err(OperationError::WrongVector {
+                expected_dim: dim,
+                received_dim: vector.len(),
+            });
+        }
+
+        let mut result = vec![0.0; dim];
+        if self.positives.is_empty() {
+            return Ok(result);
+        }
+
+        add_scaled(&mut result, &self.positives, &self.positive_weights, 1.0);
+        add_scaled(&mut result, &self.negatives, &self.negative_weights, -1.0);
+        Ok(result)
+    }
+}
+
+/// Add `sign * avg(weight * vector)` of `vectors` to `result`
+fn add_scaled(
+    result: &mut [VectorElementType],
+    vectors: &[VectorType],
+    weights: &[ScoreType],
+    sign: VectorElementType,
+) {
+    let scale = sign / vectors.len() as VectorElementType;
+    for (i, vector) in vectors.iter().enumerate() {
+        let scale = scale * weight(weights, i);
+        for (acc, value) in result.iter_mut().zip(vector) {
+            *acc += value * scale;
+        }
+    }
+}
+
+impl RecoQuery<SparseVector> {
+    /// Sparse version of [`RecoQuery::into_average_vector`], weights are summed per dimension
+    ///
+    /// Result is sorted by index. Without positives, an empty vector is returned.
+    pub fn into_average_vector(self) -> SparseVector {
+        if self.positives.is_empty() {
+            return SparseVector::new(vec![], vec![]);
+        }
+
+        let mut weights = BTreeMap::new();
+        for (vectors, example_weights, sign) in [
+            (&self.positives, &self.positive_weights, 1.0),
+            (&self.negatives, &self.negative_weights, -1.0),
+        ] {
+            let scale = sign / vectors.len() as DimWeight;
+            for (i, vector) in vectors.iter().enumerate() {
+                let scale = scale * weight(example_weights, i);
+                for (&idx, &weight) in vector.indices.iter().zip(&vector.weights) {
+                    *weights.entry(idx).or_insert(0.0) += weight * scale;
+                }
+            }
+        }
+
+        let (indices, weights) = weights.into_iter().unzip();
+        SparseVector::new(indices, weights)
+    }
+}
+
+impl RecoQuery<VectorOrSparse> {
+    /// Build a recommendation query out of sparse examples
+    pub fn from_sparse(positives: Vec<SparseVector>, negatives: Vec<SparseVector>) -> Self {
+        RecoQuery::new(positives, negatives).transform(VectorOrSparse::Sparse)
+    }
+}
+
+impl TryFrom<RecoQuery<VectorOrSparse>> for RecoQuery<VectorType> {
+    type Error = OperationError;
+
+    fn try_from(query: RecoQuery<VectorOrSparse>) -> Result<Self, Self::Error> {
+        let positives = query
+            .positives
+            .into_iter()
+            .map(VectorType::try_from)
+            .collect::<Result<_, _>>()?;
+        let negatives = query
+            .negatives
+            .into_iter()
+            .map(VectorType::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(RecoQuery::new(positives, negatives)
+            .with_strategy(query.strategy)
+            .with_negative_aggregation(query.negative_aggregation)
+            .with_weights(query.positive_weights, query.negative_weights)
+            .with_negative_penalty(query.negative_penalty))
     }
 }
 
 impl From<RecoQuery<VectorType>> for QueryVector {
     fn from(query: RecoQuery<VectorType>) -> Self {
+        QueryVector::Recommend(query.transform(VectorOrSparse::Vector))
+    }
+}
+
+impl From<RecoQuery<VectorOrSparse>> for QueryVector {
+    fn from(query: RecoQuery<VectorOrSparse>) -> Self {
         QueryVector::Recommend(query)
     }
 }
@@ -73,8 +324,11 @@ impl From<RecoQuery<VectorType>> for QueryVector {
 mod test {
     use common::types::ScoreType;
     use rstest::rstest;
+    use sparse::common::sparse_vector::SparseVector;
 
-    use super::RecoQuery;
+    use super::{NegativeAggregation, RecoQuery, RecoStrategy, DEFAULT_NEGATIVE_PENALTY};
+    use crate::common::operation_error::OperationError;
+    use crate::data_types::vectors::{QueryVector, VectorOrSparse, VectorType};
 
     #[rstest]
     #[case::higher_positive(vec![42], vec![4], 42.0)]
@@ -99,4 +353,237 @@ mod test {
 
         assert_eq!(score, expected);
     }
+
+    #[rstest]
+    #[case::max_negative(NegativeAggregation::Max, vec![5], vec![8, 2], -(8.0 * 8.0))]
+    #[case::min_negative(NegativeAggregation::Min, vec![5], vec![8, 2], 5.0)]
+    #[case::min_negative_higher(NegativeAggregation::Min, vec![5], vec![8, 6], -(6.0 * 6.0))]
+    #[case::min_no_negatives(NegativeAggregation::Min, vec![5], vec![], 5.0)]
+    fn score_query_negative_aggregation(
+        #[case] negative_aggregation: NegativeAggregation,
+        #[case] positives: Vec<isize>,
+        #[case] negatives: Vec<isize>,
+        #[case] expected: ScoreType,
+    ) {
+        let query =
+            RecoQuery::new(positives, negatives).with_negative_aggregation(negative_aggregation);
+
+        let score = query.score_by(|x: &isize| *x as ScoreType);
+
+        assert_eq!(score, expected);
+    }
+
+    #[rstest]
+    #[case::higher_negative(vec![4], vec![42], -42.0)]
+    #[case::both_under_zero_but_negative_is_higher(vec![-84], vec![-42], -42.0)]
+    #[case::multiple_with_negative_best(vec![1, 2, 3], vec![4, 5, 6], -6.0)]
+    #[case::multiple_with_positive_best(vec![10, 2, 3], vec![4, 5, 6], 10.0)]
+    #[case::no_input(vec![], vec![], ScoreType::NEG_INFINITY)]
+    fn score_query_linear_penalty(
+        #[case] positives: Vec<isize>,
+        #[case] negatives: Vec<isize>,
+        #[case] expected: ScoreType,
+    ) {
+        let query = RecoQuery::new(positives, negatives).with_negative_penalty(1.0);
+
+        let score = query.score_by(|x: &isize| *x as ScoreType);
+
+        assert_eq!(score, expected);
+    }
+
+    #[rstest]
+    #[case::only_positives(vec![1, 2, 3], vec![], 2.0)]
+    #[case::positive_higher(vec![4, 6], vec![1, 3], 3.0)]
+    #[case::negative_higher(vec![1, 3], vec![4, 6], -3.0)]
+    #[case::equal(vec![2, 4], vec![3], 0.0)]
+    #[case::under_zero(vec![-2], vec![-6, -4], 3.0)]
+    #[case::no_positives(vec![], vec![4], ScoreType::NEG_INFINITY)]
+    #[case::no_input(vec![], vec![], ScoreType::NEG_INFINITY)]
+    fn score_query_average_vector(
+        #[case] positives: Vec<isize>,
+        #[case] negatives: Vec<isize>,
+        #[case] expected: ScoreType,
+    ) {
+        let query = RecoQuery::new(positives, negatives).with_strategy(RecoStrategy::AverageVector);
+
+        let score = query.score_by(|x: &isize| *x as ScoreType);
+
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn average_vector_ignores_negative_aggregation() {
+        let query = RecoQuery::new(vec![5], vec![8, 2]).with_strategy(RecoStrategy::AverageVector);
+        let min_query = query
+            .clone()
+            .with_negative_aggregation(NegativeAggregation::Min);
+
+        let similarity = |x: &isize| *x as ScoreType;
+        assert_eq!(query.score_by(similarity), 0.0);
+        assert_eq!(min_query.score_by(similarity), 0.0);
+    }
+
+    #[rstest]
+    // `a` is close to one of the negatives, `b` is moderately close to all of them
+    #[case::max_negative(NegativeAggregation::Max, ["b", "a"])]
+    #[case::min_negative(NegativeAggregation::Min, ["a", "b"])]
+    fn negative_aggregation_ranking(
+        #[case] negative_aggregation: NegativeAggregation,
+        #[case] expected_order: [&str; 2],
+    ) {
+        // similarities of a candidate to the single positive and to both negatives
+        let similarities = [("a", 5, [8, 2]), ("b", 4, [3, 3])];
+
+        let mut ranked: Vec<_> = similarities
+            .iter()
+            .map(|&(name, positive, negatives)| {
+                let query = RecoQuery::new(vec![positive], negatives.to_vec())
+                    .with_negative_aggregation(negative_aggregation);
+                (name, query.score_by(|x: &isize| *x as ScoreType))
+            })
+            .collect();
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        let order: Vec<_> = ranked.into_iter().map(|(name, _)| name).collect();
+        assert_eq!(order, expected_order);
+    }
+
+    #[test]
+    fn average_vector_matches_average_strategy() {
+        let positives = vec![vec![1.0, 2.0, 0.0], vec![3.0, 0.0, 1.0]];
+        let negatives = vec![vec![0.0, 4.0, 2.0]];
+        let query = RecoQuery::new(positives, negatives);
+
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+        let candidate = vec![0.5, -1.0, 2.0];
+        let strategy_score = query
+            .clone()
+            .with_strategy(RecoStrategy::AverageVector)
+            .score_by(|example| dot(example, &candidate));
+
+        let average = query.clone().into_average_vector(3).unwrap();
+        assert_eq!(average, vec![2.0, -3.0, -1.5]);
+        assert!((dot(&average, &candidate) - strategy_score).abs() < 1e-6);
+
+        let no_positives = RecoQuery::new(vec![], vec![vec![1.0, 2.0]]);
+        assert_eq!(no_positives.into_average_vector(2).unwrap(), vec![0.0, 0.0]);
+        let no_examples = RecoQuery::<VectorType>::new(vec![], vec![]);
+        assert_eq!(no_examples.into_average_vector(2).unwrap(), vec![0.0, 0.0]);
+
+        // Examples of another dimension are rejected, not truncated
+        assert!(matches!(
+            query.into_average_vector(2),
+            Err(OperationError::WrongVector {
+                expected_dim: 2,
+                received_dim: 3,
+            })
+        ));
+        let mismatched = RecoQuery::new(vec![vec![1.0, 2.0]], vec![vec![1.0, 2.0, 3.0]]);
+        assert!(matches!(
+            mismatched.into_average_vector(2),
+            Err(OperationError::WrongVector {
+                expected_dim: 2,
+                received_dim: 3,
+            })
+        ));
+    }
+
+    #[test]
+    fn sparse_average_vector() {
+        let query = RecoQuery::new(
+            vec![
+                SparseVector::new(vec![1, 5], vec![4.0, 2.0]),
+                SparseVector::new(vec![1, 3], vec![2.0, 1.0]),
+            ],
+            vec![SparseVector::new(vec![3, 7], vec![1.0, 1.0])],
+        );
+
+        let average = query.clone().into_average_vector();
+        assert_eq!(average.indices, vec![1, 3, 5, 7]);
+        assert_eq!(average.weights, vec![3.0, -0.5, 1.0, -1.0]);
+
+        let candidate = SparseVector::new(vec![1, 3, 7], vec![1.0, 2.0, 0.5]);
+        let strategy_score = query
+            .with_strategy(RecoStrategy::AverageVector)
+            .score_by(|example| example.dot(&candidate));
+        assert_eq!(average.dot(&candidate), strategy_score);
+
+        let no_positives = RecoQuery::new(vec![], vec![SparseVector::new(vec![1], vec![1.0])]);
+        assert_eq!(
+            no_positives.into_average_vector(),
+            SparseVector::new(vec![], vec![])
+        );
+    }
+
+    #[test]
+    fn sparse_reco_query_into_query_vector() {
+        let positive = SparseVector::new(vec![1, 3], vec![0.5, 1.0]);
+        let negative = SparseVector::new(vec![2], vec![0.7]);
+
+        let query: QueryVector =
+            RecoQuery::from_sparse(vec![positive.clone()], vec![negative.clone()]).into();
+
+        let QueryVector::Recommend(reco_query) = query else {
+            panic!("expected a recommendation query");
+        };
+        assert_eq!(reco_query.positives, vec![VectorOrSparse::Sparse(positive)]);
+        assert_eq!(reco_query.negatives, vec![VectorOrSparse::Sparse(negative)]);
+        assert!(RecoQuery::<VectorType>::try_from(reco_query).is_err());
+    }
+
+    #[rstest]
+    #[case::best_score(RecoStrategy::BestScore)]
+    #[case::average_vector(RecoStrategy::AverageVector)]
+    fn weighted_positive_wins(#[case] strategy: RecoStrategy) {
+        // Candidate "a" is very similar to a weak positive, "b" is moderately similar to a
+        // strong positive
+        let similarity = |candidate: &str, example: &&str| match (candidate, *example) {
+            ("a", "weak") => 0.9,
+            ("a", "strong") => 0.1,
+            ("b", "weak") => 0.1,
+            ("b", "strong") => 0.6,
+            _ => unreachable!(),
+        };
+
+        let query = RecoQuery::new(vec!["weak", "strong"], vec![]).with_strategy(strategy);
+        assert!(query.score_by(|x| similarity("a", x)) > query.score_by(|x| similarity("b", x)));
+
+        let query = query.with_weights(vec![0.5, 2.0], vec![]);
+        assert!(query.score_by(|x| similarity("a", x)) < query.score_by(|x| similarity("b", x)));
+
+        // Weights survive transformation
+        let query = query.transform(|x| x.to_string());
+        assert_eq!(query.positive_weights, vec![0.5, 2.0]);
+    }
+
+    #[test]
+    fn weighted_negative_penalizes() {
+        let query = RecoQuery::new(vec![5], vec![4]).with_weights(vec![], vec![2.0]);
+        assert_eq!(query.score_by(|x: &isize| *x as ScoreType), -(8.0 * 8.0));
+    }
+
+    #[test]
+    fn reco_query_serde_round_trip() {
+        let query = RecoQuery::new(
+            vec![
+                VectorOrSparse::Vector(vec![1.0, 2.0, 3.0]),
+                VectorOrSparse::Sparse(SparseVector::new(vec![0, 5], vec![0.5, -1.0])),
+            ],
+            vec![VectorOrSparse::Vector(vec![-1.0, 0.0, 0.5])],
+        );
+
+        let json = serde_json::to_string(&query).unwrap();
+        let restored: RecoQuery<VectorOrSparse> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.positives, query.positives);
+        assert_eq!(restored.negatives, query.negatives);
+        assert_eq!(restored.strategy, RecoStrategy::BestScore);
+        assert_eq!(restored.negative_penalty, DEFAULT_NEGATIVE_PENALTY);
+
+        let query = query.with_strategy(RecoStrategy::AverageVector);
+        let json = serde_json::to_string(&query).unwrap();
+        assert!(json.contains(r#""strategy":"average_vector""#));
+        let restored: RecoQuery<VectorOrSparse> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.strategy, RecoStrategy::AverageVector);
+    }
 }
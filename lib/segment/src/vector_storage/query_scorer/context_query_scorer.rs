@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+use common::types::{PointOffsetType, ScoreType};
+
+use crate::data_types::vectors::{VectorElementType, VectorType};
+use crate::spaces::metric::{Metric, PreprocessedVector};
+use crate::vector_storage::query::context_query::ContextQuery;
+use crate::vector_storage::query_scorer::QueryScorer;
+use crate::vector_storage::VectorStorage;
+
+pub struct ContextQueryScorer<'a, TMetric: Metric, TVectorStorage: VectorStorage> {
+    vector_storage: &'a TVectorStorage,
+    query: ContextQuery<PreprocessedVector>,
+    metric: PhantomData<TMetric>,
+}
+
+impl<'a, TMetric: Metric, TVectorStorage: VectorStorage>
+    ContextQueryScorer<'a, TMetric, TVectorStorage>
+{
+    pub fn new(query: ContextQuery<VectorType>, vector_storage: &'a TVectorStorage) -> Self {
+        let query = query.transform(|vector| TMetric::preprocess(vector));
+
+        Self {
+            query,
+            vector_storage,
+            metric: PhantomData,
+        }
+    }
+}
+
+impl<'a, TMetric: Metric, TVectorStorage: VectorStorage> QueryScorer
+    for ContextQueryScorer<'a, TMetric, TVectorStorage>
+{
+    #[inline]
+    fn score_stored(&self, idx: PointOffsetType) -> ScoreType {
+        let stored = self.vector_storage.get_vector(idx);
+        self.score(&stored)
+    }
+
+    #[inline]
+    fn score(&self, against: &[VectorElementType]) -> ScoreType {
+        self.query
+            .score_by(|example| TMetric::similarity(example, against))
+    }
+
+    /// Query examples are not involved in comparing two stored points, so this falls back to
+    /// plain metric similarity, same as for nearest search
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let v1 = self.vector_storage.get_vector(point_a);
+        let v2 = self.vector_storage.get_vector(point_b);
+        TMetric::similarity(&v1, &v2)
+    }
+}
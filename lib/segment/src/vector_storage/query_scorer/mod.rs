@@ -2,6 +2,7 @@ use common::types::{PointOffsetType, ScoreType};
 
 use crate::data_types::vectors::VectorElementType;
 
+pub mod context_query_scorer;
 pub mod metric_query_scorer;
 pub mod reco_query_scorer;
 
@@ -3,14 +3,14 @@ use std::marker::PhantomData;
 use common::types::{PointOffsetType, ScoreType};
 
 use crate::data_types::vectors::{VectorElementType, VectorType};
-use crate::spaces::metric::Metric;
+use crate::spaces::metric::{Metric, PreprocessedVector};
 use crate::vector_storage::query::reco_query::RecoQuery;
 use crate::vector_storage::query_scorer::QueryScorer;
 use crate::vector_storage::VectorStorage;
 
 pub struct RecoQueryScorer<'a, TMetric: Metric, TVectorStorage: VectorStorage> {
     vector_storage: &'a TVectorStorage,
-    query: RecoQuery<VectorType>,
+    query: RecoQuery<PreprocessedVector>,
     metric: PhantomData<TMetric>,
 }
 
@@ -34,7 +34,7 @@ impl<'a, TMetric: Metric, TVectorStorage: VectorStorage> QueryScorer
     #[inline]
     fn score_stored(&self, idx: PointOffsetType) -> ScoreType {
         let stored = self.vector_storage.get_vector(idx);
-        self.score(stored)
+        self.score(&stored)
     }
 
     #[inline]
@@ -43,7 +43,11 @@ impl<'a, TMetric: Metric, TVectorStorage: VectorStorage> QueryScorer
             .score_by(|example| TMetric::similarity(example, against))
     }
 
-    fn score_internal(&self, _point_a: PointOffsetType, _point_b: PointOffsetType) -> ScoreType {
-        unimplemented!("Recommendation scorer compares against multiple vectors, not just one")
+    /// Query examples are not involved in comparing two stored points, so this falls back to
+    /// plain metric similarity, same as for nearest search
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let v1 = self.vector_storage.get_vector(point_a);
+        let v2 = self.vector_storage.get_vector(point_b);
+        TMetric::similarity(&v1, &v2)
     }
 }
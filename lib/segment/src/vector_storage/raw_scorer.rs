@@ -1,13 +1,19 @@
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 use bitvec::prelude::BitSlice;
 use common::types::{PointOffsetType, ScoreType, ScoredPointOffset};
 
+use super::query_scorer::context_query_scorer::ContextQueryScorer;
 use super::query_scorer::reco_query_scorer::RecoQueryScorer;
 use super::{VectorStorage, VectorStorageEnum};
+use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
 use crate::data_types::vectors::QueryVector;
+use crate::payload_storage::FilterContext;
 use crate::spaces::metric::Metric;
-use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::spaces::simple::{
+    ChebyshevMetric, CosineMetric, DotProductMetric, EuclidMetric, HammingMetric, L1Metric,
+};
 use crate::spaces::tools::peek_top_largest_iterable;
 use crate::types::Distance;
 use crate::vector_storage::query_scorer::metric_query_scorer::MetricQueryScorer;
@@ -44,7 +50,8 @@ pub trait RawScorer {
         points: &mut dyn Iterator<Item = PointOffsetType>,
     ) -> Vec<ScoredPointOffset>;
 
-    /// Return true if vector satisfies current search context for given point (exists and not deleted)
+    /// Return true if vector satisfies current search context for given point
+    /// (exists, not deleted and passes the filter context, if any)
     fn check_vector(&self, point: PointOffsetType) -> bool;
 
     /// Score stored vector with vector under the given index
@@ -78,6 +85,8 @@ pub struct RawScorerImpl<'a, TQueryScorer: QueryScorer> {
     /// This flag indicates that the search process is stopped externally,
     /// the search result is no longer needed and the search process should be stopped as soon as possible.
     pub is_stopped: &'a AtomicBool,
+    /// Points rejected by this context are skipped in the same way as deleted ones
+    pub filter_context: Option<&'a dyn FilterContext>,
 }
 
 pub fn new_stoppable_raw_scorer<'a>(
@@ -85,18 +94,21 @@ pub fn new_stoppable_raw_scorer<'a>(
     vector_storage: &'a VectorStorageEnum,
     point_deleted: &'a BitSlice,
     is_stopped: &'a AtomicBool,
-) -> Box<dyn RawScorer + 'a> {
+) -> OperationResult<Box<dyn RawScorer + 'a>> {
     match vector_storage {
-        VectorStorageEnum::Simple(vs) => raw_scorer_impl(query, vs, point_deleted, is_stopped),
+        VectorStorageEnum::Simple(vs) => {
+            raw_scorer_impl(query, vs, point_deleted, is_stopped, None)
+        }
 
         VectorStorageEnum::Memmap(vs) => {
-            if vs.has_async_reader() {
+            // Async reader bypasses the vector cache, only use it for uncached storages
+            if vs.has_async_reader() && !vs.has_vector_cache() {
                 #[cfg(target_os = "linux")]
                 {
                     let scorer_result =
                         super::async_raw_scorer::new(query.clone(), vs, point_deleted, is_stopped);
                     match scorer_result {
-                        Ok(raw_scorer) => return raw_scorer,
+                        Ok(raw_scorer) => return Ok(raw_scorer),
                         Err(err) => log::error!("failed to initialize async raw scorer: {err}"),
                     };
                 }
@@ -105,49 +117,197 @@ pub fn new_stoppable_raw_scorer<'a>(
                 log::warn!("async raw scorer is only supported on Linux");
             }
 
-            raw_scorer_impl(query, vs.as_ref(), point_deleted, is_stopped)
+            raw_scorer_impl(query, vs.as_ref(), point_deleted, is_stopped, None)
         }
 
         VectorStorageEnum::AppendableMemmap(vs) => {
-            raw_scorer_impl(query, vs.as_ref(), point_deleted, is_stopped)
+            raw_scorer_impl(query, vs.as_ref(), point_deleted, is_stopped, None)
         }
     }
 }
 
 pub static DEFAULT_STOPPED: AtomicBool = AtomicBool::new(false);
 
+/// Exact search: score `points` which are not deleted and pass `filter`, collecting top `top`
+///
+/// Scan stops early once `deadline` is passed, in which case the top of already scored points
+/// is returned. Cancellation with `is_stopped` is reported as an error.
+pub fn score_and_collect_with_deadline(
+    raw_scorer: &dyn RawScorer,
+    points: &mut dyn Iterator<Item = PointOffsetType>,
+    filter: &dyn FilterContext,
+    top: usize,
+    deadline: Option<Instant>,
+    is_stopped: &AtomicBool,
+) -> OperationResult<Vec<ScoredPointOffset>> {
+    let scores = points
+        .take_while(|_| {
+            !is_stopped.load(Ordering::Relaxed)
+                && !deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        })
+        .filter(|&point_id| raw_scorer.check_vector(point_id) && filter.check(point_id))
+        .map(|point_id| ScoredPointOffset {
+            idx: point_id,
+            score: raw_scorer.score_point(point_id),
+        });
+    let top_scores = peek_top_largest_iterable(scores, top);
+    check_process_stopped(is_stopped)?;
+    Ok(top_scores)
+}
+
+/// Two-stage search: preselect `top * oversampling` candidates with a fast `quantized_scorer`,
+/// then rescore them with an `exact_scorer` and return the final `top`
+///
+/// Both scorers are expected to be built for the same query.
+pub fn rescore(
+    quantized_scorer: &dyn RawScorer,
+    exact_scorer: &dyn RawScorer,
+    points: &mut dyn Iterator<Item = PointOffsetType>,
+    top: usize,
+    oversampling: f64,
+) -> Vec<ScoredPointOffset> {
+    let oversampled_top = ((top as f64 * oversampling.max(1.0)) as usize).max(top);
+    let candidates = quantized_scorer.peek_top_iter(points, oversampled_top);
+    let mut rescored =
+        exact_scorer.score_points_unfiltered(&mut candidates.iter().map(|scored| scored.idx));
+    rescored.sort_unstable();
+    rescored.reverse();
+    rescored.truncate(top);
+    rescored
+}
+
 pub fn new_raw_scorer<'a>(
     vector: QueryVector,
     vector_storage: &'a VectorStorageEnum,
     point_deleted: &'a BitSlice,
-) -> Box<dyn RawScorer + 'a> {
+) -> OperationResult<Box<dyn RawScorer + 'a>> {
     new_stoppable_raw_scorer(vector, vector_storage, point_deleted, &DEFAULT_STOPPED)
 }
 
+/// Create scorer over `vector_storage`, optionally skipping points rejected by `filter_context`
+///
+/// Filtered points are excluded from [`RawScorer::score_points`] and `peek_top_*` results,
+/// same as deleted ones.
 pub fn raw_scorer_impl<'a, TVectorStorage: VectorStorage>(
     query: QueryVector,
     vector_storage: &'a TVectorStorage,
     point_deleted: &'a BitSlice,
     is_stopped: &'a AtomicBool,
-) -> Box<dyn RawScorer + 'a> {
-    match vector_storage.distance() {
+    filter_context: Option<&'a dyn FilterContext>,
+) -> OperationResult<Box<dyn RawScorer + 'a>> {
+    raw_scorer_with_distance_impl(
+        query,
+        vector_storage,
+        vector_storage.distance(),
+        point_deleted,
+        is_stopped,
+        filter_context,
+    )
+}
+
+/// Create scorer which scores stored vectors under `distance` instead of the storage own metric
+///
+/// Stored vectors are reused as is, so only metrics which interpret them the same way are
+/// allowed. Vectors stored for Cosine are already normalized and can be scored with Dot, but
+/// not the other way around: Dot storage keeps raw vectors, which Cosine would score as dot.
+pub fn new_raw_scorer_with_distance<'a>(
+    query: QueryVector,
+    vector_storage: &'a VectorStorageEnum,
+    distance: Distance,
+    point_deleted: &'a BitSlice,
+) -> OperationResult<Box<dyn RawScorer + 'a>> {
+    let storage_distance = vector_storage.distance();
+    let is_compatible = storage_distance == distance
+        || matches!(
+            (storage_distance, distance),
+            (Distance::Cosine, Distance::Dot)
+        );
+    if !is_compatible {
+        return Err(OperationError::ValidationError {
+            description: format!(
+                "Can't score vectors stored for {storage_distance:?} distance with {distance:?}"
+            ),
+        });
+    }
+
+    match vector_storage {
+        VectorStorageEnum::Simple(vs) => raw_scorer_with_distance_impl(
+            query,
+            vs,
+            distance,
+            point_deleted,
+            &DEFAULT_STOPPED,
+            None,
+        ),
+        VectorStorageEnum::Memmap(vs) => raw_scorer_with_distance_impl(
+            query,
+            vs.as_ref(),
+            distance,
+            point_deleted,
+            &DEFAULT_STOPPED,
+            None,
+        ),
+        VectorStorageEnum::AppendableMemmap(vs) => raw_scorer_with_distance_impl(
+            query,
+            vs.as_ref(),
+            distance,
+            point_deleted,
+            &DEFAULT_STOPPED,
+            None,
+        ),
+    }
+}
+
+fn raw_scorer_with_distance_impl<'a, TVectorStorage: VectorStorage>(
+    query: QueryVector,
+    vector_storage: &'a TVectorStorage,
+    distance: Distance,
+    point_deleted: &'a BitSlice,
+    is_stopped: &'a AtomicBool,
+    filter_context: Option<&'a dyn FilterContext>,
+) -> OperationResult<Box<dyn RawScorer + 'a>> {
+    match distance {
         Distance::Cosine => new_scorer_with_metric::<CosineMetric, _>(
             query,
             vector_storage,
             point_deleted,
             is_stopped,
+            filter_context,
         ),
         Distance::Euclid => new_scorer_with_metric::<EuclidMetric, _>(
             query,
             vector_storage,
             point_deleted,
             is_stopped,
+            filter_context,
         ),
         Distance::Dot => new_scorer_with_metric::<DotProductMetric, _>(
             query,
             vector_storage,
             point_deleted,
             is_stopped,
+            filter_context,
+        ),
+        Distance::L1 => new_scorer_with_metric::<L1Metric, _>(
+            query,
+            vector_storage,
+            point_deleted,
+            is_stopped,
+            filter_context,
+        ),
+        Distance::Hamming => new_scorer_with_metric::<HammingMetric, _>(
+            query,
+            vector_storage,
+            point_deleted,
+            is_stopped,
+            filter_context,
+        ),
+        Distance::Chebyshev => new_scorer_with_metric::<ChebyshevMetric, _>(
+            query,
+            vector_storage,
+            point_deleted,
+            is_stopped,
+            filter_context,
         ),
     }
 }
@@ -157,22 +317,36 @@ fn new_scorer_with_metric<'a, TMetric: Metric + 'a, TVectorStorage: VectorStorag
     vector_storage: &'a TVectorStorage,
     point_deleted: &'a BitSlice,
     is_stopped: &'a AtomicBool,
-) -> Box<dyn RawScorer + 'a> {
+    filter_context: Option<&'a dyn FilterContext>,
+) -> OperationResult<Box<dyn RawScorer + 'a>> {
     let vec_deleted = vector_storage.deleted_vector_bitslice();
-    match query {
+    let raw_scorer = match query {
         QueryVector::Nearest(vector) => raw_scorer_from_query_scorer(
-            MetricQueryScorer::<TMetric, TVectorStorage>::new(vector, vector_storage),
+            MetricQueryScorer::<TMetric, TVectorStorage>::new(vector.try_into()?, vector_storage),
             point_deleted,
             vec_deleted,
             is_stopped,
+            filter_context,
         ),
         QueryVector::Recommend(reco_query) => raw_scorer_from_query_scorer(
-            RecoQueryScorer::<TMetric, TVectorStorage>::new(reco_query, vector_storage),
+            RecoQueryScorer::<TMetric, TVectorStorage>::new(reco_query.try_into()?, vector_storage),
             point_deleted,
             vec_deleted,
             is_stopped,
+            filter_context,
         ),
-    }
+        QueryVector::Context(context_query) => raw_scorer_from_query_scorer(
+            ContextQueryScorer::<TMetric, TVectorStorage>::new(
+                context_query.try_into()?,
+                vector_storage,
+            ),
+            point_deleted,
+            vec_deleted,
+            is_stopped,
+            filter_context,
+        ),
+    };
+    Ok(raw_scorer)
 }
 
 pub fn raw_scorer_from_query_scorer<'a, TQueryScorer: QueryScorer + 'a>(
@@ -180,12 +354,14 @@ pub fn raw_scorer_from_query_scorer<'a, TQueryScorer: QueryScorer + 'a>(
     point_deleted: &'a BitSlice,
     vec_deleted: &'a BitSlice,
     is_stopped: &'a AtomicBool,
+    filter_context: Option<&'a dyn FilterContext>,
 ) -> Box<dyn RawScorer + 'a> {
     Box::new(RawScorerImpl::<TQueryScorer> {
         query_scorer,
         point_deleted,
         vec_deleted,
         is_stopped,
+        filter_context,
     })
 }
 
@@ -247,6 +423,9 @@ where
                 .map(|x| *x)
                 // Default to deleted if the point mapping was removed from the ID tracker
                 .unwrap_or(true)
+            && self
+                .filter_context
+                .map_or(true, |filter_context| filter_context.check(point))
     }
 
     fn score_point(&self, point: PointOffsetType) -> ScoreType {
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+use std::io::Write;
 use std::mem::size_of;
 use std::ops::Range;
 use std::path::Path;
@@ -13,12 +15,13 @@ use rocksdb::DB;
 use serde::{Deserialize, Serialize};
 
 use super::chunked_vectors::ChunkedVectors;
-use super::vector_storage_base::VectorStorage;
+use super::common::STOP_CHECK_INTERVAL;
+use super::vector_storage_base::{Checkpoint, CheckpointJournals, VectorStorage};
 use super::VectorStorageEnum;
 use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
 use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
+use crate::data_types::vectors::{CowVector, VectorElementType};
 use crate::types::{Distance, QuantizationConfig};
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
 
@@ -34,6 +37,7 @@ pub struct SimpleVectorStorage {
     deleted: BitVec,
     /// Current number of deleted vectors.
     deleted_count: usize,
+    checkpoints: CheckpointJournals,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -86,6 +90,7 @@ pub fn open_simple_vector_storage(
             },
             deleted,
             deleted_count,
+            checkpoints: CheckpointJournals::default(),
         },
     ))))
 }
@@ -144,8 +149,8 @@ impl VectorStorage for SimpleVectorStorage {
         self.vectors.len()
     }
 
-    fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
-        self.vectors.get(key)
+    fn get_vector(&self, key: PointOffsetType) -> CowVector<'_> {
+        Cow::Borrowed(self.vectors.get(key))
     }
 
     fn insert_vector(
@@ -153,12 +158,24 @@ impl VectorStorage for SimpleVectorStorage {
         key: PointOffsetType,
         vector: &[VectorElementType],
     ) -> OperationResult<()> {
+        if (key as usize) < self.vectors.len() {
+            self.checkpoints
+                .record_overwrite(key, self.vectors.get(key));
+        }
         self.vectors.insert(key, vector)?;
         self.set_deleted(key, false);
         self.update_stored(key, false, Some(vector))?;
         Ok(())
     }
 
+    fn reserve(&mut self, additional: usize) -> OperationResult<()> {
+        self.vectors.reserve(additional)?;
+        let deleted_additional =
+            (self.vectors.len() + additional).saturating_sub(self.deleted.len());
+        self.deleted.reserve(deleted_additional);
+        Ok(())
+    }
+
     fn update_from(
         &mut self,
         other: &VectorStorageEnum,
@@ -166,14 +183,16 @@ impl VectorStorage for SimpleVectorStorage {
         stopped: &AtomicBool,
     ) -> OperationResult<Range<PointOffsetType>> {
         let start_index = self.vectors.len() as PointOffsetType;
-        for point_id in other_ids {
-            check_process_stopped(stopped)?;
+        for (i, point_id) in other_ids.enumerate() {
+            if i % STOP_CHECK_INTERVAL == 0 {
+                check_process_stopped(stopped)?;
+            }
             // Do not perform preprocessing - vectors should be already processed
             let other_vector = other.get_vector(point_id);
             let other_deleted = other.is_deleted_vector(point_id);
-            let new_id = self.vectors.push(other_vector)?;
+            let new_id = self.vectors.push(&other_vector)?;
             self.set_deleted(new_id, other_deleted);
-            self.update_stored(new_id, other_deleted, Some(other_vector))?;
+            self.update_stored(new_id, other_deleted, Some(&other_vector))?;
         }
         let end_index = self.vectors.len() as PointOffsetType;
         Ok(start_index..end_index)
@@ -240,9 +259,91 @@ impl VectorStorage for SimpleVectorStorage {
         self.deleted_count
     }
 
+    fn recount_deleted_vectors(&mut self) -> usize {
+        self.deleted_count = self.deleted.count_ones();
+        self.deleted_count
+    }
+
     fn deleted_vector_bitslice(&self) -> &BitSlice {
         self.deleted.as_bitslice()
     }
+
+    fn assert_contiguous(&self) -> OperationResult<()> {
+        let total_vector_count = self.vectors.len();
+        let mut stored = BitVec::repeat(false, total_vector_count);
+        for (key, _) in self.db_wrapper.lock_db().iter()? {
+            let point_id: PointOffsetType = bincode::deserialize(&key).map_err(|_| {
+                OperationError::service_error("cannot deserialize point id from db")
+            })?;
+            if let Some(mut bit) = stored.get_mut(point_id as usize) {
+                *bit = true;
+            }
+        }
+        match stored.first_zero() {
+            Some(gap) => Err(OperationError::InconsistentStorage {
+                description: format!(
+                    "Vector storage has {total_vector_count} vectors, but no stored record for offset {gap}",
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.checkpoints
+            .checkpoint(self.vectors.len(), self.deleted.as_bitslice())
+    }
+
+    fn restore_checkpoint(&mut self, checkpoint: Checkpoint) -> OperationResult<()> {
+        let total_vector_count = checkpoint.total_vector_count;
+        let num_vectors = self.vectors.len();
+        if total_vector_count > num_vectors {
+            return Err(OperationError::service_error(format!(
+                "Can't restore checkpoint with {total_vector_count} vectors, storage has only {num_vectors}",
+            )));
+        }
+        let overwritten = self.checkpoints.restore(&checkpoint)?;
+
+        // Drop persisted records past the checkpoint, so they are not loaded back on reopen
+        for key in total_vector_count..num_vectors {
+            self.db_wrapper
+                .remove(bincode::serialize(&(key as PointOffsetType)).unwrap())?;
+        }
+        self.vectors.truncate(total_vector_count);
+        self.deleted.truncate(total_vector_count);
+
+        for key in 0..total_vector_count as PointOffsetType {
+            let is_deleted = checkpoint.deleted.get(key as usize).is_some_and(|bit| *bit);
+            let previous_vector = overwritten.get(&key);
+            if let Some(vector) = previous_vector {
+                self.vectors.insert(key, vector)?;
+            }
+            let was_deleted = self.set_deleted(key, is_deleted);
+            if previous_vector.is_some() || was_deleted != is_deleted {
+                let vector = self.vectors.get(key).to_vec();
+                self.update_stored(key, is_deleted, Some(&vector))?;
+            }
+        }
+        self.deleted_count = self.deleted.count_ones();
+        Ok(())
+    }
+
+    fn clear(&mut self) -> OperationResult<()> {
+        // Drop persisted records too, so they are not loaded back on reopen
+        self.db_wrapper.recreate_column_family()?;
+        self.vectors = ChunkedVectors::new(self.dim);
+        self.quantized_vectors = None;
+        self.deleted = BitVec::new();
+        self.deleted_count = 0;
+        Ok(())
+    }
+
+    fn snapshot_to_tar(&self, _writer: &mut dyn Write) -> OperationResult<()> {
+        // Vectors live in the shared RocksDB, which is snapshotted with the segment database
+        Err(OperationError::service_error(
+            "Archive snapshot is not supported by RocksDB backed vector storage",
+        ))
+    }
 }
 
 /// Set deleted state in given bitvec.
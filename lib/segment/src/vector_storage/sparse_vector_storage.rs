@@ -0,0 +1,1343 @@
+use std::borrow::Cow;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::Write;
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+use bitvec::prelude::{BitSlice, BitVec};
+use common::types::{PointOffsetType, ScoredPointOffset};
+use memory::mmap_ops::{
+    create_and_ensure_length, open_write_mmap, transmute_from_u8_to_slice, transmute_to_u8_slice,
+};
+use serde::{Deserialize, Serialize};
+use sparse::common::sparse_vector::{SparseCosineScorer, SparseVector, SparseVectorRef};
+use sparse::common::types::{DimId, DimWeight};
+use sparse::common::{delta_encoding, weight_quantization};
+
+use crate::common::mmap_type::{MmapSlice, MmapType};
+use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::Flusher;
+use crate::types::Distance;
+use crate::vector_storage::common::STOP_CHECK_INTERVAL;
+use crate::vector_storage::dynamic_mmap_flags::DynamicMmapFlags;
+use crate::vector_storage::{VectorStorage, VectorStorageEnum};
+
+const CONFIG_PATH: &str = "config.json";
+const STATUS_PATH: &str = "status.dat";
+const INDICES_PATH: &str = "indices.dat";
+const WEIGHTS_PATH: &str = "weights.dat";
+const OFFSETS_PATH: &str = "offsets.dat";
+const DELETED_DIR_PATH: &str = "deleted";
+
+/// Size of the scale, stored in front of quantized weights
+const SCALE_SIZE: usize = size_of::<DimWeight>();
+
+/// Minimal number of elements in each mapped file
+#[cfg(debug_assertions)]
+const MINIMAL_CAPACITY: usize = 16;
+#[cfg(not(debug_assertions))]
+const MINIMAL_CAPACITY: usize = 64 * 1024;
+
+/// Storage of sparse vectors
+///
+/// Sparse vectors have no fixed dimension, so unlike dense storages this one does not implement
+/// [`VectorStorage`](super::VectorStorage), which operates on fixed size vector slices.
+/// It is a standalone storage, not a variant of [`VectorStorageEnum`](super::VectorStorageEnum),
+/// so segments do not pick it up by themselves.
+///
+/// Vectors are preprocessed for the storage distance on insert, see
+/// [`Distance::preprocess_sparse_vector`].
+///
+/// Storage opened with [`SparseVectorStorage::open_mmap`] keeps vectors in memory mapped files
+/// only, vectors are read as slices of the mapped data unless [`SparseStorageConfig`] requires
+/// decoding them.
+pub struct SparseVectorStorage {
+    /// Vectors of in-memory storage, unused if `files` is set
+    vectors: Vec<SparseVector>,
+    /// BitVec for deleted flags. Grows dynamically upto last set flag.
+    deleted: BitVec,
+    /// Current number of deleted vectors.
+    deleted_count: usize,
+    /// Maximum dimension index of all inserted vectors
+    max_index: usize,
+    /// Number of index-weight pairs of all stored vectors
+    nonzero_entries: usize,
+    distance: Distance,
+    /// Vectors of on-disk storage
+    files: Option<SparseFiles>,
+}
+
+impl Default for SparseVectorStorage {
+    fn default() -> Self {
+        Self {
+            vectors: vec![],
+            deleted: BitVec::default(),
+            deleted_count: 0,
+            max_index: 0,
+            nonzero_entries: 0,
+            distance: Distance::Dot,
+            files: None,
+        }
+    }
+}
+
+/// Layout of on-disk sparse storage, fixed when the storage is created
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseStorageConfig {
+    /// Store sorted indices as gaps between them, see [`delta_encoding`]
+    #[serde(default)]
+    pub delta_encoded_indices: bool,
+    /// Store weights as int8 with a scale per vector, see [`weight_quantization`]
+    ///
+    /// Decoded weights differ from inserted ones by up to half of the scale.
+    #[serde(default)]
+    pub quantized_weights: bool,
+}
+
+/// Sparse vector read from storage
+///
+/// Borrows the memory mapped data, unless it had to be decoded.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StoredSparseVector<'a> {
+    pub indices: Cow<'a, [DimId]>,
+    pub weights: Cow<'a, [DimWeight]>,
+}
+
+impl<'a> StoredSparseVector<'a> {
+    pub fn as_vec_ref(&self) -> SparseVectorRef<'_> {
+        SparseVectorRef::new(&self.indices, &self.weights)
+    }
+
+    /// Number of stored dimensions
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn to_owned_vector(self) -> SparseVector {
+        SparseVector::new(self.indices.into_owned(), self.weights.into_owned())
+    }
+}
+
+impl<'a> From<SparseVectorRef<'a>> for StoredSparseVector<'a> {
+    fn from(vector: SparseVectorRef<'a>) -> Self {
+        StoredSparseVector {
+            indices: Cow::Borrowed(vector.indices),
+            weights: Cow::Borrowed(vector.weights),
+        }
+    }
+}
+
+#[repr(C)]
+struct SparseFilesStatus {
+    /// Number of vectors
+    len: usize,
+    /// Number of used bytes in indices file
+    indices_len: usize,
+    /// Number of used bytes in weights file
+    weights_len: usize,
+}
+
+/// Position of stored bytes in a data file
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct DataSlot {
+    start: u64,
+    len: u32,
+    /// Number of bytes reserved for the data, at least `len`
+    capacity: u32,
+}
+
+/// Location of a single vector in the indices and weights files
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct SparseOffset {
+    indices: DataSlot,
+    weights: DataSlot,
+}
+
+/// Memory mapped files of on-disk sparse storage
+///
+/// Encoded indices and weights of all vectors are stored in flat files, the offsets file holds
+/// a [`SparseOffset`] record per key. A replacing vector reuses the slots of the previous one
+/// if it fits, otherwise it is appended and the old slot is left unused.
+struct SparseFiles {
+    directory: PathBuf,
+    config: SparseStorageConfig,
+    status: MmapType<SparseFilesStatus>,
+    indices: MmapSlice<u8>,
+    weights: MmapSlice<u8>,
+    offsets: MmapSlice<SparseOffset>,
+    deleted: DynamicMmapFlags,
+}
+
+impl SparseFiles {
+    fn open(directory: &Path, config: SparseStorageConfig) -> OperationResult<Self> {
+        create_dir_all(directory)?;
+        let config = ensure_config(&directory.join(CONFIG_PATH), config)?;
+        let status_path = directory.join(STATUS_PATH);
+        if !status_path.exists() {
+            create_and_ensure_length(&status_path, size_of::<SparseFilesStatus>())?;
+        }
+        let status: MmapType<SparseFilesStatus> =
+            unsafe { MmapType::try_from(open_write_mmap(&status_path)?)? };
+
+        let indices = open_mmap_slice(&directory.join(INDICES_PATH), status.indices_len)?;
+        let weights = open_mmap_slice(&directory.join(WEIGHTS_PATH), status.weights_len)?;
+        let offsets = open_mmap_slice(&directory.join(OFFSETS_PATH), status.len)?;
+        let deleted = DynamicMmapFlags::open(&directory.join(DELETED_DIR_PATH))?;
+
+        let files = SparseFiles {
+            directory: directory.to_path_buf(),
+            config,
+            status,
+            indices,
+            weights,
+            offsets,
+            deleted,
+        };
+        files.check_offsets()?;
+        Ok(files)
+    }
+
+    /// Check that all records point into the stored data and can be decoded
+    fn check_offsets(&self) -> OperationResult<()> {
+        let inconsistent = |key: usize, what: &str| OperationError::InconsistentStorage {
+            description: format!("Sparse vector {key} has malformed {what}"),
+        };
+        for (key, offset) in self.offsets[..self.status.len].iter().enumerate() {
+            if !offset.indices.is_within(self.status.indices_len) {
+                return Err(inconsistent(key, "indices offset"));
+            }
+            if !offset.weights.is_within(self.status.weights_len)
+                || self.weights_count(offset.weights.len as usize).is_none()
+            {
+                return Err(inconsistent(key, "weights offset"));
+            }
+            let indices = slot_bytes(&self.indices, offset.indices);
+            let indices_count = if self.config.delta_encoded_indices {
+                delta_encoding::decode_indices(indices).map(|indices| indices.len())
+            } else {
+                (indices.len() % size_of::<DimId>() == 0)
+                    .then_some(indices.len() / size_of::<DimId>())
+            };
+            if indices_count != Some(self.vector_len(key)) {
+                return Err(inconsistent(key, "indices"));
+            }
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.status.len
+    }
+
+    fn offset(&self, key: usize) -> Option<SparseOffset> {
+        (key < self.len()).then(|| self.offsets[key])
+    }
+
+    /// Number of stored dimensions of the vector, zero if out of range
+    fn vector_len(&self, key: usize) -> usize {
+        self.offset(key).map_or(0, |offset| {
+            // Checked on open
+            self.weights_count(offset.weights.len as usize)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Number of weights, encoded in `bytes`, `None` if it is not a valid size
+    fn weights_count(&self, bytes: usize) -> Option<usize> {
+        if !self.config.quantized_weights {
+            return (bytes % size_of::<DimWeight>() == 0).then_some(bytes / size_of::<DimWeight>());
+        }
+        match bytes {
+            0 => Some(0),
+            1..=SCALE_SIZE => None,
+            _ => Some(bytes - SCALE_SIZE),
+        }
+    }
+
+    /// Number of bytes the vector takes in the indices and weights files
+    fn stored_bytes(&self, key: usize) -> usize {
+        self.offset(key).map_or(0, |offset| {
+            offset.indices.len as usize + offset.weights.len as usize
+        })
+    }
+
+    fn get_vector(&self, key: PointOffsetType) -> Option<StoredSparseVector<'_>> {
+        let offset = self.offset(key as usize)?;
+        let indices = slot_bytes(&self.indices, offset.indices);
+        let indices = if self.config.delta_encoded_indices {
+            // Decoding is checked on open
+            Cow::Owned(delta_encoding::decode_indices(indices).unwrap_or_default())
+        } else {
+            Cow::Borrowed(transmute_from_u8_to_slice(indices))
+        };
+        let weights = slot_bytes(&self.weights, offset.weights);
+        let weights = if !self.config.quantized_weights {
+            Cow::Borrowed(transmute_from_u8_to_slice(weights))
+        } else if weights.is_empty() {
+            Cow::Borrowed(&[][..])
+        } else {
+            let (scale, quantized) = weights.split_at(SCALE_SIZE);
+            let scale = DimWeight::from_le_bytes(scale.try_into().unwrap());
+            Cow::Owned(weight_quantization::decode_weights(
+                transmute_from_u8_to_slice(quantized),
+                scale,
+            ))
+        };
+        Some(StoredSparseVector { indices, weights })
+    }
+
+    fn write_vector(
+        &mut self,
+        key: PointOffsetType,
+        vector: SparseVectorRef,
+    ) -> OperationResult<()> {
+        let key = key as usize;
+        let previous = self.offset(key).unwrap_or_default();
+        let indices = if self.config.delta_encoded_indices {
+            Cow::Owned(delta_encoding::encode_indices(vector.indices))
+        } else {
+            Cow::Borrowed(transmute_to_u8_slice(vector.indices))
+        };
+        let indices = write_slot(
+            &mut self.indices,
+            &self.directory.join(INDICES_PATH),
+            &mut self.status.indices_len,
+            previous.indices,
+            &indices,
+        )?;
+        let weights = if self.config.quantized_weights && !vector.is_empty() {
+            let (quantized, scale) = weight_quantization::quantize_weights(vector.weights);
+            let mut bytes = scale.to_le_bytes().to_vec();
+            bytes.extend_from_slice(transmute_to_u8_slice(&quantized));
+            Cow::Owned(bytes)
+        } else {
+            Cow::Borrowed(transmute_to_u8_slice(vector.weights))
+        };
+        let weights = write_slot(
+            &mut self.weights,
+            &self.directory.join(WEIGHTS_PATH),
+            &mut self.status.weights_len,
+            previous.weights,
+            &weights,
+        )?;
+
+        let old_len = self.len();
+        if key >= old_len {
+            ensure_capacity(
+                &mut self.offsets,
+                &self.directory.join(OFFSETS_PATH),
+                key + 1,
+            )?;
+            // Skipped keys hold empty vectors
+            self.offsets[old_len..key].fill(SparseOffset::default());
+        }
+        self.offsets[key] = SparseOffset { indices, weights };
+        self.status.len = self.status.len.max(key + 1);
+        Ok(())
+    }
+
+    fn set_deleted(&mut self, key: PointOffsetType, deleted: bool) -> OperationResult<()> {
+        if self.deleted.len() <= key as usize {
+            self.deleted.set_len(key as usize + 1)?;
+        }
+        self.deleted.set(key, deleted);
+        Ok(())
+    }
+
+    fn flusher(&self) -> Flusher {
+        // Flush data before the status, which makes it reachable
+        let flushers = [
+            self.indices.flusher(),
+            self.weights.flusher(),
+            self.offsets.flusher(),
+            self.status.flusher(),
+            self.deleted.flusher(),
+        ];
+        Box::new(move || {
+            for flusher in flushers {
+                flusher()?;
+            }
+            Ok(())
+        })
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![
+            self.directory.join(CONFIG_PATH),
+            self.directory.join(STATUS_PATH),
+            self.directory.join(INDICES_PATH),
+            self.directory.join(WEIGHTS_PATH),
+            self.directory.join(OFFSETS_PATH),
+        ];
+        files.extend(self.deleted.files());
+        files
+    }
+}
+
+impl DataSlot {
+    fn is_within(self, used_len: usize) -> bool {
+        self.len <= self.capacity && self.start as usize + self.capacity as usize <= used_len
+    }
+}
+
+/// Store `config` for new storage, or check that the existing storage uses the same one
+fn ensure_config(path: &Path, config: SparseStorageConfig) -> OperationResult<SparseStorageConfig> {
+    if !path.exists() {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        serde_json::to_writer(&mut file, &config)?;
+        file.flush()?;
+        return Ok(config);
+    }
+    let stored: SparseStorageConfig = serde_json::from_reader(File::open(path)?)?;
+    if stored != config {
+        return Err(OperationError::service_error(format!(
+            "Wrong configuration in {}: expected {config:?}, found {stored:?}",
+            path.display(),
+        )));
+    }
+    Ok(stored)
+}
+
+fn slot_bytes(mmap: &MmapSlice<u8>, slot: DataSlot) -> &[u8] {
+    let start = slot.start as usize;
+    &mmap[start..start + slot.len as usize]
+}
+
+/// Write `data` into the `previous` slot if it fits, otherwise append it after `used_len` bytes
+fn write_slot(
+    mmap: &mut MmapSlice<u8>,
+    path: &Path,
+    used_len: &mut usize,
+    previous: DataSlot,
+    data: &[u8],
+) -> OperationResult<DataSlot> {
+    let len = data.len();
+    let slot = if len <= previous.capacity as usize {
+        DataSlot {
+            len: len as u32,
+            ..previous
+        }
+    } else {
+        ensure_capacity(mmap, path, *used_len + len)?;
+        let slot = DataSlot {
+            start: *used_len as u64,
+            len: len as u32,
+            capacity: len as u32,
+        };
+        *used_len += len;
+        slot
+    };
+    let start = slot.start as usize;
+    mmap[start..start + len].copy_from_slice(data);
+    Ok(slot)
+}
+
+/// Map file as a slice of `T` with at least `len` elements, growing the file if needed
+fn open_mmap_slice<T>(path: &Path, len: usize) -> OperationResult<MmapSlice<T>> {
+    let file_len = if path.exists() {
+        std::fs::metadata(path)?.len() as usize
+    } else {
+        0
+    };
+    let capacity = (file_len / size_of::<T>()).max(len).max(MINIMAL_CAPACITY);
+    if capacity * size_of::<T>() != file_len {
+        create_and_ensure_length(path, capacity * size_of::<T>())?;
+    }
+    let mmap = open_write_mmap(path)?;
+    Ok(unsafe { MmapSlice::try_from(mmap)? })
+}
+
+/// Remap `mmap` to fit at least `len` elements, doubling its capacity
+fn ensure_capacity<T>(mmap: &mut MmapSlice<T>, path: &Path, len: usize) -> OperationResult<()> {
+    if len > mmap.len() {
+        *mmap = open_mmap_slice(path, len.max(mmap.len() * 2))?;
+    }
+    Ok(())
+}
+
+/// Source of vectors for [`SparseVectorStorage::update_from`]
+pub trait SparseVectorSource {
+    /// Get sparse vector by the given key
+    ///
+    /// Storages of dense vectors return [`OperationError::WrongSparse`].
+    fn get_sparse(&self, key: PointOffsetType) -> OperationResult<StoredSparseVector<'_>>;
+
+    fn is_deleted_vector(&self, key: PointOffsetType) -> bool;
+}
+
+impl SparseVectorSource for SparseVectorStorage {
+    fn get_sparse(&self, key: PointOffsetType) -> OperationResult<StoredSparseVector<'_>> {
+        Ok(self.get_vector(key))
+    }
+
+    fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
+        SparseVectorStorage::is_deleted_vector(self, key)
+    }
+}
+
+/// All variants of [`VectorStorageEnum`] hold dense vectors
+impl SparseVectorSource for VectorStorageEnum {
+    fn get_sparse(&self, _key: PointOffsetType) -> OperationResult<StoredSparseVector<'_>> {
+        Err(OperationError::WrongSparse)
+    }
+
+    fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
+        VectorStorage::is_deleted_vector(self, key)
+    }
+}
+
+/// Only dot product and cosine similarity are defined for sparse vectors
+fn check_distance(distance: Distance) -> OperationResult<()> {
+    match distance {
+        Distance::Dot | Distance::Cosine => Ok(()),
+        Distance::Euclid | Distance::L1 | Distance::Hamming | Distance::Chebyshev => {
+            Err(OperationError::ValidationError {
+                description: format!("Distance {distance:?} is not supported by sparse vectors"),
+            })
+        }
+    }
+}
+
+impl SparseVectorStorage {
+    /// In-memory storage, scoring with dot product
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// In-memory storage, scoring with `distance`
+    pub fn with_distance(distance: Distance) -> OperationResult<Self> {
+        check_distance(distance)?;
+        Ok(Self {
+            distance,
+            ..Self::default()
+        })
+    }
+
+    /// Open on-disk storage in `path`
+    ///
+    /// Existing storage must have been created with the same `config`. Stored vectors are not
+    /// preprocessed again, so `distance` must not change either.
+    pub fn open_mmap(
+        path: &Path,
+        config: SparseStorageConfig,
+        distance: Distance,
+    ) -> OperationResult<Self> {
+        check_distance(distance)?;
+        let files = SparseFiles::open(path, config)?;
+        let deleted: BitVec = (0..files.len()).map(|key| files.deleted.get(key)).collect();
+        let mut storage = Self {
+            vectors: vec![],
+            deleted_count: deleted.count_ones(),
+            deleted,
+            max_index: 0,
+            nonzero_entries: 0,
+            distance,
+            files: Some(files),
+        };
+        storage.recompute_dimension_stats();
+        Ok(storage)
+    }
+
+    pub fn is_on_disk(&self) -> bool {
+        self.files.is_some()
+    }
+
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files
+            .as_ref()
+            .map(SparseFiles::files)
+            .unwrap_or_default()
+    }
+
+    pub fn flusher(&self) -> Flusher {
+        match &self.files {
+            Some(files) => files.flusher(),
+            None => Box::new(|| Ok(())),
+        }
+    }
+
+    /// Maximum dimension index seen so far, zero if there are no vectors
+    pub fn vector_dim(&self) -> usize {
+        self.max_index
+    }
+
+    /// Maximum dimension id of all stored vectors, zero if there are no vectors
+    ///
+    /// Replaced vectors may still contribute until [`SparseVectorStorage::update_from`]
+    /// recomputes the value.
+    pub fn max_dimension(&self) -> DimId {
+        self.max_index as DimId
+    }
+
+    /// Total number of index-weight pairs of all stored vectors
+    ///
+    /// - includes soft deleted vectors, as they are still stored
+    pub fn total_nonzero_entries(&self) -> usize {
+        self.nonzero_entries
+    }
+
+    pub fn distance(&self) -> Distance {
+        self.distance
+    }
+
+    /// Number of vectors
+    ///
+    /// - includes soft deleted vectors, as they are still stored
+    pub fn total_vector_count(&self) -> usize {
+        match &self.files {
+            Some(files) => files.len(),
+            None => self.vectors.len(),
+        }
+    }
+
+    pub fn available_vector_count(&self) -> usize {
+        self.total_vector_count()
+            .saturating_sub(self.deleted_vector_count())
+    }
+
+    /// Size of stored indices and weights of available vectors in bytes
+    pub fn size_of_available_vectors_in_bytes(&self) -> usize {
+        (0..self.total_vector_count())
+            .filter(|key| !self.is_deleted_vector(*key as PointOffsetType))
+            .map(|key| match &self.files {
+                Some(files) => files.stored_bytes(key),
+                None => self.vectors[key].len() * (size_of::<DimId>() + size_of::<DimWeight>()),
+            })
+            .sum()
+    }
+
+    /// Get vector by the given key, out of range keys yield an empty vector
+    pub fn get_vector(&self, key: PointOffsetType) -> StoredSparseVector<'_> {
+        self.stored_vector(key).unwrap_or_default()
+    }
+
+    /// Get vector by the given key, if it is in range and not deleted
+    pub fn get_vector_opt(&self, key: PointOffsetType) -> Option<StoredSparseVector<'_>> {
+        if self.is_deleted_vector(key) {
+            return None;
+        }
+        self.stored_vector(key)
+    }
+
+    /// Preprocess and insert or replace vector at `key`
+    ///
+    /// Skipped keys are filled with empty vectors. Vector must be sorted by index, have unique
+    /// indices and finite weights, see [`SparseVector::validate_sorted`].
+    pub fn insert_vector(
+        &mut self,
+        key: PointOffsetType,
+        vector: &SparseVector,
+    ) -> OperationResult<()> {
+        vector
+            .validate_sorted()
+            .map_err(|err| OperationError::ValidationError {
+                description: err.to_string(),
+            })?;
+        let vector = self.distance.preprocess_sparse_vector(vector.clone());
+        self.write_vector(key, vector.as_vec_ref())
+    }
+
+    /// Score available vectors by the given keys against `query`
+    ///
+    /// The query is preprocessed the same way as inserted vectors. Deleted and out of range
+    /// keys are skipped.
+    ///
+    /// Cosine scores use actual norms of stored vectors, as quantized weights are not
+    /// normalized exactly.
+    pub fn score_points(
+        &self,
+        query: &SparseVector,
+        points: &[PointOffsetType],
+    ) -> OperationResult<Vec<ScoredPointOffset>> {
+        query
+            .validate_sorted()
+            .map_err(|err| OperationError::ValidationError {
+                description: err.to_string(),
+            })?;
+        let query = self.distance.preprocess_sparse_vector(query.clone());
+        let cosine_scorer =
+            (self.distance == Distance::Cosine).then(|| SparseCosineScorer::new(&query));
+        let scores = points
+            .iter()
+            .filter_map(|&idx| {
+                let vector = self.get_vector_opt(idx)?;
+                let score = match &cosine_scorer {
+                    Some(scorer) => scorer.score(vector.as_vec_ref()),
+                    None => query.as_vec_ref().dot(vector.as_vec_ref()),
+                };
+                Some(ScoredPointOffset { idx, score })
+            })
+            .collect();
+        Ok(scores)
+    }
+
+    /// Append vectors with `other_ids` from `other` storage, carrying over deletion flags
+    ///
+    /// Vectors of `other` are expected to be preprocessed for the same distance. Returns the range
+    /// of new offsets. Fails with [`OperationError::WrongSparse`] if `other` yields dense vectors.
+    pub fn update_from(
+        &mut self,
+        other: &dyn SparseVectorSource,
+        other_ids: &mut dyn Iterator<Item = PointOffsetType>,
+        stopped: &AtomicBool,
+    ) -> OperationResult<Range<PointOffsetType>> {
+        let start_index = self.total_vector_count() as PointOffsetType;
+        for (i, point_id) in other_ids.enumerate() {
+            if i % STOP_CHECK_INTERVAL == 0 {
+                check_process_stopped(stopped)?;
+            }
+            // Vectors of other storage are already validated
+            let other_vector = other.get_sparse(point_id)?;
+            let other_deleted = other.is_deleted_vector(point_id);
+            let new_id = self.total_vector_count() as PointOffsetType;
+            self.write_vector(new_id, other_vector.as_vec_ref())?;
+            self.set_deleted(new_id, other_deleted)?;
+        }
+        self.recompute_dimension_stats();
+        let end_index = self.total_vector_count() as PointOffsetType;
+        Ok(start_index..end_index)
+    }
+
+    /// Flag the vector by the given key as deleted
+    ///
+    /// Returns true if the vector was not deleted before and is now deleted
+    pub fn delete_vector(&mut self, key: PointOffsetType) -> OperationResult<bool> {
+        if key as usize >= self.total_vector_count() {
+            return Ok(false);
+        }
+        Ok(!self.set_deleted(key, true)?)
+    }
+
+    pub fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
+        self.deleted.get(key as usize).map(|b| *b).unwrap_or(false)
+    }
+
+    pub fn deleted_vector_count(&self) -> usize {
+        self.deleted_count
+    }
+
+    pub fn deleted_vector_bitslice(&self) -> &BitSlice {
+        self.deleted.as_bitslice()
+    }
+
+    /// Remove all vectors and deletion flags, next inserted vector starts at offset 0
+    ///
+    /// Only supported by in-memory storage.
+    pub fn clear(&mut self) -> OperationResult<()> {
+        if self.files.is_some() {
+            return Err(OperationError::service_error(
+                "Clearing is not supported by on-disk sparse vector storage",
+            ));
+        }
+        *self = Self {
+            distance: self.distance,
+            ..Self::default()
+        };
+        Ok(())
+    }
+
+    fn stored_vector(&self, key: PointOffsetType) -> Option<StoredSparseVector<'_>> {
+        match &self.files {
+            Some(files) => files.get_vector(key),
+            None => self
+                .vectors
+                .get(key as usize)
+                .map(|vector| vector.as_vec_ref().into()),
+        }
+    }
+
+    /// Store sorted and validated vector, undeleting it
+    fn write_vector(
+        &mut self,
+        key: PointOffsetType,
+        vector: SparseVectorRef,
+    ) -> OperationResult<()> {
+        let previous_len = match &self.files {
+            Some(files) => files.vector_len(key as usize),
+            None => self.vectors.get(key as usize).map_or(0, SparseVector::len),
+        };
+        match &mut self.files {
+            Some(files) => files.write_vector(key, vector)?,
+            None => {
+                let key_usize = key as usize;
+                if key_usize >= self.vectors.len() {
+                    self.vectors
+                        .resize_with(key_usize + 1, || SparseVector::new(vec![], vec![]));
+                }
+                self.vectors[key_usize] = vector.to_owned_vector();
+            }
+        }
+        self.nonzero_entries = self.nonzero_entries - previous_len + vector.len();
+        if let Some(&max_index) = vector.indices.last() {
+            self.max_index = self.max_index.max(max_index as usize);
+        }
+        self.set_deleted(key, false)?;
+        Ok(())
+    }
+
+    /// Recompute max dimension and number of entries from all stored vectors
+    fn recompute_dimension_stats(&mut self) {
+        let (max_index, nonzero_entries) = (0..self.total_vector_count() as PointOffsetType)
+            .map(|key| self.get_vector(key))
+            .fold((0, 0), |(max_index, entries), vector| {
+                let vector_max = vector.indices.last().copied().unwrap_or_default() as usize;
+                (max_index.max(vector_max), entries + vector.len())
+            });
+        self.max_index = max_index;
+        self.nonzero_entries = nonzero_entries;
+    }
+
+    /// Set deleted flag for given key. Returns previous deleted state.
+    fn set_deleted(&mut self, key: PointOffsetType, deleted: bool) -> OperationResult<bool> {
+        let key_usize = key as usize;
+        if key_usize >= self.total_vector_count() {
+            return Ok(false);
+        }
+        if key_usize >= self.deleted.len() {
+            if !deleted {
+                return Ok(false);
+            }
+            self.deleted.resize(key_usize + 1, false);
+        }
+        if let Some(files) = &mut self.files {
+            files.set_deleted(key, deleted)?;
+        }
+        let was_deleted = self.deleted.replace(key_usize, deleted);
+        if was_deleted != deleted {
+            if !was_deleted {
+                self.deleted_count += 1;
+            } else {
+                self.deleted_count -= 1;
+            }
+        }
+        Ok(was_deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector_storage::appendable_mmap_vector_storage::open_appendable_memmap_vector_storage;
+    use crate::vector_storage::DEFAULT_STOPPED;
+
+    #[test]
+    fn test_sparse_vector_storage() {
+        let vectors = [
+            SparseVector::new(vec![0, 2], vec![1.0, 0.5]),
+            SparseVector::new(vec![7], vec![0.3]),
+            SparseVector::new(vec![1, 3, 4], vec![0.1, 0.2, 0.3]),
+            SparseVector::new(vec![], vec![]),
+        ];
+        let mut storage = SparseVectorStorage::new();
+        assert_eq!(storage.vector_dim(), 0);
+        assert_eq!(storage.distance(), Distance::Dot);
+
+        for (i, vector) in vectors.iter().enumerate() {
+            storage.insert_vector(i as PointOffsetType, vector).unwrap();
+        }
+        assert_eq!(storage.total_vector_count(), 4);
+        assert_eq!(storage.vector_dim(), 7);
+
+        assert!(storage.delete_vector(1).unwrap());
+        assert!(storage.delete_vector(3).unwrap());
+        assert!(!storage.delete_vector(3).unwrap());
+        // Out of range keys are never deleted
+        assert!(!storage.delete_vector(10).unwrap());
+        assert_eq!(storage.deleted_vector_count(), 2);
+        assert_eq!(storage.available_vector_count(), 2);
+        assert_eq!(storage.deleted_vector_bitslice().count_ones(), 2);
+        assert!(storage.is_deleted_vector(1));
+        assert!(!storage.is_deleted_vector(2));
+
+        for (i, vector) in vectors.iter().enumerate() {
+            assert_eq!(
+                storage.get_vector(i as PointOffsetType).as_vec_ref(),
+                vector.as_vec_ref(),
+            );
+        }
+
+        // Reinserting undeletes, inserting past the end fills the gap
+        storage.insert_vector(1, &vectors[2]).unwrap();
+        assert!(!storage.is_deleted_vector(1));
+        assert_eq!(storage.deleted_vector_count(), 1);
+        storage.insert_vector(6, &vectors[0]).unwrap();
+        assert_eq!(storage.total_vector_count(), 7);
+        assert_eq!(storage.get_vector(5).as_vec_ref(), vectors[3].as_vec_ref());
+        assert!(storage.get_vector(7).is_empty());
+        assert_eq!(storage.get_vector_opt(7), None);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_preprocess() {
+        let vectors = [
+            SparseVector::new(vec![1, 4, 9], vec![3.0, 0.0, 4.0]),
+            SparseVector::new(vec![4, 7], vec![-1.0, 1.0]),
+            SparseVector::new(vec![2], vec![0.0]),
+        ];
+        let query = SparseVector::new(vec![1, 4, 7], vec![1.0, 2.0, 2.0]);
+
+        let mut dot = SparseVectorStorage::new();
+        let mut cosine = SparseVectorStorage::with_distance(Distance::Cosine).unwrap();
+        for (i, vector) in vectors.iter().enumerate() {
+            dot.insert_vector(i as PointOffsetType, vector).unwrap();
+            cosine.insert_vector(i as PointOffsetType, vector).unwrap();
+        }
+        cosine.delete_vector(1).unwrap();
+
+        // Dot product keeps vectors as inserted, cosine normalizes them
+        assert_eq!(dot.get_vector(0).as_vec_ref(), vectors[0].as_vec_ref());
+        assert_eq!(cosine.get_vector(0).weights.as_ref(), &[0.6, 0.0, 0.8]);
+
+        let scores = dot.score_points(&query, &[0, 1, 2, 5]).unwrap();
+        let expected: Vec<_> = vectors.iter().map(|vector| query.dot(vector)).collect();
+        assert_eq!(
+            scores.iter().map(|scored| scored.score).collect::<Vec<_>>(),
+            expected,
+        );
+
+        let scores = cosine.score_points(&query, &[0, 1, 2]).unwrap();
+        assert_eq!(
+            scores.iter().map(|scored| scored.idx).collect::<Vec<_>>(),
+            vec![0, 2],
+        );
+        assert!((scores[0].score - query.cosine(&vectors[0])).abs() < 1e-6);
+        assert_eq!(scores[1].score, 0.0);
+
+        let unsorted = SparseVector::new(vec![7, 1], vec![1.0, 1.0]);
+        assert!(dot.score_points(&unsorted, &[0]).is_err());
+        assert!(SparseVectorStorage::with_distance(Distance::Euclid).is_err());
+
+        // Distance survives clearing
+        cosine.clear().unwrap();
+        assert_eq!(cosine.distance(), Distance::Cosine);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_rejects_malformed() {
+        let mut storage = SparseVectorStorage::new();
+        storage
+            .insert_vector(0, &SparseVector::new(vec![0, 2], vec![1.0, 0.5]))
+            .unwrap();
+
+        let unsorted = SparseVector::new(vec![3, 1], vec![0.1, 0.2]);
+        let result = storage.insert_vector(1, &unsorted);
+        assert!(matches!(
+            result,
+            Err(OperationError::ValidationError { .. })
+        ));
+        assert_eq!(storage.total_vector_count(), 1);
+
+        // Replacing an existing vector keeps the old one
+        let duplicate = SparseVector::new(vec![1, 1], vec![0.1, 0.2]);
+        assert!(storage.insert_vector(0, &duplicate).is_err());
+        assert_eq!(
+            storage.get_vector(0).as_vec_ref(),
+            SparseVector::new(vec![0, 2], vec![1.0, 0.5]).as_vec_ref(),
+        );
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_size() {
+        let mut storage = SparseVectorStorage::new();
+        assert_eq!(storage.size_of_available_vectors_in_bytes(), 0);
+
+        storage
+            .insert_vector(0, &SparseVector::new(vec![0, 2], vec![1.0, 0.5]))
+            .unwrap();
+        storage
+            .insert_vector(1, &SparseVector::new(vec![1, 3, 4], vec![0.1, 0.2, 0.3]))
+            .unwrap();
+        // Index and weight take 4 bytes each
+        assert_eq!(storage.size_of_available_vectors_in_bytes(), 40);
+
+        storage.delete_vector(1).unwrap();
+        assert_eq!(storage.size_of_available_vectors_in_bytes(), 16);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_dimension_stats() {
+        let mut storage = SparseVectorStorage::new();
+        assert_eq!(storage.max_dimension(), 0);
+        assert_eq!(storage.total_nonzero_entries(), 0);
+
+        let mut expected_entries = 0;
+        for i in 0..10 {
+            let max_dim = (i + 1) * 100;
+            let vector = SparseVector::new(vec![i, max_dim / 2, max_dim], vec![0.1, 0.2, 0.3]);
+            storage.insert_vector(i, &vector).unwrap();
+            expected_entries += 3;
+            assert_eq!(storage.max_dimension(), max_dim);
+            assert_eq!(storage.total_nonzero_entries(), expected_entries);
+        }
+
+        // Replacing a vector updates the number of entries
+        storage
+            .insert_vector(9, &SparseVector::new(vec![5], vec![1.0]))
+            .unwrap();
+        assert_eq!(storage.total_nonzero_entries(), expected_entries - 2);
+
+        // Stats are recomputed from scratch on update
+        let mut target = SparseVectorStorage::new();
+        target
+            .update_from(&storage, &mut (5..10), &DEFAULT_STOPPED)
+            .unwrap();
+        assert_eq!(target.max_dimension(), 900);
+        assert_eq!(target.total_nonzero_entries(), 4 * 3 + 1);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_clear() {
+        let mut storage = SparseVectorStorage::new();
+        for key in 0..4 {
+            let vector = SparseVector::new(vec![key, 10 + key], vec![0.1, 0.2]);
+            storage.insert_vector(key, &vector).unwrap();
+        }
+        storage.delete_vector(2).unwrap();
+
+        storage.clear().unwrap();
+        assert_eq!(storage.total_vector_count(), 0);
+        assert_eq!(storage.deleted_vector_count(), 0);
+        assert_eq!(storage.max_dimension(), 0);
+        assert_eq!(storage.total_nonzero_entries(), 0);
+        assert!(!storage.is_deleted_vector(2));
+
+        let vector = SparseVector::new(vec![5], vec![1.0]);
+        storage.insert_vector(0, &vector).unwrap();
+        assert_eq!(storage.total_vector_count(), 1);
+        assert_eq!(storage.get_vector(0).as_vec_ref(), vector.as_vec_ref());
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_update_from() {
+        let vectors = [
+            SparseVector::new(vec![0, 2], vec![1.0, 0.5]),
+            SparseVector::new(vec![7], vec![0.3]),
+            SparseVector::new(vec![1, 3, 4], vec![0.1, 0.2, 0.3]),
+        ];
+        let mut storage = SparseVectorStorage::new();
+        storage.insert_vector(0, &vectors[0]).unwrap();
+        storage.insert_vector(1, &vectors[1]).unwrap();
+
+        let mut other = SparseVectorStorage::new();
+        for (i, vector) in vectors.iter().enumerate() {
+            other.insert_vector(i as PointOffsetType, vector).unwrap();
+        }
+        other.delete_vector(1).unwrap();
+
+        let range = storage
+            .update_from(&other, &mut [2, 1].into_iter(), &DEFAULT_STOPPED)
+            .unwrap();
+        assert_eq!(range, 2..4);
+        assert_eq!(storage.total_vector_count(), 4);
+        assert_eq!(storage.get_vector(2).as_vec_ref(), vectors[2].as_vec_ref());
+        assert_eq!(storage.get_vector(3).as_vec_ref(), vectors[1].as_vec_ref());
+        assert!(!storage.is_deleted_vector(1));
+        assert!(!storage.is_deleted_vector(2));
+        assert!(storage.is_deleted_vector(3));
+        assert_eq!(storage.deleted_vector_count(), 1);
+        assert_eq!(storage.vector_dim(), 7);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_update_from_dense() {
+        let dir = tempfile::Builder::new()
+            .prefix("dense_storage")
+            .tempdir()
+            .unwrap();
+        let dense = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        dense
+            .borrow_mut()
+            .insert_vector(0, &[1.0, 0.0, 0.5, 0.0])
+            .unwrap();
+
+        let mut storage = SparseVectorStorage::new();
+        storage
+            .insert_vector(0, &SparseVector::new(vec![0, 2], vec![1.0, 0.5]))
+            .unwrap();
+        let result = storage.update_from(&*dense.borrow(), &mut [0].into_iter(), &DEFAULT_STOPPED);
+        assert!(matches!(result, Err(OperationError::WrongSparse)));
+        assert_eq!(storage.total_vector_count(), 1);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_mmap_persistence() {
+        let dir = tempfile::Builder::new()
+            .prefix("sparse_storage")
+            .tempdir()
+            .unwrap();
+        let vectors = [
+            SparseVector::new(vec![0, 2], vec![1.0, 0.5]),
+            SparseVector::new(vec![9, 11], vec![0.3, -0.2]),
+            SparseVector::new(vec![1, 3, 4], vec![0.1, 0.2, 0.3]),
+        ];
+        {
+            let mut storage = SparseVectorStorage::open_mmap(
+                dir.path(),
+                SparseStorageConfig::default(),
+                Distance::Dot,
+            )
+            .unwrap();
+            assert!(storage.is_on_disk());
+            for (i, vector) in vectors.iter().enumerate() {
+                storage.insert_vector(i as PointOffsetType, vector).unwrap();
+            }
+            // Replaced vector must be loaded in its latest state, gap must be empty
+            storage.insert_vector(0, &vectors[2]).unwrap();
+            storage.insert_vector(4, &vectors[1]).unwrap();
+            storage.delete_vector(1).unwrap();
+            storage.flusher()().unwrap();
+
+            let files = storage.files();
+            assert!(files.iter().all(|file| file.exists()));
+            assert!(files.contains(&dir.path().join(OFFSETS_PATH)));
+        }
+
+        let storage = SparseVectorStorage::open_mmap(
+            dir.path(),
+            SparseStorageConfig::default(),
+            Distance::Dot,
+        )
+        .unwrap();
+        assert_eq!(storage.total_vector_count(), 5);
+        assert_eq!(storage.vector_dim(), 11);
+        assert_eq!(storage.deleted_vector_count(), 1);
+        assert!(storage.is_deleted_vector(1));
+        let expected = [
+            &vectors[2],
+            &vectors[1],
+            &vectors[2],
+            &SparseVector::new(vec![], vec![]),
+            &vectors[1],
+        ];
+        for (i, vector) in expected.into_iter().enumerate() {
+            assert_eq!(
+                storage.get_vector(i as PointOffsetType).as_vec_ref(),
+                vector.as_vec_ref(),
+            );
+        }
+
+        assert!(!SparseVectorStorage::new().is_on_disk());
+        assert!(SparseVectorStorage::new().files().is_empty());
+
+        // Persisted data is never implicitly dropped
+        let mut storage = storage;
+        assert!(storage.clear().is_err());
+        assert_eq!(storage.total_vector_count(), 5);
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_mmap_reuses_slots() {
+        let dir = tempfile::Builder::new()
+            .prefix("sparse_storage")
+            .tempdir()
+            .unwrap();
+        let data_len = |storage: &SparseVectorStorage| {
+            storage.files.as_ref().unwrap().status.weights_len / size_of::<DimWeight>()
+        };
+
+        let mut storage = SparseVectorStorage::open_mmap(
+            dir.path(),
+            SparseStorageConfig::default(),
+            Distance::Dot,
+        )
+        .unwrap();
+        let long = SparseVector::new(vec![1, 3, 4], vec![0.1, 0.2, 0.3]);
+        let short = SparseVector::new(vec![5, 6], vec![0.5, 0.6]);
+        storage.insert_vector(0, &long).unwrap();
+        storage.insert_vector(1, &short).unwrap();
+        assert_eq!(data_len(&storage), 5);
+
+        // Replacements fitting into the previous slot are written in place
+        storage.insert_vector(0, &short).unwrap();
+        storage.insert_vector(0, &long).unwrap();
+        assert_eq!(data_len(&storage), 5);
+        assert_eq!(storage.get_vector(0).as_vec_ref(), long.as_vec_ref());
+        assert_eq!(storage.get_vector(1).as_vec_ref(), short.as_vec_ref());
+
+        // Larger replacement is appended
+        storage.insert_vector(1, &long).unwrap();
+        assert_eq!(data_len(&storage), 8);
+
+        // Grow files past their minimal capacity
+        let vectors: Vec<_> = (0..100)
+            .map(|i| SparseVector::new(vec![i, i + 1, i + 2], vec![0.1, 0.2, i as f32]))
+            .collect();
+        for (i, vector) in vectors.iter().enumerate() {
+            storage.insert_vector(i as PointOffsetType, vector).unwrap();
+        }
+        assert_eq!(data_len(&storage), 8 + 98 * 3);
+        storage.flusher()().unwrap();
+        drop(storage);
+
+        let storage = SparseVectorStorage::open_mmap(
+            dir.path(),
+            SparseStorageConfig::default(),
+            Distance::Dot,
+        )
+        .unwrap();
+        assert_eq!(storage.total_vector_count(), 100);
+        assert_eq!(storage.total_nonzero_entries(), 300);
+        assert_eq!(storage.max_dimension(), 101);
+        for (i, vector) in vectors.iter().enumerate() {
+            assert_eq!(
+                storage.get_vector(i as PointOffsetType).as_vec_ref(),
+                vector.as_vec_ref()
+            );
+        }
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_mmap_delta_encoded() {
+        let dir = tempfile::Builder::new()
+            .prefix("sparse_storage")
+            .tempdir()
+            .unwrap();
+        let config = SparseStorageConfig {
+            delta_encoded_indices: true,
+            quantized_weights: false,
+        };
+        let vectors = [
+            SparseVector::new((100..200).collect(), vec![0.5; 100]),
+            SparseVector::new(vec![0, 1, 1_000, DimId::MAX], vec![0.1, 0.2, 0.3, 0.4]),
+            SparseVector::new(vec![], vec![]),
+            SparseVector::new(vec![7], vec![-1.0]),
+        ];
+        {
+            let mut storage =
+                SparseVectorStorage::open_mmap(dir.path(), config, Distance::Dot).unwrap();
+            for (i, vector) in vectors.iter().enumerate() {
+                storage.insert_vector(i as PointOffsetType, vector).unwrap();
+            }
+            // Replacing vector with fewer gap bytes fits into the previous slot
+            storage.insert_vector(3, &vectors[1]).unwrap();
+            storage.insert_vector(3, &vectors[3]).unwrap();
+            storage.flusher()().unwrap();
+
+            // Small gaps take a single byte instead of four, the largest index takes five
+            assert_eq!(storage.files.as_ref().unwrap().status.indices_len, 119);
+            assert_eq!(
+                storage.size_of_available_vectors_in_bytes(),
+                100 + 9 + 1 + 105 * size_of::<DimWeight>(),
+            );
+        }
+
+        let storage = SparseVectorStorage::open_mmap(dir.path(), config, Distance::Dot).unwrap();
+        assert_eq!(storage.total_vector_count(), 4);
+        assert_eq!(storage.total_nonzero_entries(), 105);
+        assert_eq!(storage.max_dimension(), DimId::MAX);
+        for (i, vector) in vectors.iter().enumerate() {
+            let stored = storage.get_vector(i as PointOffsetType);
+            assert!(matches!(stored.indices, Cow::Owned(_)));
+            assert_eq!(stored.to_owned_vector(), *vector);
+        }
+
+        // Layout can not be changed for existing storage
+        assert!(SparseVectorStorage::open_mmap(
+            dir.path(),
+            SparseStorageConfig::default(),
+            Distance::Dot
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_mmap_quantized() {
+        let dir = tempfile::Builder::new()
+            .prefix("sparse_storage")
+            .tempdir()
+            .unwrap();
+        let config = SparseStorageConfig {
+            delta_encoded_indices: false,
+            quantized_weights: true,
+        };
+        let vectors = [
+            SparseVector::new(
+                vec![1, 4, 9, 16, 25],
+                vec![0.013, -2.5, 0.77, 1.25, -0.0001],
+            ),
+            SparseVector::new(vec![1, 2, 3, 5, 8], vec![0.3, 1.2, -0.4, 0.9, 0.05]),
+            SparseVector::new(vec![], vec![]),
+            SparseVector::new(vec![2, 5, 13], vec![-0.7, 0.33, 2.1]),
+        ];
+        {
+            let mut storage =
+                SparseVectorStorage::open_mmap(dir.path(), config, Distance::Dot).unwrap();
+            for (i, vector) in vectors.iter().enumerate() {
+                storage.insert_vector(i as PointOffsetType, vector).unwrap();
+            }
+            storage.flusher()().unwrap();
+
+            // Weight takes a single byte, plus a scale per non-empty vector
+            assert_eq!(
+                storage.size_of_available_vectors_in_bytes(),
+                13 * size_of::<DimId>() + 13 + 3 * SCALE_SIZE,
+            );
+        }
+
+        let storage = SparseVectorStorage::open_mmap(dir.path(), config, Distance::Dot).unwrap();
+        assert_eq!(storage.total_vector_count(), 4);
+        assert_eq!(storage.total_nonzero_entries(), 13);
+        let query = &vectors[1];
+        for (i, vector) in vectors.iter().enumerate() {
+            let stored = storage.get_vector(i as PointOffsetType);
+            assert!(matches!(stored.indices, Cow::Borrowed(_)));
+            let decoded = stored.to_owned_vector();
+            assert_eq!(decoded.indices, vector.indices);
+
+            // Decoded weights are within half of the quantization step
+            let max_abs = vector
+                .weights
+                .iter()
+                .fold(0.0f32, |max, w| max.max(w.abs()));
+            let max_error = max_abs / f32::from(i8::MAX) / 2.0;
+            for (original, decoded) in vector.weights.iter().zip(&decoded.weights) {
+                assert!((original - decoded).abs() <= max_error + f32::EPSILON);
+            }
+            assert!((query.dot(vector) - query.dot(&decoded)).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_sparse_vector_storage_mmap_quantized_cosine() {
+        let dir = tempfile::Builder::new()
+            .prefix("sparse_storage")
+            .tempdir()
+            .unwrap();
+        let config = SparseStorageConfig {
+            delta_encoded_indices: true,
+            quantized_weights: true,
+        };
+        let vectors = [
+            SparseVector::new(vec![1, 3, 8], vec![0.5, 0.25, 1.5]),
+            SparseVector::new(vec![2, 5, 13], vec![-0.7, 0.33, 2.1]),
+            SparseVector::new(vec![1, 2, 3, 5, 8], vec![0.3, 1.2, -0.4, 0.9, 0.05]),
+        ];
+        let mut storage =
+            SparseVectorStorage::open_mmap(dir.path(), config, Distance::Cosine).unwrap();
+        for (i, vector) in vectors.iter().enumerate() {
+            storage.insert_vector(i as PointOffsetType, vector).unwrap();
+        }
+
+        let query = SparseVector::new(vec![1, 2, 3, 5, 8], vec![0.3, 1.2, -0.4, 0.9, 0.05]);
+        let scores = storage.score_points(&query, &[0, 1, 2]).unwrap();
+        for (scored, vector) in scores.iter().zip(&vectors) {
+            assert!((scored.score - query.cosine(vector)).abs() < 0.01);
+        }
+        // Identical vector stays the best match, despite quantization
+        assert!(scores[2].score <= 1.0 + f32::EPSILON);
+        assert!((scores[2].score - 1.0).abs() < 1e-3);
+        assert!(scores[2].score > scores[0].score.max(scores[1].score));
+    }
+}
@@ -96,7 +96,7 @@ fn test_random_score(
         .collect_vec()
         .into();
 
-    let raw_scorer = new_raw_scorer(query.clone(), storage, deleted_points);
+    let raw_scorer = new_raw_scorer(query.clone(), storage, deleted_points)?;
 
     let is_stopped = AtomicBool::new(false);
     let async_raw_scorer = if let VectorStorageEnum::Memmap(storage) = storage {
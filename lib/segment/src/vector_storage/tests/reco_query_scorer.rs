@@ -177,7 +177,7 @@ fn scoring_equivalency(
             query.clone(),
             &raw_storage,
             id_tracker.deleted_point_bitslice(),
-        );
+        )?;
 
         let is_stopped = AtomicBool::new(false);
 
@@ -187,8 +187,8 @@ fn scoring_equivalency(
                 id_tracker.deleted_point_bitslice(),
                 other_storage.deleted_vector_bitslice(),
                 &is_stopped,
-            ),
-            None => new_raw_scorer(query, &other_storage, id_tracker.deleted_point_bitslice()),
+            )?,
+            None => new_raw_scorer(query, &other_storage, id_tracker.deleted_point_bitslice())?,
         };
 
         let points =
@@ -1,18 +1,37 @@
+use std::cell::RefCell;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use atomic_refcell::AtomicRefCell;
 use common::types::{PointOffsetType, ScoredPointOffset};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sparse::common::sparse_vector::SparseVector;
 use tempfile::Builder;
 
+use super::utils::{delete_random_vectors, insert_distributed_vectors, sampler};
+use crate::common::operation_error::OperationError;
 use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
-use crate::data_types::vectors::QueryVector;
+use crate::data_types::vectors::{QueryVector, VectorOrSparseRef};
 use crate::fixtures::payload_context_fixture::FixtureIdTracker;
 use crate::id_tracker::{IdTracker, IdTrackerSS};
-use crate::types::{Distance, PointIdType, QuantizationConfig, ScalarQuantizationConfig};
+use crate::payload_storage::FilterContext;
+use crate::types::{
+    BinaryQuantizationConfig, CompressionRatio, Distance, Indexes, PointIdType,
+    ProductQuantizationConfig, QuantizationConfig, ScalarQuantizationConfig, VectorDataConfig,
+    VectorStorageType,
+};
 use crate::vector_storage::appendable_mmap_vector_storage::open_appendable_memmap_vector_storage;
+use crate::vector_storage::chunked_utils::chunk_name;
+use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage;
+use crate::vector_storage::query::context_query::{ContextPair, ContextQuery};
+use crate::vector_storage::query::reco_query::RecoQuery;
 use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
-use crate::vector_storage::{new_raw_scorer, VectorStorage, VectorStorageEnum};
+use crate::vector_storage::{
+    new_raw_scorer, new_raw_scorer_with_distance, raw_scorer_impl, rescore,
+    score_and_collect_with_deadline, VectorStorage, VectorStorageEnum, DEFAULT_STOPPED,
+};
 
 fn do_test_delete_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
     let points = vec![
@@ -58,6 +77,7 @@ fn do_test_delete_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
     )
+    .unwrap()
     .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 5);
     assert_eq!(closest.len(), 3, "must have 3 vectors, 2 are deleted");
     assert_eq!(closest[0].idx, 0);
@@ -84,6 +104,7 @@ fn do_test_delete_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
     )
+    .unwrap()
     .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 5);
     assert_eq!(closest.len(), 2, "must have 2 vectors, 3 are deleted");
     assert_eq!(closest[0].idx, 4);
@@ -109,6 +130,7 @@ fn do_test_delete_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
     )
+    .unwrap()
     .peek_top_all(5);
     assert!(closest.is_empty(), "must have no results, all deleted");
 }
@@ -167,6 +189,7 @@ fn do_test_update_from_delete_points(storage: Arc<AtomicRefCell<VectorStorageEnu
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
     )
+    .unwrap()
     .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 5);
     assert_eq!(closest.len(), 3, "must have 3 vectors, 2 are deleted");
     assert_eq!(closest[0].idx, 0);
@@ -216,6 +239,7 @@ fn do_test_score_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
     )
+    .unwrap()
     .peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 2);
 
     let top_idx = match closest.get(0) {
@@ -234,7 +258,8 @@ fn do_test_score_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
         query,
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
-    );
+    )
+    .unwrap();
     let closest = raw_scorer.peek_top_iter(&mut [0, 1, 2, 3, 4].iter().cloned(), 2);
 
     let query_points = vec![0, 1, 2, 3, 4];
@@ -303,17 +328,22 @@ fn test_score_quantized_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
     let query: QueryVector = vec![0.5, 0.5, 0.5, 0.5].into();
 
     {
-        let scorer_quant = borrowed_storage.quantized_storage().unwrap().raw_scorer(
-            query.clone(),
-            borrowed_id_tracker.deleted_point_bitslice(),
-            borrowed_storage.deleted_vector_bitslice(),
-            &stopped,
-        );
+        let scorer_quant = borrowed_storage
+            .quantized_storage()
+            .unwrap()
+            .raw_scorer(
+                query.clone(),
+                borrowed_id_tracker.deleted_point_bitslice(),
+                borrowed_storage.deleted_vector_bitslice(),
+                &stopped,
+            )
+            .unwrap();
         let scorer_orig = new_raw_scorer(
             query.clone(),
             &borrowed_storage,
             borrowed_id_tracker.deleted_point_bitslice(),
-        );
+        )
+        .unwrap();
         for i in 0..5 {
             let quant = scorer_quant.score_point(i);
             let orig = scorer_orig.score_point(i);
@@ -330,17 +360,22 @@ fn test_score_quantized_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
     borrowed_storage.load_quantization(dir.path()).unwrap();
     assert_eq!(files, borrowed_storage.files());
 
-    let scorer_quant = borrowed_storage.quantized_storage().unwrap().raw_scorer(
-        query.clone(),
-        borrowed_id_tracker.deleted_point_bitslice(),
-        borrowed_storage.deleted_vector_bitslice(),
-        &stopped,
-    );
+    let scorer_quant = borrowed_storage
+        .quantized_storage()
+        .unwrap()
+        .raw_scorer(
+            query.clone(),
+            borrowed_id_tracker.deleted_point_bitslice(),
+            borrowed_storage.deleted_vector_bitslice(),
+            &stopped,
+        )
+        .unwrap();
     let scorer_orig = new_raw_scorer(
         query,
         &borrowed_storage,
         borrowed_id_tracker.deleted_point_bitslice(),
-    );
+    )
+    .unwrap();
     for i in 0..5 {
         let quant = scorer_quant.score_point(i);
         let orig = scorer_orig.score_point(i);
@@ -352,109 +387,2097 @@ fn test_score_quantized_points(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
     }
 }
 
-#[test]
-fn test_delete_points_in_simple_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+fn do_test_append_vectors(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let points = vec![
+        vec![1.0, 0.0, 1.0, 1.0],
+        vec![1.0, 0.0, 1.0, 0.0],
+        vec![1.0, 1.0, 1.0, 1.0],
+    ];
+    let mut borrowed_storage = storage.borrow_mut();
 
-    {
-        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
-        do_test_delete_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+    for (i, vec) in points.iter().enumerate() {
+        let offset = borrowed_storage.append_vector(vec).unwrap();
+        assert_eq!(offset, i as PointOffsetType);
+    }
+
+    // Appending continues after explicitly inserted vectors
+    borrowed_storage.insert_vector(5, &points[0]).unwrap();
+    assert_eq!(borrowed_storage.append_vector(&points[1]).unwrap(), 6);
+
+    assert_eq!(borrowed_storage.total_vector_count(), 7);
+    for (i, vec) in points.iter().enumerate() {
+        assert_eq!(
+            borrowed_storage.get_vector(i as PointOffsetType),
+            vec.as_slice()
+        );
     }
-    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
 }
 
-#[test]
-fn test_update_from_delete_points_simple_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
-        do_test_update_from_delete_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+fn do_test_insert_vectors(
+    single_storage: Arc<AtomicRefCell<VectorStorageEnum>>,
+    batch_storage: Arc<AtomicRefCell<VectorStorageEnum>>,
+) {
+    let num_vectors = 10_000;
+    let dim = 4;
+    let mut sampler = sampler(StdRng::seed_from_u64(42));
+    let vectors: Vec<Vec<f32>> = (0..num_vectors)
+        .map(|_| (&mut sampler).take(dim).collect())
+        .collect();
+
+    let mut single_storage = single_storage.borrow_mut();
+    let mut batch_storage = batch_storage.borrow_mut();
+
+    // Some vector is already in place and gets overwritten
+    single_storage.insert_vector(1, &vectors[0]).unwrap();
+    batch_storage.insert_vector(1, &vectors[0]).unwrap();
+    single_storage.delete_vector(1).unwrap();
+    batch_storage.delete_vector(1).unwrap();
+
+    for (key, vector) in vectors.iter().enumerate() {
+        single_storage
+            .insert_vector(key as PointOffsetType, vector)
+            .unwrap();
     }
 
-    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let refs: Vec<VectorOrSparseRef> = vectors.iter().map(|v| v.as_slice().into()).collect();
+    batch_storage.insert_vectors(0, &refs).unwrap();
+
+    assert_eq!(batch_storage.total_vector_count(), num_vectors);
+    assert_eq!(
+        batch_storage.total_vector_count(),
+        single_storage.total_vector_count(),
+    );
+    assert_eq!(batch_storage.deleted_vector_count(), 0);
+    assert_eq!(
+        batch_storage.deleted_vector_count(),
+        single_storage.deleted_vector_count(),
+    );
+    for (key, vector) in vectors.iter().enumerate() {
+        let key = key as PointOffsetType;
+        assert_eq!(batch_storage.get_vector(key), vector.as_slice());
+        assert_eq!(
+            batch_storage.get_vector(key),
+            single_storage.get_vector(key)
+        );
+        assert!(!batch_storage.is_deleted_vector(key));
+    }
+
+    // Sparse vectors are rejected
+    let sparse = SparseVector::new(vec![0], vec![1.0]);
+    let result = batch_storage.insert_vectors(0, &[VectorOrSparseRef::Sparse(&sparse)]);
+    assert!(matches!(result, Err(OperationError::WrongSparse)));
 }
 
-#[test]
-fn test_score_points_in_simple_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
-        do_test_score_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+fn do_test_reserve(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let mut borrowed_storage = storage.borrow_mut();
+    borrowed_storage.reserve(1000).unwrap();
+    assert_eq!(borrowed_storage.total_vector_count(), 0);
+
+    for i in 0..1000 {
+        borrowed_storage
+            .insert_vector(i, &[i as f32, 0.0, 1.0, 0.0])
+            .unwrap();
     }
+    borrowed_storage.delete_vector(10).unwrap();
 
-    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    // Reserving again keeps stored vectors intact, inserting beyond reserved capacity grows
+    borrowed_storage.reserve(10).unwrap();
+    for i in 1000..1100 {
+        borrowed_storage
+            .insert_vector(i, &[i as f32, 0.0, 1.0, 0.0])
+            .unwrap();
+    }
+
+    assert_eq!(borrowed_storage.total_vector_count(), 1100);
+    assert_eq!(borrowed_storage.available_vector_count(), 1099);
+    assert!(borrowed_storage.is_deleted_vector(10));
+    for i in 0..1100 {
+        assert_eq!(borrowed_storage.get_vector(i)[0], i as f32);
+    }
 }
 
-#[test]
-fn test_score_quantized_points_simple_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
-        test_score_quantized_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+fn do_test_clone_range_to(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 10;
+    let mut borrowed_storage = storage.borrow_mut();
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
     }
+    borrowed_storage.delete_vector(2).unwrap();
+    borrowed_storage.delete_vector(7).unwrap();
 
-    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
-    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let left_dir = Builder::new().prefix("left_storage").tempdir().unwrap();
+    let right_dir = Builder::new().prefix("right_storage").tempdir().unwrap();
+    let (left, left_remap) = borrowed_storage
+        .clone_range_to(0..5, left_dir.path())
+        .unwrap();
+    let (right, right_remap) = borrowed_storage
+        .clone_range_to(5..num_vectors, right_dir.path())
+        .unwrap();
+
+    assert_eq!(left.total_vector_count(), 4);
+    assert_eq!(right.total_vector_count(), 4);
+    assert_eq!(left.deleted_vector_count(), 0);
+    assert_eq!(right.deleted_vector_count(), 0);
+
+    // New offsets start from 0 in each part
+    assert_eq!(left_remap[&0], 0);
+    assert_eq!(left_remap[&3], 2);
+    assert_eq!(right_remap[&5], 0);
+    assert_eq!(right_remap[&8], 2);
+
+    for old_id in 0..num_vectors {
+        let copied = match (left_remap.get(&old_id), right_remap.get(&old_id)) {
+            (Some(&new_id), None) => left.get_vector(new_id),
+            (None, Some(&new_id)) => right.get_vector(new_id),
+            (None, None) => {
+                assert!(borrowed_storage.is_deleted_vector(old_id));
+                continue;
+            }
+            (Some(_), Some(_)) => panic!("vector {old_id} is copied twice"),
+        };
+        assert_eq!(copied, borrowed_storage.get_vector(old_id));
+    }
 }
 
-// ----------------------------------------------
+fn do_test_deleted_since(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let mut borrowed_storage = storage.borrow_mut();
+    for i in 0..6 {
+        borrowed_storage
+            .insert_vector(i, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(1).unwrap();
+    let snapshot = borrowed_storage.deleted_vector_bitslice().to_bitvec();
 
-#[test]
-fn test_delete_points_in_appendable_memmap_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
-        do_test_delete_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+    assert!(borrowed_storage.deleted_since(&snapshot).is_empty());
+
+    borrowed_storage.delete_vector(1).unwrap();
+    borrowed_storage.delete_vector(4).unwrap();
+    borrowed_storage.delete_vector(2).unwrap();
+    assert_eq!(borrowed_storage.deleted_since(&snapshot), vec![2, 4]);
+
+    // Ids not covered by the snapshot are treated as not deleted before
+    borrowed_storage
+        .insert_vector(10, &[1.0, 1.0, 0.0, 1.0])
+        .unwrap();
+    borrowed_storage.delete_vector(10).unwrap();
+    assert_eq!(
+        borrowed_storage.deleted_since(&snapshot[..3]),
+        vec![2, 4, 10]
+    );
+}
+
+struct EvenIdsFilter;
+
+impl FilterContext for EvenIdsFilter {
+    fn check(&self, point_id: PointOffsetType) -> bool {
+        point_id % 2 == 0
     }
+}
 
-    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+fn do_test_restore_checkpoint(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let mut borrowed_storage = storage.borrow_mut();
+
+    for i in 0..4 {
+        borrowed_storage
+            .append_vector(&[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(1).unwrap();
+    let checkpoint = borrowed_storage.checkpoint();
+
+    for i in 4..8 {
+        borrowed_storage
+            .append_vector(&[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(2).unwrap();
+    borrowed_storage.delete_vector(6).unwrap();
+    assert_eq!(borrowed_storage.total_vector_count(), 8);
+    assert_eq!(borrowed_storage.deleted_vector_count(), 3);
+
+    borrowed_storage.restore_checkpoint(checkpoint).unwrap();
+    assert_eq!(borrowed_storage.total_vector_count(), 4);
+    assert_eq!(borrowed_storage.deleted_vector_count(), 1);
+    assert!(borrowed_storage.is_deleted_vector(1));
+    assert!(!borrowed_storage.is_deleted_vector(2));
+    assert_eq!(&*borrowed_storage.get_vector(3), &[3.0, 1.0, 0.0, 1.0]);
+
+    // Overwrites below the checkpoint are undone
+    let checkpoint = borrowed_storage.checkpoint();
+    borrowed_storage
+        .insert_vector(0, &[-1.0, -1.0, -1.0, -1.0])
+        .unwrap();
+    borrowed_storage
+        .insert_vector(0, &[-2.0, -2.0, -2.0, -2.0])
+        .unwrap();
+    borrowed_storage
+        .insert_vector(1, &[-1.0, -1.0, -1.0, -1.0])
+        .unwrap();
+    let later_checkpoint = borrowed_storage.checkpoint();
+    borrowed_storage.restore_checkpoint(checkpoint).unwrap();
+    assert_eq!(&*borrowed_storage.get_vector(0), &[0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(&*borrowed_storage.get_vector(1), &[1.0, 1.0, 0.0, 1.0]);
+    assert!(borrowed_storage.is_deleted_vector(1));
+    assert_eq!(borrowed_storage.deleted_vector_count(), 1);
+    // Checkpoints taken after a restored one are invalidated
+    assert!(borrowed_storage
+        .restore_checkpoint(later_checkpoint)
+        .is_err());
+
+    // Dropped offsets are reused, without stale deletion flags
+    for i in 4..8 {
+        let offset = borrowed_storage
+            .append_vector(&[i as f32 * 10.0, 1.0, 0.0, 1.0])
+            .unwrap();
+        assert_eq!(offset, i);
+    }
+    assert_eq!(borrowed_storage.deleted_vector_count(), 1);
+    assert!(!borrowed_storage.is_deleted_vector(6));
+    assert_eq!(&*borrowed_storage.get_vector(6), &[60.0, 1.0, 0.0, 1.0]);
+
+    // Can't restore forward
+    let checkpoint = borrowed_storage.checkpoint();
+    borrowed_storage
+        .restore_checkpoint(checkpoint.clone())
+        .unwrap();
+    let mut ahead = checkpoint;
+    ahead.total_vector_count += 1;
+    assert!(borrowed_storage.restore_checkpoint(ahead).is_err());
 }
 
-#[test]
-fn test_update_from_delete_points_appendable_memmap_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+fn do_test_count_scored_with_filter(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 10;
+    let id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(4).unwrap();
 
-        do_test_update_from_delete_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+    let query: QueryVector = vec![1.0, 0.0, 0.0, 0.0].into();
+    let (scored_count, top) = borrowed_storage
+        .count_scored_with_filter(
+            query,
+            &EvenIdsFilter,
+            id_tracker.deleted_point_bitslice(),
+            3,
+        )
+        .unwrap();
+
+    // Even ids, except the deleted one
+    assert_eq!(scored_count, 4);
+    let top_ids: Vec<_> = top.iter().map(|scored| scored.idx).collect();
+    assert_eq!(top_ids, vec![8, 6, 2]);
+}
+
+struct OddIdsFilter;
+
+impl FilterContext for OddIdsFilter {
+    fn check(&self, point_id: PointOffsetType) -> bool {
+        point_id % 2 == 1
     }
+}
 
-    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+fn do_test_filtered_raw_scorer(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 10;
+    let id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(5).unwrap();
+
+    let query: QueryVector = vec![1.0, 0.0, 0.0, 0.0].into();
+    let raw_scorer = raw_scorer_impl(
+        query,
+        &*borrowed_storage,
+        id_tracker.deleted_point_bitslice(),
+        &DEFAULT_STOPPED,
+        Some(&OddIdsFilter),
+    )
+    .unwrap();
+
+    assert!(!raw_scorer.check_vector(2));
+    assert!(!raw_scorer.check_vector(5));
+    assert!(raw_scorer.check_vector(3));
+
+    // Even and deleted points are excluded from scoring
+    let points: Vec<_> = (0..num_vectors as PointOffsetType).collect();
+    let mut scores = vec![ScoredPointOffset { idx: 0, score: 0. }; num_vectors];
+    let scored_count = raw_scorer.score_points(&points, &mut scores);
+    let scored_ids: Vec<_> = scores[..scored_count]
+        .iter()
+        .map(|scored| scored.idx)
+        .collect();
+    assert_eq!(scored_ids, vec![1, 3, 7, 9]);
+
+    let top_ids: Vec<_> = raw_scorer
+        .peek_top_all(3)
+        .iter()
+        .map(|scored| scored.idx)
+        .collect();
+    assert_eq!(top_ids, vec![9, 7, 3]);
+
+    let top_ids: Vec<_> = raw_scorer
+        .peek_top_iter(&mut points.iter().copied(), 10)
+        .iter()
+        .map(|scored| scored.idx)
+        .collect();
+    assert_eq!(top_ids, vec![9, 7, 3, 1]);
 }
 
-#[test]
-fn test_score_points_in_appendable_memmap_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
-        do_test_score_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+fn do_test_par_iter_scores(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 1000;
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+    insert_distributed_vectors(
+        &mut *borrowed_storage,
+        num_vectors,
+        &mut sampler(rng.clone()),
+    )
+    .unwrap();
+    delete_random_vectors(&mut rng, &mut *borrowed_storage, &mut id_tracker, 100).unwrap();
+
+    let query: QueryVector = sampler(rng.clone()).take(4).collect::<Vec<_>>().into();
+    let is_stopped = AtomicBool::new(false);
+    for max_threads in [1, 3, 8] {
+        let parallel = borrowed_storage
+            .par_iter_scores(
+                query.clone(),
+                id_tracker.deleted_point_bitslice(),
+                10,
+                max_threads,
+                &is_stopped,
+            )
+            .unwrap();
+        let serial = new_raw_scorer(
+            query.clone(),
+            &borrowed_storage,
+            id_tracker.deleted_point_bitslice(),
+        )
+        .unwrap()
+        .peek_top_all(10);
+        assert_eq!(parallel, serial);
+        assert!(parallel
+            .iter()
+            .all(|scored| !borrowed_storage.is_deleted_vector(scored.idx)));
     }
 
-    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    let is_stopped = AtomicBool::new(true);
+    let result = borrowed_storage.par_iter_scores(
+        query,
+        id_tracker.deleted_point_bitslice(),
+        10,
+        2,
+        &is_stopped,
+    );
+    assert!(result.is_err());
 }
 
-#[test]
-fn test_score_quantized_points_appendable_memmap_vector_storages() {
-    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-    {
-        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
-        test_score_quantized_points(storage.clone());
-        storage.borrow().flusher()().unwrap();
+fn do_test_for_each_vector(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 10;
+    let mut borrowed_storage = storage.borrow_mut();
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
     }
+    borrowed_storage.delete_vector(3).unwrap();
 
-    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    let stopped = AtomicBool::new(false);
+    let mut visited = vec![];
+    borrowed_storage
+        .for_each_vector(
+            |key, vector| {
+                let vector: &[f32] = vector.try_into()?;
+                assert_eq!(vector[0], key as f32);
+                visited.push(key);
+                Ok(())
+            },
+            &stopped,
+        )
+        .unwrap();
+    assert_eq!(visited, vec![0, 1, 2, 4, 5, 6, 7, 8, 9]);
+
+    // First callback error stops iteration
+    let mut calls = 0;
+    let result = borrowed_storage.for_each_vector(
+        |key, _| {
+            calls += 1;
+            if key == 5 {
+                return Err(OperationError::service_error("export failed"));
+            }
+            Ok(())
+        },
+        &stopped,
+    );
+    assert!(result.is_err());
+    assert_eq!(calls, 5);
+
+    let stopped = AtomicBool::new(true);
+    let mut calls = 0;
+    let result = borrowed_storage.for_each_vector(
+        |_, _| {
+            calls += 1;
+            Ok(())
+        },
+        &stopped,
+    );
+    assert!(matches!(result, Err(OperationError::Cancelled { .. })));
+    assert_eq!(calls, 0);
+}
+
+fn do_test_iter_vectors(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let mut borrowed_storage = storage.borrow_mut();
+    assert_eq!(borrowed_storage.iter_vectors().count(), 0);
+
+    let num_vectors = 10;
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(0).unwrap();
+    borrowed_storage.delete_vector(3).unwrap();
+    borrowed_storage.delete_vector(9).unwrap();
+
+    let mut visited = vec![];
+    for (key, vector) in borrowed_storage.iter_vectors() {
+        assert_eq!(vector, borrowed_storage.get_vector(key));
+        assert_eq!(vector[0], key as f32);
+        visited.push(key);
+    }
+    assert_eq!(visited, vec![1, 2, 4, 5, 6, 7, 8]);
+    assert_eq!(visited.len(), borrowed_storage.available_vector_count());
+}
+
+fn do_test_snapshot_to_tar(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 100;
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+    insert_distributed_vectors(
+        &mut *borrowed_storage,
+        num_vectors,
+        &mut sampler(rng.clone()),
+    )
+    .unwrap();
+    delete_random_vectors(&mut rng, &mut *borrowed_storage, &mut id_tracker, 10).unwrap();
+    borrowed_storage.flusher()().unwrap();
+
+    let mut archive = Vec::new();
+    borrowed_storage.snapshot_to_tar(&mut archive).unwrap();
+
+    let config = VectorDataConfig {
+        size: borrowed_storage.vector_dim(),
+        distance: borrowed_storage.distance(),
+        storage_type: VectorStorageType::Memory,
+        index: Indexes::Plain {},
+        quantization_config: None,
+        max_sparse_dimension: None,
+        datatype: Default::default(),
+        vector_cache_size: None,
+        strict_vector_checks: false,
+    };
+    let restore_dir = Builder::new().prefix("restored_storage").tempdir().unwrap();
+    let restored =
+        VectorStorageEnum::restore_from_tar(&mut archive.as_slice(), restore_dir.path(), &config)
+            .unwrap();
+
+    assert_eq!(restored.total_vector_count(), num_vectors);
+    assert_eq!(
+        restored.deleted_vector_count(),
+        borrowed_storage.deleted_vector_count(),
+    );
+    for i in 0..num_vectors as PointOffsetType {
+        assert_eq!(restored.get_vector(i), borrowed_storage.get_vector(i));
+        assert_eq!(
+            restored.is_deleted_vector(i),
+            borrowed_storage.is_deleted_vector(i),
+        );
+    }
+}
+
+fn do_test_merge_many(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let dirs: Vec<_> = (0..3)
+        .map(|_| Builder::new().prefix("source_storage").tempdir().unwrap())
+        .collect();
+    let sources: Vec<_> = dirs
+        .iter()
+        .zip([10, 1, 25])
+        .enumerate()
+        .map(|(seed, (dir, num_vectors))| {
+            let source =
+                open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+            let mut rng = StdRng::seed_from_u64(seed as u64);
+            let mut id_tracker = FixtureIdTracker::new(num_vectors);
+            let mut borrowed_source = source.borrow_mut();
+            insert_distributed_vectors(
+                &mut *borrowed_source,
+                num_vectors,
+                &mut sampler(rng.clone()),
+            )
+            .unwrap();
+            delete_random_vectors(
+                &mut rng,
+                &mut *borrowed_source,
+                &mut id_tracker,
+                num_vectors / 5,
+            )
+            .unwrap();
+            drop(borrowed_source);
+            source
+        })
+        .collect();
+    let borrowed_sources: Vec<_> = sources.iter().map(|source| source.borrow()).collect();
+    let source_refs: Vec<&VectorStorageEnum> =
+        borrowed_sources.iter().map(|source| &**source).collect();
+
+    let mut borrowed_storage = storage.borrow_mut();
+    let ranges = borrowed_storage
+        .merge_many(&source_refs, &AtomicBool::new(false))
+        .unwrap();
+
+    assert_eq!(ranges, vec![0..10, 10..11, 11..36]);
+    assert_eq!(borrowed_storage.total_vector_count(), 36);
+    for (source, range) in source_refs.iter().zip(ranges) {
+        for (source_id, id) in range.enumerate() {
+            let source_id = source_id as PointOffsetType;
+            assert_eq!(
+                borrowed_storage.get_vector(id),
+                source.get_vector(source_id)
+            );
+            assert_eq!(
+                borrowed_storage.is_deleted_vector(id),
+                source.is_deleted_vector(source_id),
+            );
+        }
+    }
+
+    // Incompatible sources are rejected before anything is copied
+    let other_dir = Builder::new().prefix("source_storage").tempdir().unwrap();
+    let other =
+        open_appendable_memmap_vector_storage(other_dir.path(), 4, Distance::Euclid).unwrap();
+    let other = other.borrow();
+    let result = borrowed_storage.merge_many(&[source_refs[0], &*other], &AtomicBool::new(false));
+    assert!(matches!(
+        result,
+        Err(OperationError::ValidationError { .. })
+    ));
+    assert_eq!(borrowed_storage.total_vector_count(), 36);
+}
+
+fn do_test_update_from_cancelled(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 5000;
+    let dir = Builder::new().prefix("db_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let other = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    for i in 0..num_vectors {
+        other
+            .borrow_mut()
+            .insert_vector(i, &[i as f32, 0.0, 1.0, 0.0])
+            .unwrap();
+    }
+
+    // Flip the flag in the middle of copying
+    let stopped = AtomicBool::new(false);
+    let mut other_ids = (0..num_vectors).inspect(|&id| {
+        if id == 1500 {
+            stopped.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+    });
+    let result = storage
+        .borrow_mut()
+        .update_from(&other.borrow(), &mut other_ids, &stopped);
+    assert!(matches!(result, Err(OperationError::Cancelled { .. })));
+
+    // Copying stops at the next check
+    assert_eq!(other_ids.next(), Some(2001));
+}
+
+fn do_test_bulk_scores_match_single(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let points = vec![
+        vec![1.0, 0.0, 1.0, 1.0],
+        vec![1.0, 0.0, 1.0, 0.0],
+        vec![1.0, 1.0, 1.0, 1.0],
+        vec![1.0, 1.0, 0.0, 1.0],
+        vec![1.0, 0.0, 0.0, 0.0],
+    ];
+    let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(points.len())));
+    let mut borrowed_storage = storage.borrow_mut();
+    let borrowed_id_tracker = id_tracker.borrow_mut();
+
+    for (i, vec) in points.iter().enumerate() {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, vec)
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(2).unwrap();
+
+    let config: QuantizationConfig = ScalarQuantizationConfig {
+        r#type: Default::default(),
+        quantile: None,
+        always_ram: None,
+    }
+    .into();
+    let dir = Builder::new()
+        .prefix("quantization_path")
+        .tempdir()
+        .unwrap();
+    let stopped = AtomicBool::new(false);
+    borrowed_storage
+        .quantize(dir.path(), &config, 1, &stopped)
+        .unwrap();
+
+    let query: QueryVector = vec![0.5, 0.5, 0.5, 0.5].into();
+    let scorer_orig = new_raw_scorer(
+        query.clone(),
+        &borrowed_storage,
+        borrowed_id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap();
+    let scorer_quant = borrowed_storage
+        .quantized_storage()
+        .unwrap()
+        .raw_scorer(
+            query,
+            borrowed_id_tracker.deleted_point_bitslice(),
+            borrowed_storage.deleted_vector_bitslice(),
+            &stopped,
+        )
+        .unwrap();
+
+    let query_points: Vec<PointOffsetType> = (0..points.len() as PointOffsetType).collect();
+    for scorer in [&scorer_orig, &scorer_quant] {
+        let mut scores = vec![ScoredPointOffset { idx: 0, score: 0. }; query_points.len()];
+        let count = scorer.score_points(&query_points, &mut scores);
+
+        // Deleted vector is skipped, the rest is scored exactly as one by one
+        assert_eq!(count, query_points.len() - 1);
+        assert!(scores[..count].iter().all(|scored| scored.idx != 2));
+        for scored in &scores[..count] {
+            assert_eq!(scored.score, scorer.score_point(scored.idx));
+        }
+    }
+}
+
+fn do_test_get_vector_opt(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let points = vec![
+        vec![1.0, 0.0, 1.0, 1.0],
+        vec![1.0, 0.0, 1.0, 0.0],
+        vec![1.0, 1.0, 1.0, 1.0],
+    ];
+    let dir = Builder::new().prefix("db_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let other = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    for (i, vec) in points.iter().enumerate() {
+        other
+            .borrow_mut()
+            .insert_vector(i as PointOffsetType, vec)
+            .unwrap();
+    }
+
+    let mut borrowed_storage = storage.borrow_mut();
+    borrowed_storage
+        .update_from(
+            &other.borrow(),
+            &mut (0..points.len() as PointOffsetType),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+    borrowed_storage.delete_vector(1).unwrap();
+
+    assert_eq!(
+        borrowed_storage.get_vector_opt(0).as_deref(),
+        Some(points[0].as_slice())
+    );
+    assert_eq!(borrowed_storage.get_vector_opt(1), None);
+    assert_eq!(
+        borrowed_storage.get_vector_opt(2).as_deref(),
+        Some(points[2].as_slice())
+    );
+    assert_eq!(borrowed_storage.get_vector_opt(3), None);
+    assert_eq!(borrowed_storage.get_vector_opt(PointOffsetType::MAX), None);
+}
+
+fn do_test_delete_vectors(storage: Arc<AtomicRefCell<VectorStorageEnum>>) {
+    let num_vectors = 6;
+    let dir = Builder::new().prefix("db_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let other = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    for i in 0..num_vectors {
+        other
+            .borrow_mut()
+            .insert_vector(i, &[i as f32, 0.0, 1.0, 1.0])
+            .unwrap();
+    }
+
+    let mut borrowed_storage = storage.borrow_mut();
+    borrowed_storage
+        .update_from(
+            &other.borrow(),
+            &mut (0..num_vectors),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+    borrowed_storage.delete_vector(1).unwrap();
+
+    // Already deleted, duplicate and out of range keys are not counted
+    let keys = [0, 1, 3, 3, 5, num_vectors, PointOffsetType::MAX];
+    assert_eq!(borrowed_storage.delete_vectors(&keys).unwrap(), 3);
+    assert_eq!(borrowed_storage.deleted_vector_count(), 4);
+    borrowed_storage.validate_counts().unwrap();
+    assert_eq!(borrowed_storage.deleted_vector_bitslice().count_ones(), 4);
+    for key in 0..num_vectors {
+        let expected = [0, 1, 3, 5].contains(&key);
+        assert_eq!(borrowed_storage.is_deleted_vector(key), expected);
+    }
+
+    assert_eq!(borrowed_storage.delete_vectors(&keys).unwrap(), 0);
+    assert_eq!(borrowed_storage.delete_vectors(&[]).unwrap(), 0);
+    assert_eq!(borrowed_storage.deleted_vector_count(), 4);
+}
+
+fn do_test_write_snapshot(
+    storage: Arc<AtomicRefCell<VectorStorageEnum>>,
+    restore_type: VectorStorageType,
+) {
+    let num_vectors = 100;
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+    insert_distributed_vectors(
+        &mut *borrowed_storage,
+        num_vectors,
+        &mut sampler(rng.clone()),
+    )
+    .unwrap();
+    delete_random_vectors(&mut rng, &mut *borrowed_storage, &mut id_tracker, 10).unwrap();
+
+    let mut snapshot = Vec::new();
+    borrowed_storage.write_snapshot(&mut snapshot).unwrap();
+
+    let mut config = VectorDataConfig {
+        size: borrowed_storage.vector_dim(),
+        distance: borrowed_storage.distance(),
+        storage_type: restore_type,
+        index: Indexes::Plain {},
+        quantization_config: None,
+        max_sparse_dimension: None,
+        datatype: Default::default(),
+        vector_cache_size: None,
+        strict_vector_checks: false,
+    };
+    let restore_dir = Builder::new().prefix("restored_storage").tempdir().unwrap();
+    let restored =
+        VectorStorageEnum::read_snapshot(&mut snapshot.as_slice(), restore_dir.path(), &config)
+            .unwrap();
+
+    assert_eq!(restored.total_vector_count(), num_vectors);
+    assert_eq!(
+        restored.deleted_vector_count(),
+        borrowed_storage.deleted_vector_count(),
+    );
+    for i in 0..num_vectors as PointOffsetType {
+        assert_eq!(restored.get_vector(i), borrowed_storage.get_vector(i));
+        assert_eq!(
+            restored.is_deleted_vector(i),
+            borrowed_storage.is_deleted_vector(i),
+        );
+    }
+
+    // Truncated frame
+    let restore_dir = Builder::new().prefix("restored_storage").tempdir().unwrap();
+    let truncated = &snapshot[..snapshot.len() - 1];
+    assert!(
+        VectorStorageEnum::read_snapshot(&mut &truncated[..], restore_dir.path(), &config).is_err()
+    );
+
+    // Dimension mismatch
+    config.size += 1;
+    let restore_dir = Builder::new().prefix("restored_storage").tempdir().unwrap();
+    let result =
+        VectorStorageEnum::read_snapshot(&mut snapshot.as_slice(), restore_dir.path(), &config);
+    assert!(matches!(result, Err(OperationError::WrongVector { .. })));
+}
+
+#[test]
+fn test_delete_points_in_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+
+    {
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        do_test_delete_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_update_from_delete_points_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        do_test_update_from_delete_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_update_from_cancelled_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_update_from_cancelled(storage);
+}
+
+#[test]
+fn test_score_points_in_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        do_test_score_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_score_quantized_points_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        test_score_quantized_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let _storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_bulk_scores_match_single_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_bulk_scores_match_single(storage);
+}
+
+#[test]
+fn test_get_vector_opt_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_get_vector_opt(storage);
+}
+
+#[test]
+fn test_delete_vectors_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_delete_vectors(storage);
+}
+
+#[test]
+fn test_encoded_query_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 16, Distance::Dot).unwrap();
+    let mut borrowed_storage = storage.borrow_mut();
+
+    let num_points = 300;
+    let mut rng = StdRng::seed_from_u64(42);
+    insert_distributed_vectors(&mut *borrowed_storage, num_points, &mut sampler(&mut rng)).unwrap();
+    let id_tracker = FixtureIdTracker::new(num_points);
+    let stopped = AtomicBool::new(false);
+
+    let query: Vec<_> = (0..16).map(|i| i as f32 / 16.0 - 0.3).collect();
+    let points: Vec<PointOffsetType> = (0..num_points as PointOffsetType).collect();
+
+    let configs: [QuantizationConfig; 2] = [
+        ScalarQuantizationConfig {
+            r#type: Default::default(),
+            quantile: None,
+            always_ram: None,
+        }
+        .into(),
+        ProductQuantizationConfig {
+            compression: CompressionRatio::X4,
+            always_ram: Some(true),
+        }
+        .into(),
+    ];
+    let mut previous_encoded = None;
+    for config in configs {
+        let quantization_dir = Builder::new()
+            .prefix("quantization_path")
+            .tempdir()
+            .unwrap();
+        borrowed_storage
+            .quantize(quantization_dir.path(), &config, 1, &stopped)
+            .unwrap();
+        let quantized = borrowed_storage.quantized_storage().unwrap();
+
+        let raw_scorer = quantized
+            .raw_scorer(
+                query.clone().into(),
+                id_tracker.deleted_point_bitslice(),
+                borrowed_storage.deleted_vector_bitslice(),
+                &stopped,
+            )
+            .unwrap();
+        let expected: Vec<_> = points
+            .iter()
+            .map(|&idx| raw_scorer.score_point(idx))
+            .collect();
+
+        // Same encoded query serves several scorers
+        let encoded = quantized.encode_query(&query);
+        for _ in 0..2 {
+            let scorer = quantized
+                .raw_scorer_encoded(
+                    &encoded,
+                    id_tracker.deleted_point_bitslice(),
+                    borrowed_storage.deleted_vector_bitslice(),
+                    &stopped,
+                )
+                .unwrap();
+            let scores: Vec<_> = points.iter().map(|&idx| scorer.score_point(idx)).collect();
+            assert_eq!(scores, expected);
+        }
+
+        // Query encoded for scalar quantization can't be used with product quantization
+        if let Some(previous_encoded) = &previous_encoded {
+            let result = quantized.raw_scorer_encoded(
+                previous_encoded,
+                id_tracker.deleted_point_bitslice(),
+                borrowed_storage.deleted_vector_bitslice(),
+                &stopped,
+            );
+            assert!(matches!(result, Err(OperationError::ServiceError { .. })));
+        }
+        previous_encoded = Some(encoded);
+    }
+}
+
+#[test]
+fn test_size_of_available_vectors_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let mut borrowed_storage = storage.borrow_mut();
+    assert_eq!(borrowed_storage.size_of_available_vectors_in_bytes(), 0);
+
+    for i in 0..5 {
+        borrowed_storage
+            .insert_vector(i, &[1.0, 0.0, 1.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(3).unwrap();
+
+    // 4 available vectors of dimension 4, f32 each
+    assert_eq!(borrowed_storage.size_of_available_vectors_in_bytes(), 64);
+}
+
+#[test]
+fn test_write_snapshot_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_write_snapshot(storage, VectorStorageType::Memory);
+}
+
+#[test]
+fn test_clear_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    {
+        let storage =
+            open_simple_vector_storage(db.clone(), DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        for i in 0..5 {
+            borrowed_storage
+                .insert_vector(i, &[i as f32, 0.0, 1.0, 1.0])
+                .unwrap();
+        }
+        borrowed_storage.delete_vector(3).unwrap();
+
+        borrowed_storage.clear().unwrap();
+        assert_eq!(borrowed_storage.total_vector_count(), 0);
+        assert_eq!(borrowed_storage.deleted_vector_count(), 0);
+        assert_eq!(borrowed_storage.deleted_vector_bitslice().count_ones(), 0);
+
+        let key = borrowed_storage
+            .append_vector(&[9.0, 0.0, 0.0, 1.0])
+            .unwrap();
+        assert_eq!(key, 0);
+        assert_eq!(borrowed_storage.total_vector_count(), 1);
+        assert!(!borrowed_storage.is_deleted_vector(0));
+        borrowed_storage.flusher()().unwrap();
+    }
+
+    // Cleared records are not loaded back
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let borrowed_storage = storage.borrow();
+    assert_eq!(borrowed_storage.total_vector_count(), 1);
+    assert_eq!(&*borrowed_storage.get_vector(0), &[9.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_append_vectors_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_append_vectors(storage);
+}
+
+#[test]
+fn test_insert_vectors_simple_vector_storages() {
+    let single_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let batch_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let single_db = open_db(single_dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let batch_db = open_db(batch_dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let single_storage =
+        open_simple_vector_storage(single_db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let batch_storage =
+        open_simple_vector_storage(batch_db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_insert_vectors(single_storage, batch_storage);
+}
+
+#[test]
+fn test_reserve_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_reserve(storage);
+}
+
+#[test]
+fn test_clone_range_to_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_clone_range_to(storage);
+}
+
+#[test]
+fn test_deleted_since_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_deleted_since(storage);
+}
+
+#[test]
+fn test_restore_checkpoint_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db.clone(), DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_restore_checkpoint(storage);
+
+    // Restored state is persisted
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let borrowed_storage = storage.borrow();
+    assert_eq!(borrowed_storage.total_vector_count(), 8);
+    assert_eq!(borrowed_storage.deleted_vector_count(), 1);
+    assert!(borrowed_storage.is_deleted_vector(1));
+    assert_eq!(&*borrowed_storage.get_vector(0), &[0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(&*borrowed_storage.get_vector(6), &[60.0, 1.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_count_scored_with_filter_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_count_scored_with_filter(storage);
+}
+
+#[test]
+fn test_filtered_raw_scorer_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_filtered_raw_scorer(storage);
+}
+
+#[test]
+fn test_par_iter_scores_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_par_iter_scores(storage);
+}
+
+#[test]
+fn test_for_each_vector_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_for_each_vector(storage);
+}
+
+#[test]
+fn test_iter_vectors_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_iter_vectors(storage);
+}
+
+#[test]
+fn test_raw_scorer_with_distance_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Cosine).unwrap();
+    let mut borrowed_storage = storage.borrow_mut();
+    let points = [
+        [1.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [1.0, 1.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0, 0.0],
+    ];
+    for (i, vec) in points.iter().enumerate() {
+        let preprocessed = Distance::Cosine.preprocess_vector(vec.to_vec());
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, preprocessed.as_slice())
+            .unwrap();
+    }
+    let id_tracker = FixtureIdTracker::new(points.len());
+
+    let query = [0.0, 3.0, 0.0, 4.0];
+    let query_norm = 5.0;
+    let expected_cosine = |point: &[f32; 4]| {
+        let dot: f32 = point.iter().zip(&query).map(|(a, b)| a * b).sum();
+        let norm = point.iter().map(|a| a * a).sum::<f32>().sqrt();
+        dot / (norm * query_norm)
+    };
+
+    let cosine_scores = new_raw_scorer_with_distance(
+        query.into(),
+        &borrowed_storage,
+        Distance::Cosine,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap()
+    .peek_top_all(points.len());
+    let dot_scores = new_raw_scorer_with_distance(
+        query.into(),
+        &borrowed_storage,
+        Distance::Dot,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap()
+    .peek_top_all(points.len());
+
+    assert_eq!(cosine_scores.len(), points.len());
+    for scored in &cosine_scores {
+        let expected = expected_cosine(&points[scored.idx as usize]);
+        assert!((scored.score - expected).abs() < 1e-5);
+    }
+
+    // Dot does not normalize the query, so scores are real cosine scaled by the query norm
+    assert_eq!(dot_scores.len(), cosine_scores.len());
+    for (dot, cosine) in dot_scores.iter().zip(&cosine_scores) {
+        assert_eq!(dot.idx, cosine.idx);
+        assert!((dot.score / query_norm - cosine.score).abs() < 1e-5);
+    }
+
+    let result = new_raw_scorer_with_distance(
+        query.into(),
+        &borrowed_storage,
+        Distance::Euclid,
+        id_tracker.deleted_point_bitslice(),
+    );
+    assert!(matches!(
+        result,
+        Err(OperationError::ValidationError { .. })
+    ));
+
+    // Raw vectors stored for Dot are not normalized, so they can't be scored as Cosine
+    let dot_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let dot_db = open_db(dot_dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let dot_storage = open_simple_vector_storage(dot_db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let borrowed_dot_storage = dot_storage.borrow();
+    let result = new_raw_scorer_with_distance(
+        query.into(),
+        &borrowed_dot_storage,
+        Distance::Cosine,
+        id_tracker.deleted_point_bitslice(),
+    );
+    assert!(matches!(
+        result,
+        Err(OperationError::ValidationError { .. })
+    ));
+}
+
+#[test]
+fn test_assert_contiguous_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let mut borrowed_storage = storage.borrow_mut();
+
+    for i in 0..3 {
+        borrowed_storage
+            .insert_vector(i, &[1.0, 0.0, 1.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(1).unwrap();
+    borrowed_storage.assert_contiguous().unwrap();
+
+    // Skip offsets 3 and 4
+    borrowed_storage
+        .insert_vector(5, &[1.0, 0.0, 1.0, 1.0])
+        .unwrap();
+    let error = borrowed_storage.assert_contiguous().unwrap_err();
+    assert!(matches!(error, OperationError::InconsistentStorage { .. }));
+    assert!(error.to_string().contains("offset 3"), "{error}");
+}
+
+#[test]
+fn test_score_and_collect_with_deadline_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let num_vectors = 20;
+    let id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, &[i as f32, 1.0, 0.0, 1.0])
+            .unwrap();
+    }
+    borrowed_storage.delete_vector(18).unwrap();
+
+    let query: QueryVector = vec![1.0, 0.0, 0.0, 0.0].into();
+    let raw_scorer = new_raw_scorer(
+        query,
+        &borrowed_storage,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap();
+    let collect = |top: usize, deadline: Option<Instant>| {
+        let mut points = (0..num_vectors as PointOffsetType).inspect(|&point_id| {
+            // Make the scan slow halfway
+            if point_id == 10 && deadline.is_some() {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        });
+        score_and_collect_with_deadline(
+            raw_scorer.as_ref(),
+            &mut points,
+            &EvenIdsFilter,
+            top,
+            deadline,
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|scored| scored.idx)
+        .collect::<Vec<_>>()
+    };
+
+    // Filtered, live, sorted descending and bounded by top
+    assert_eq!(collect(3, None), vec![16, 14, 12]);
+    assert_eq!(collect(100, None).len(), 9);
+
+    // Deadline passed while scanning, only points before it are considered
+    let deadline = Instant::now() + Duration::from_millis(50);
+    assert_eq!(collect(3, Some(deadline)), vec![8, 6, 4]);
+
+    let result = score_and_collect_with_deadline(
+        raw_scorer.as_ref(),
+        &mut (0..num_vectors as PointOffsetType),
+        &EvenIdsFilter,
+        3,
+        None,
+        &AtomicBool::new(true),
+    );
+    assert!(matches!(result, Err(OperationError::Cancelled { .. })));
+}
+
+#[test]
+fn test_hamming_binary_quantized_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 8, Distance::Hamming).unwrap();
+    let points = vec![
+        vec![1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+        vec![0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+        vec![1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0],
+        vec![1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0],
+    ];
+    let id_tracker = FixtureIdTracker::new(points.len());
+    let mut borrowed_storage = storage.borrow_mut();
+    for (i, vec) in points.iter().enumerate() {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, vec)
+            .unwrap();
+    }
+
+    let quantization_dir = Builder::new()
+        .prefix("quantization_path")
+        .tempdir()
+        .unwrap();
+    let stopped = AtomicBool::new(false);
+
+    // Other quantization kinds can't approximate Hamming distance
+    let scalar_config: QuantizationConfig = ScalarQuantizationConfig {
+        r#type: Default::default(),
+        quantile: None,
+        always_ram: None,
+    }
+    .into();
+    let result = borrowed_storage.quantize(quantization_dir.path(), &scalar_config, 1, &stopped);
+    assert!(matches!(
+        result,
+        Err(OperationError::ValidationError { .. })
+    ));
+
+    let config: QuantizationConfig = BinaryQuantizationConfig { always_ram: None }.into();
+    borrowed_storage
+        .quantize(quantization_dir.path(), &config, 1, &stopped)
+        .unwrap();
+
+    let query = vec![1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+    let quantized_scorer = borrowed_storage
+        .quantized_storage()
+        .unwrap()
+        .raw_scorer(
+            query.clone().into(),
+            id_tracker.deleted_point_bitslice(),
+            borrowed_storage.deleted_vector_bitslice(),
+            &stopped,
+        )
+        .unwrap();
+    let exact_scorer = new_raw_scorer(
+        query.clone().into(),
+        &borrowed_storage,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap();
+
+    // Binary encoding is exact for 0/1 vectors, scores are negated bit differences
+    for (i, expected) in [0.0, 8.0, 1.0, 3.0].into_iter().enumerate() {
+        let i = i as PointOffsetType;
+        assert_eq!(exact_scorer.score_point(i), -expected);
+        assert_eq!(quantized_scorer.score_point(i), -expected);
+        assert_eq!(Distance::Hamming.postprocess_score(-expected), expected);
+    }
+    assert_eq!(quantized_scorer.score_internal(1, 3), -5.0);
+
+    // Recommendation scores similarities to examples, which must be converted as well
+    let reco_query: QueryVector = RecoQuery::new(vec![query], vec![points[1].clone()]).into();
+    let quantized_scorer = borrowed_storage
+        .quantized_storage()
+        .unwrap()
+        .raw_scorer(
+            reco_query.clone(),
+            id_tracker.deleted_point_bitslice(),
+            borrowed_storage.deleted_vector_bitslice(),
+            &stopped,
+        )
+        .unwrap();
+    let exact_scorer = new_raw_scorer(
+        reco_query,
+        &borrowed_storage,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap();
+    for i in 0..points.len() as PointOffsetType {
+        assert_eq!(quantized_scorer.score_point(i), exact_scorer.score_point(i));
+    }
+    // Comparing two stored points falls back to plain Hamming similarity
+    assert_eq!(quantized_scorer.score_internal(1, 3), -5.0);
+    assert_eq!(exact_scorer.score_internal(1, 3), -5.0);
+}
+
+#[test]
+fn test_score_internal_query_scorers_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 3, Distance::Dot).unwrap();
+    let points = vec![
+        vec![1.0, 0.0, 2.0],
+        vec![0.5, 1.0, 0.0],
+        vec![0.0, 3.0, 1.0],
+    ];
+    let id_tracker = FixtureIdTracker::new(points.len());
+    let mut borrowed_storage = storage.borrow_mut();
+    for (i, vec) in points.iter().enumerate() {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, vec)
+            .unwrap();
+    }
+
+    let queries: [QueryVector; 2] = [
+        RecoQuery::new(vec![points[0].clone()], vec![points[1].clone()]).into(),
+        ContextQuery::new(vec![ContextPair::new(points[0].clone(), points[2].clone())]).into(),
+    ];
+    for query in queries {
+        let scorer = new_raw_scorer(
+            query,
+            &borrowed_storage,
+            id_tracker.deleted_point_bitslice(),
+        )
+        .unwrap();
+        // Query examples are not involved, stored points are compared by dot product
+        assert_eq!(scorer.score_internal(0, 1), 0.5);
+        assert_eq!(scorer.score_internal(1, 2), 3.0);
+    }
+}
+
+#[test]
+fn test_chebyshev_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 3, Distance::Chebyshev).unwrap();
+    let points = vec![
+        vec![1.0, 2.0, 3.0],
+        vec![4.0, 0.0, 3.0],
+        vec![-1.0, 2.5, 3.0],
+    ];
+    let id_tracker = FixtureIdTracker::new(points.len());
+    let mut borrowed_storage = storage.borrow_mut();
+    for (i, vec) in points.iter().enumerate() {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, vec)
+            .unwrap();
+    }
+
+    let scorer = new_raw_scorer(
+        vec![1.0, 1.0, 1.0].into(),
+        &borrowed_storage,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap();
+    for (i, expected) in [2.0, 3.0, 2.0].into_iter().enumerate() {
+        let i = i as PointOffsetType;
+        assert_eq!(scorer.score_point(i), -expected);
+        assert_eq!(Distance::Chebyshev.postprocess_score(-expected), expected);
+    }
+    assert_eq!(scorer.score_internal(0, 1), -3.0);
+    assert_eq!(scorer.score_internal(0, 2), -2.0);
+
+    // No quantization approximates Chebyshev distance yet
+    let quantization_dir = Builder::new()
+        .prefix("quantization_path")
+        .tempdir()
+        .unwrap();
+    let stopped = AtomicBool::new(false);
+    let configs: [QuantizationConfig; 2] = [
+        ScalarQuantizationConfig {
+            r#type: Default::default(),
+            quantile: None,
+            always_ram: None,
+        }
+        .into(),
+        BinaryQuantizationConfig { always_ram: None }.into(),
+    ];
+    for config in &configs {
+        let result = borrowed_storage.quantize(quantization_dir.path(), config, 1, &stopped);
+        let Err(OperationError::ValidationError { description }) = result else {
+            panic!("expected validation error");
+        };
+        assert_eq!(description, "unsupported quantization for Chebyshev");
+    }
+}
+
+#[test]
+fn test_rescore_quantized_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 32, Distance::Dot).unwrap();
+    let num_vectors = 500;
+    let top = 10;
+    let id_tracker = FixtureIdTracker::new(num_vectors);
+    let mut borrowed_storage = storage.borrow_mut();
+
+    let mut centered = sampler(StdRng::seed_from_u64(42)).map(|value| value - 0.5);
+    insert_distributed_vectors(&mut *borrowed_storage, num_vectors, &mut centered).unwrap();
+
+    let quantization_dir = Builder::new()
+        .prefix("quantization_path")
+        .tempdir()
+        .unwrap();
+    let config: QuantizationConfig = BinaryQuantizationConfig { always_ram: None }.into();
+    let stopped = AtomicBool::new(false);
+    borrowed_storage
+        .quantize(quantization_dir.path(), &config, 1, &stopped)
+        .unwrap();
+
+    let query: QueryVector = sampler(StdRng::seed_from_u64(43))
+        .map(|value| value - 0.5)
+        .take(32)
+        .collect::<Vec<_>>()
+        .into();
+    let quantized_scorer = borrowed_storage
+        .quantized_storage()
+        .unwrap()
+        .raw_scorer(
+            query.clone(),
+            id_tracker.deleted_point_bitslice(),
+            borrowed_storage.deleted_vector_bitslice(),
+            &stopped,
+        )
+        .unwrap();
+    let exact_scorer = new_raw_scorer(
+        query,
+        &borrowed_storage,
+        id_tracker.deleted_point_bitslice(),
+    )
+    .unwrap();
+
+    let exact = exact_scorer.peek_top_all(top);
+    let quantized = quantized_scorer.peek_top_all(top);
+    let rescored = rescore(
+        quantized_scorer.as_ref(),
+        exact_scorer.as_ref(),
+        &mut (0..num_vectors as PointOffsetType),
+        top,
+        4.0,
+    );
+    assert_eq!(rescored.len(), top);
+
+    // Rescored points carry exact scores in descending order
+    for pair in rescored.windows(2) {
+        assert!(pair[0].score >= pair[1].score);
+    }
+    for scored in &rescored {
+        assert_eq!(scored.score, exact_scorer.score_point(scored.idx));
+    }
+
+    let recall = |result: &[ScoredPointOffset]| {
+        result
+            .iter()
+            .filter(|scored| exact.iter().any(|expected| expected.idx == scored.idx))
+            .count()
+    };
+    assert!(
+        recall(&rescored) > recall(&quantized),
+        "rescoring must recover recall: {} vs {}",
+        recall(&rescored),
+        recall(&quantized),
+    );
+}
+
+#[test]
+fn test_snapshot_to_tar_simple_vector_storages_unsupported() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let mut archive = Vec::new();
+    assert!(storage.borrow().snapshot_to_tar(&mut archive).is_err());
+}
+
+#[test]
+fn test_merge_many_simple_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    do_test_merge_many(storage);
+}
+
+// ----------------------------------------------
+
+#[test]
+fn test_delete_points_in_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        do_test_delete_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_update_from_delete_points_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+
+        do_test_update_from_delete_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_update_from_cancelled_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_update_from_cancelled(storage);
+}
+
+#[test]
+fn test_update_from_cancelled_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_update_from_cancelled(storage);
+}
+
+#[test]
+fn test_score_points_in_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        do_test_score_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_score_quantized_points_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    {
+        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        test_score_quantized_points(storage.clone());
+        storage.borrow().flusher()().unwrap();
+    }
+
+    let _storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+}
+
+#[test]
+fn test_bulk_scores_match_single_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_bulk_scores_match_single(storage);
+}
+
+#[test]
+fn test_get_vector_opt_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_get_vector_opt(storage);
+}
+
+#[test]
+fn test_delete_vectors_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_delete_vectors(storage);
+}
+
+#[test]
+fn test_delete_vectors_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_delete_vectors(storage);
+}
+
+#[test]
+fn test_write_snapshot_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_write_snapshot(storage, VectorStorageType::ChunkedMmap);
+}
+
+#[test]
+fn test_clear_appendable_memmap_vector_storages_unsupported() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    let mut borrowed_storage = storage.borrow_mut();
+    borrowed_storage
+        .insert_vector(0, &[1.0, 0.0, 1.0, 1.0])
+        .unwrap();
+    assert!(borrowed_storage.clear().is_err());
+    assert_eq!(borrowed_storage.total_vector_count(), 1);
+}
+
+#[test]
+fn test_flush_with_progress_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let dim = 1024;
+    let num_vectors = 400;
+    {
+        let storage =
+            open_appendable_memmap_vector_storage(dir.path(), dim, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        let vector = vec![1.0; dim];
+        for i in 0..num_vectors {
+            borrowed_storage
+                .insert_vector(i as PointOffsetType, &vector)
+                .unwrap();
+        }
+
+        let reports = RefCell::new(Vec::new());
+        borrowed_storage
+            .flush_with_progress(|written, total| reports.borrow_mut().push((written, total)))
+            .unwrap();
+        let reports = reports.into_inner();
+
+        // Vectors span several chunks, so there must be intermediate reports
+        assert!(reports.len() > 2, "got reports: {reports:?}");
+        let total = reports[0].1;
+        assert!(total > 0);
+        assert!(reports.iter().all(|(_, t)| *t == total));
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(reports.last().unwrap().0, total);
+    }
+
+    let storage = open_appendable_memmap_vector_storage(dir.path(), dim, Distance::Dot).unwrap();
+    assert_eq!(storage.borrow().total_vector_count(), num_vectors);
+}
+
+#[test]
+fn test_flush_with_progress_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let db_dir = Builder::new().prefix("db_dir").tempdir().unwrap();
+    let db = open_db(db_dir.path(), &[DB_VECTOR_CF]).unwrap();
+    let other = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+    let num_vectors = 100;
+    for i in 0..num_vectors {
+        other
+            .borrow_mut()
+            .insert_vector(i, &[i as f32, 0.0, 1.0, 1.0])
+            .unwrap();
+    }
+
+    {
+        let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        borrowed_storage
+            .update_from(
+                &other.borrow(),
+                &mut (0..num_vectors),
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+        borrowed_storage.delete_vector(7).unwrap();
+
+        let reports = RefCell::new(Vec::new());
+        borrowed_storage
+            .flush_with_progress(|written, total| reports.borrow_mut().push((written, total)))
+            .unwrap();
+        let reports = reports.into_inner();
+
+        // Only deleted flags are flushed, so only their bytes are reported
+        assert_eq!(reports.len(), 2, "got reports: {reports:?}");
+        let total = reports[0].1;
+        assert_eq!(
+            total,
+            std::fs::metadata(dir.path().join("deleted.dat"))
+                .unwrap()
+                .len(),
+        );
+        assert!(reports.iter().all(|(_, t)| *t == total));
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(reports[0].0, 0);
+        assert_eq!(reports.last().unwrap().0, total);
+    }
+
+    let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    let borrowed_storage = storage.borrow();
+    assert_eq!(borrowed_storage.total_vector_count(), num_vectors as usize);
+    assert!(borrowed_storage.is_deleted_vector(7));
+    assert_eq!(borrowed_storage.deleted_vector_count(), 1);
+}
+
+#[test]
+fn test_progress_flusher_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let dim = 1024;
+    let num_vectors = 400;
+    {
+        let storage =
+            open_appendable_memmap_vector_storage(dir.path(), dim, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        let vector = vec![1.0; dim];
+        for i in 0..num_vectors {
+            borrowed_storage
+                .insert_vector(i as PointOffsetType, &vector)
+                .unwrap();
+        }
+
+        let flusher = borrowed_storage.progress_flusher();
+        drop(borrowed_storage);
+
+        let reports = RefCell::new(Vec::new());
+        flusher(&|progress| reports.borrow_mut().push(progress)).unwrap();
+        let reports = reports.into_inner();
+
+        // Vectors span several chunks, so there must be intermediate reports
+        assert!(reports.len() > 2, "got reports: {reports:?}");
+        assert_eq!(reports.first(), Some(&0.0));
+        assert_eq!(reports.last(), Some(&1.0));
+        assert!(reports.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    let storage = open_appendable_memmap_vector_storage(dir.path(), dim, Distance::Dot).unwrap();
+    assert_eq!(storage.borrow().total_vector_count(), num_vectors);
+}
+
+#[cfg(feature = "roaring")]
+#[test]
+fn test_live_ids_bitmap_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    let mut borrowed_storage = storage.borrow_mut();
+
+    let num_vectors = 100;
+    for i in 0..num_vectors {
+        borrowed_storage
+            .insert_vector(i as PointOffsetType, &[i as f32, 0.0, 1.0, 1.0])
+            .unwrap();
+    }
+    for i in (0..num_vectors).step_by(7) {
+        borrowed_storage
+            .delete_vector(i as PointOffsetType)
+            .unwrap();
+    }
+
+    let bitmap = borrowed_storage.live_ids_bitmap();
+    let live_ids: Vec<PointOffsetType> = (0..num_vectors as PointOffsetType)
+        .filter(|&id| !borrowed_storage.is_deleted_vector(id))
+        .collect();
+
+    assert_eq!(
+        bitmap.len() as usize,
+        num_vectors - borrowed_storage.deleted_vector_count()
+    );
+    assert_eq!(bitmap.iter().collect::<Vec<_>>(), live_ids);
+}
+
+#[test]
+fn test_append_vectors_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_append_vectors(storage);
+}
+
+#[test]
+fn test_insert_vectors_appendable_memmap_vector_storages() {
+    let single_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let batch_dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let single_storage =
+        open_appendable_memmap_vector_storage(single_dir.path(), 4, Distance::Dot).unwrap();
+    let batch_storage =
+        open_appendable_memmap_vector_storage(batch_dir.path(), 4, Distance::Dot).unwrap();
+    do_test_insert_vectors(single_storage, batch_storage);
+}
+
+#[test]
+fn test_reserve_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_reserve(storage);
+}
+
+#[test]
+fn test_clone_range_to_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_clone_range_to(storage);
+}
+
+#[test]
+fn test_restore_checkpoint_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_restore_checkpoint(storage);
+}
+
+#[test]
+fn test_deleted_since_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_deleted_since(storage);
+}
+
+#[test]
+fn test_try_from_files_infers_storage_kind() {
+    let points = vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 1.0, 0.0, 1.0]];
+    let simple_dir = Builder::new().prefix("simple_storage").tempdir().unwrap();
+    let memmap_dir = Builder::new().prefix("memmap_storage").tempdir().unwrap();
+    let appendable_dir = Builder::new()
+        .prefix("appendable_storage")
+        .tempdir()
+        .unwrap();
+
+    {
+        let db = open_db(simple_dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let simple = open_simple_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot).unwrap();
+        let appendable =
+            open_appendable_memmap_vector_storage(appendable_dir.path(), 4, Distance::Dot).unwrap();
+        for (i, vec) in points.iter().enumerate() {
+            simple
+                .borrow_mut()
+                .insert_vector(i as PointOffsetType, vec)
+                .unwrap();
+            appendable
+                .borrow_mut()
+                .insert_vector(i as PointOffsetType, vec)
+                .unwrap();
+        }
+
+        let memmap = open_memmap_vector_storage(memmap_dir.path(), 4, Distance::Dot).unwrap();
+        memmap
+            .borrow_mut()
+            .update_from(
+                &simple.borrow(),
+                &mut Box::new(0..points.len() as u32),
+                &Default::default(),
+            )
+            .unwrap();
+
+        simple.borrow().flusher()().unwrap();
+        memmap.borrow().flusher()().unwrap();
+        appendable.borrow().flusher()().unwrap();
+    }
+
+    // Storage type in config disagrees with the files on purpose, files must take precedence
+    let config = VectorDataConfig {
+        size: 4,
+        distance: Distance::Dot,
+        storage_type: VectorStorageType::ChunkedMmap,
+        index: Indexes::Plain {},
+        quantization_config: None,
+        max_sparse_dimension: None,
+        datatype: Default::default(),
+        vector_cache_size: None,
+        strict_vector_checks: false,
+    };
+
+    let simple = VectorStorageEnum::try_from_files(simple_dir.path(), &config).unwrap();
+    assert!(matches!(simple, VectorStorageEnum::Simple(_)));
+
+    let config = VectorDataConfig {
+        storage_type: VectorStorageType::Memory,
+        ..config
+    };
+
+    let memmap = VectorStorageEnum::try_from_files(memmap_dir.path(), &config).unwrap();
+    assert!(matches!(memmap, VectorStorageEnum::Memmap(_)));
+
+    let appendable = VectorStorageEnum::try_from_files(appendable_dir.path(), &config).unwrap();
+    assert!(matches!(appendable, VectorStorageEnum::AppendableMemmap(_)));
+
+    for storage in [simple, memmap, appendable] {
+        assert_eq!(storage.total_vector_count(), points.len());
+        for (i, vec) in points.iter().enumerate() {
+            assert_eq!(storage.get_vector(i as PointOffsetType), vec.as_slice());
+        }
+    }
+}
+
+#[test]
+fn test_count_scored_with_filter_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_count_scored_with_filter(storage);
+}
+
+#[test]
+fn test_filtered_raw_scorer_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_filtered_raw_scorer(storage);
+}
+
+#[test]
+fn test_par_iter_scores_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_par_iter_scores(storage);
+}
+
+#[test]
+fn test_for_each_vector_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_for_each_vector(storage);
+}
+
+#[test]
+fn test_iter_vectors_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_iter_vectors(storage);
+}
+
+#[test]
+fn test_assert_contiguous_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let far_offset = {
+        let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        borrowed_storage
+            .append_vector(&[1.0, 0.0, 1.0, 1.0])
+            .unwrap();
+        borrowed_storage.assert_contiguous().unwrap();
+
+        // Force a second chunk
+        let mut offset = 1;
+        while chunk_name(&dir.path().join("vectors"), 1)
+            .metadata()
+            .is_err()
+        {
+            offset *= 2;
+            borrowed_storage
+                .insert_vector(offset, &[1.0, 0.0, 1.0, 1.0])
+                .unwrap();
+        }
+        borrowed_storage.assert_contiguous().unwrap();
+        borrowed_storage.flusher()().unwrap();
+        offset
+    };
+
+    // Lose the last chunk, as if restored partially
+    std::fs::remove_file(chunk_name(&dir.path().join("vectors"), 1)).unwrap();
+
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    let borrowed_storage = storage.borrow();
+    assert_eq!(
+        borrowed_storage.total_vector_count(),
+        far_offset as usize + 1
+    );
+    let error = borrowed_storage.assert_contiguous().unwrap_err();
+    assert!(matches!(error, OperationError::InconsistentStorage { .. }));
+}
+
+#[test]
+fn test_snapshot_to_tar_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_snapshot_to_tar(storage);
+}
+
+#[test]
+fn test_merge_many_appendable_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_appendable_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_merge_many(storage);
+}
+
+#[test]
+fn test_merge_many_memmap_vector_storages() {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_memmap_vector_storage(dir.path(), 4, Distance::Dot).unwrap();
+    do_test_merge_many(storage);
 }
@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use common::types::PointOffsetType;
+
+use crate::data_types::vectors::VectorElementType;
+
+/// Capacity-bounded cache of dense vectors, evicting the least recently used entry first
+pub struct VectorCache {
+    capacity: usize,
+    /// Cached vector and the tick of its last access
+    entries: HashMap<PointOffsetType, (Arc<[VectorElementType]>, u64)>,
+    /// Keys by the tick of their last access, oldest first
+    access_order: BTreeMap<u64, PointOffsetType>,
+    tick: u64,
+}
+
+impl VectorCache {
+    /// Cache with zero `capacity` doesn't store anything
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            access_order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, key: PointOffsetType) -> bool {
+        self.entries.contains_key(&key)
+    }
+
+    /// Get cached vector for `key`, loading it with `load` on a miss
+    pub fn get_or_insert_with(
+        &mut self,
+        key: PointOffsetType,
+        load: impl FnOnce() -> Arc<[VectorElementType]>,
+    ) -> Arc<[VectorElementType]> {
+        self.tick += 1;
+        if let Some((vector, last_access)) = self.entries.get_mut(&key) {
+            self.access_order.remove(last_access);
+            self.access_order.insert(self.tick, key);
+            *last_access = self.tick;
+            return vector.clone();
+        }
+
+        let vector = load();
+        if self.capacity == 0 {
+            return vector;
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.access_order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (vector.clone(), self.tick));
+        self.access_order.insert(self.tick, key);
+        vector
+    }
+
+    /// Evict `key`, returns true if it was cached
+    pub fn remove(&mut self, key: PointOffsetType) -> bool {
+        match self.entries.remove(&key) {
+            Some((_, last_access)) => {
+                self.access_order.remove(&last_access);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_cache_eviction() {
+        let mut cache = VectorCache::new(2);
+        let vector = |value: VectorElementType| -> Arc<[VectorElementType]> { Arc::from([value]) };
+
+        assert_eq!(&*cache.get_or_insert_with(0, || vector(0.0)), &[0.0]);
+        assert_eq!(&*cache.get_or_insert_with(1, || vector(1.0)), &[1.0]);
+        // Hit doesn't call the loader, and makes 0 the most recently used entry
+        assert_eq!(&*cache.get_or_insert_with(0, || unreachable!()), &[0.0]);
+
+        cache.get_or_insert_with(2, || vector(2.0));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(0));
+        assert!(!cache.contains(1));
+        assert!(cache.contains(2));
+
+        assert!(cache.remove(0));
+        assert!(!cache.remove(0));
+        assert_eq!(cache.len(), 1);
+
+        let mut disabled = VectorCache::new(0);
+        disabled.get_or_insert_with(0, || vector(0.0));
+        assert!(disabled.is_empty());
+    }
+}
@@ -1,18 +1,47 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::mem::size_of;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Weak};
 
-use bitvec::prelude::BitSlice;
-use common::types::PointOffsetType;
+use atomic_refcell::AtomicRefCell;
+use bitvec::prelude::{BitSlice, BitVec};
+use common::types::{PointOffsetType, ScoredPointOffset};
+use memory::madvise::{self, Advice};
+use memory::mmap_ops::transmute_to_u8_slice;
+use parking_lot::Mutex;
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+#[cfg(feature = "roaring")]
+use roaring::RoaringBitmap;
 
-use super::memmap_vector_storage::MemmapVectorStorage;
+use super::div_ceil;
+use super::memmap_vector_storage::{
+    self, open_memmap_vector_storage_with_datatype, MemmapVectorStorage,
+};
 use super::quantized::quantized_vectors::QuantizedVectors;
-use super::simple_vector_storage::SimpleVectorStorage;
-use crate::common::operation_error::OperationResult;
-use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
-use crate::types::{Distance, QuantizationConfig};
-use crate::vector_storage::appendable_mmap_vector_storage::AppendableMmapVectorStorage;
+use super::raw_scorer::{new_raw_scorer, new_stoppable_raw_scorer};
+use super::simple_vector_storage::{open_simple_vector_storage, SimpleVectorStorage};
+use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::rocksdb_wrapper::{check_db_exists, open_db, DB_VECTOR_CF};
+use crate::common::{Flusher, ProgressFlusher};
+use crate::data_types::vectors::{CowVector, QueryVector, VectorElementType, VectorOrSparseRef};
+use crate::index::hnsw_index::max_rayon_threads;
+use crate::payload_storage::FilterContext;
+use crate::spaces::tools::peek_top_largest_iterable;
+use crate::types::{Distance, QuantizationConfig, VectorDataConfig, VectorStorageType};
+use crate::utils;
+use crate::vector_storage::appendable_mmap_vector_storage::{
+    self, open_appendable_memmap_vector_storage, open_appendable_memmap_vector_storage_impl,
+    AppendableMmapVectorStorage,
+};
+
+/// Magic bytes at the start of a [`VectorStorage::write_snapshot`] frame
+const SNAPSHOT_MAGIC: &[u8; 4] = b"QVSS";
+
+/// Version of the [`VectorStorage::write_snapshot`] frame layout
+const SNAPSHOT_VERSION: u32 = 1;
 
 /// Trait for vector storage
 /// El - type of vector element, expected numerical type
@@ -39,8 +68,30 @@ pub trait VectorStorage {
             .saturating_sub(self.deleted_vector_count())
     }
 
-    /// Number of all stored vectors including deleted
-    fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType];
+    /// Estimated size of available vectors in bytes
+    ///
+    /// By default it is computed from [`VectorStorage::available_vector_count`] and dimension.
+    /// Storages backed by files report the size of their [`VectorStorage::files`] instead.
+    fn size_of_available_vectors_in_bytes(&self) -> usize {
+        self.available_vector_count() * self.vector_dim() * size_of::<VectorElementType>()
+    }
+
+    /// Get vector by the given key
+    ///
+    /// The returned vector borrows the storage if it holds vectors as they are, for mmap storages
+    /// it then points directly into the mapped file. Storages with a different
+    /// [`VectorStorageDatatype`](crate::types::VectorStorageDatatype) decode the vector into an
+    /// owned one instead. Key must be in range of [`VectorStorage::total_vector_count`], otherwise
+    /// the call may panic. Deleted vectors are still returned, their data may be stale.
+    fn get_vector(&self, key: PointOffsetType) -> CowVector<'_>;
+
+    /// Get vector by the given key, if it is in range and not deleted
+    fn get_vector_opt(&self, key: PointOffsetType) -> Option<CowVector<'_>> {
+        if key as usize >= self.total_vector_count() || self.is_deleted_vector(key) {
+            return None;
+        }
+        Some(self.get_vector(key))
+    }
 
     fn insert_vector(
         &mut self,
@@ -48,6 +99,36 @@ pub trait VectorStorage {
         vector: &[VectorElementType],
     ) -> OperationResult<()>;
 
+    /// Hint that `additional` more vectors are about to be inserted
+    ///
+    /// Storages may allocate memory up front, to avoid growing repeatedly. No-op by default.
+    fn reserve(&mut self, _additional: usize) -> OperationResult<()> {
+        Ok(())
+    }
+
+    /// Insert `vectors` at consecutive offsets, starting from `start_key`
+    ///
+    /// Storages backed by growable files may override this to allocate capacity only once.
+    fn insert_vectors(
+        &mut self,
+        start_key: PointOffsetType,
+        vectors: &[VectorOrSparseRef],
+    ) -> OperationResult<()> {
+        for (key, &vector) in (start_key..).zip(vectors) {
+            self.insert_vector(key, vector.try_into()?)?;
+        }
+        Ok(())
+    }
+
+    /// Insert vector at the next free offset, right after all stored vectors
+    ///
+    /// Returns the assigned offset. Intended for appendable storages.
+    fn append_vector(&mut self, vector: &[VectorElementType]) -> OperationResult<PointOffsetType> {
+        let key = self.total_vector_count() as PointOffsetType;
+        self.insert_vector(key, vector)?;
+        Ok(key)
+    }
+
     fn update_from(
         &mut self,
         other: &VectorStorageEnum,
@@ -55,8 +136,106 @@ pub trait VectorStorage {
         stopped: &AtomicBool,
     ) -> OperationResult<Range<PointOffsetType>>;
 
+    /// Copy all vectors of all `sources` into this storage, in order
+    ///
+    /// All sources must match the dimension and distance of this storage, which is checked
+    /// before anything is copied. Space for all sources is reserved once up front.
+    /// Returns the new id range of each source.
+    fn merge_many(
+        &mut self,
+        sources: &[&VectorStorageEnum],
+        stopped: &AtomicBool,
+    ) -> OperationResult<Vec<Range<PointOffsetType>>> {
+        check_merge_sources(self.vector_dim(), self.distance(), sources)?;
+        let total: usize = sources
+            .iter()
+            .map(|source| source.total_vector_count())
+            .sum();
+        self.reserve(total)?;
+        sources
+            .iter()
+            .map(|source| {
+                let mut ids = 0..source.total_vector_count() as PointOffsetType;
+                self.update_from(source, &mut ids, stopped)
+            })
+            .collect()
+    }
+
     fn flusher(&self) -> Flusher;
 
+    /// Advise OS how the memory mapped vector data will be accessed
+    ///
+    /// No-op for storages which are not memory mapped.
+    fn mmap_advise(&self, _advice: Advice) -> OperationResult<()> {
+        Ok(())
+    }
+
+    /// Run a bulk read operation with sequential mmap advice, to maximize readahead
+    ///
+    /// Advice is restored to the global default once the operation returns.
+    fn mmap_advise_sequential<T>(&self, operation: impl FnOnce() -> T) -> OperationResult<T> {
+        self.mmap_advise(Advice::Sequential)?;
+        let result = operation();
+        self.mmap_advise(madvise::get_global())?;
+        Ok(result)
+    }
+
+    /// Copy vectors of the given offset range into a new appendable storage at `path`
+    ///
+    /// Deleted vectors are skipped, copied vectors get sequential offsets starting from 0.
+    /// Returns the new storage along with the mapping from old offsets to new ones.
+    fn clone_range_to(
+        &self,
+        range: Range<PointOffsetType>,
+        path: &Path,
+    ) -> OperationResult<(VectorStorageEnum, HashMap<PointOffsetType, PointOffsetType>)> {
+        let mut storage =
+            open_appendable_memmap_vector_storage_impl(path, self.vector_dim(), self.distance())?;
+        let mut id_remap = HashMap::new();
+        let end = range.end.min(self.total_vector_count() as PointOffsetType);
+        for old_id in range.start..end {
+            if self.is_deleted_vector(old_id) {
+                continue;
+            }
+            let new_id = storage.append_vector(&self.get_vector(old_id))?;
+            id_remap.insert(old_id, new_id);
+        }
+        Ok((
+            VectorStorageEnum::AppendableMemmap(Box::new(storage)),
+            id_remap,
+        ))
+    }
+
+    /// Deferred version of [`VectorStorage::flush_with_progress`], like [`VectorStorage::flusher`]
+    ///
+    /// Reported progress is monotonic, starting with 0.0 and ending with 1.0 on success.
+    /// By default progress is only reported before and after the whole flush.
+    fn progress_flusher(&self) -> ProgressFlusher {
+        let flusher = self.flusher();
+        Box::new(move |progress| {
+            progress(0.0);
+            flusher()?;
+            progress(1.0);
+            Ok(())
+        })
+    }
+
+    /// Flush storage to disk, reporting progress as `(bytes_written, total_bytes)`
+    ///
+    /// The callback is invoked with monotonically increasing `bytes_written`,
+    /// and the last call always has `bytes_written == total_bytes`.
+    fn flush_with_progress(&self, progress: impl Fn(u64, u64)) -> OperationResult<()> {
+        let total_bytes = self
+            .files()
+            .iter()
+            .map(|path| path.metadata().map(|meta| meta.len()).unwrap_or(0))
+            .sum();
+        progress(0, total_bytes);
+        (self.flusher())()?;
+        progress(total_bytes, total_bytes);
+        Ok(())
+    }
+
     // Generate quantized vectors and store them on disk
     fn quantize(
         &mut self,
@@ -78,6 +257,20 @@ pub trait VectorStorage {
     /// Returns true if the vector was not deleted before and is now deleted
     fn delete_vector(&mut self, key: PointOffsetType) -> OperationResult<bool>;
 
+    /// Flag all vectors by the given keys as deleted
+    ///
+    /// Returns the number of vectors that were not deleted before and are now deleted.
+    /// Out of range keys are skipped, same as in [`VectorStorage::delete_vector`].
+    fn delete_vectors(&mut self, keys: &[PointOffsetType]) -> OperationResult<usize> {
+        let mut deleted = 0;
+        for &key in keys {
+            if self.delete_vector(key)? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
     /// Check whether the vector at the given key is flagged as deleted
     fn is_deleted_vector(&self, key: PointOffsetType) -> bool;
 
@@ -97,11 +290,250 @@ pub trait VectorStorage {
     /// the deleted count with care.
     fn deleted_vector_count(&self) -> usize;
 
+    /// Recompute [`VectorStorage::deleted_vector_count`] from deletion flags
+    ///
+    /// Resets the cached count, if any, and returns the corrected value. Should be called by
+    /// recovery code after loading a segment.
+    fn recount_deleted_vectors(&mut self) -> usize {
+        self.deleted_vector_count()
+    }
+
+    /// Check that [`VectorStorage::deleted_vector_count`] matches deletion flags
+    ///
+    /// Only flags within [`VectorStorage::total_vector_count`] are counted. Counting is linear in
+    /// the number of vectors, so this is meant for tests and recovery code.
+    fn validate_counts(&self) -> OperationResult<()> {
+        let total_vector_count = self.total_vector_count();
+        let deleted = self.deleted_vector_bitslice();
+        let flagged_count = deleted[..total_vector_count.min(deleted.len())].count_ones();
+        let deleted_count = self.deleted_vector_count();
+        if deleted_count != flagged_count {
+            return Err(OperationError::InconsistentStorage {
+                description: format!(
+                    "Deleted vector count {deleted_count} does not match {flagged_count} deletion flags of {total_vector_count} vectors",
+                ),
+            });
+        }
+        Ok(())
+    }
+
     /// Get [`BitSlice`] representation for deleted vectors with deletion flags
     ///
     /// The size of this slice is not guaranteed. It may be smaller/larger than the number of
     /// vectors in this segment.
     fn deleted_vector_bitslice(&self) -> &BitSlice;
+
+    /// Call `f` for each vector which is not deleted, in order of offsets
+    ///
+    /// Stops at the first error returned by `f`, or when `stopped` is set.
+    fn for_each_vector(
+        &self,
+        mut f: impl FnMut(PointOffsetType, VectorOrSparseRef) -> OperationResult<()>,
+        stopped: &AtomicBool,
+    ) -> OperationResult<()> {
+        for key in 0..self.total_vector_count() as PointOffsetType {
+            check_process_stopped(stopped)?;
+            if self.is_deleted_vector(key) {
+                continue;
+            }
+            f(key, self.get_vector(key).as_ref().into())?;
+        }
+        Ok(())
+    }
+
+    /// Iterate over all vectors which are not deleted, in order of offsets
+    fn iter_vectors(&self) -> Box<dyn Iterator<Item = (PointOffsetType, CowVector)> + '_> {
+        Box::new(
+            (0..self.total_vector_count() as PointOffsetType)
+                .filter(|&key| !self.is_deleted_vector(key))
+                .map(|key| (key, self.get_vector(key))),
+        )
+    }
+
+    /// Check that all `total_vector_count` offsets are backed by stored data, without gaps
+    ///
+    /// Returns [`OperationError::InconsistentStorage`] describing the first gap otherwise.
+    /// Storages which can't have gaps by construction don't check anything.
+    fn assert_contiguous(&self) -> OperationResult<()> {
+        Ok(())
+    }
+
+    /// Record current length and deletion state, to roll back to it later
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.total_vector_count(), self.deleted_vector_bitslice())
+    }
+
+    /// Roll back to a previously recorded [`Checkpoint`]
+    ///
+    /// Vectors inserted after the checkpoint are dropped, vectors overwritten after it get their
+    /// previous data back and deletion flags are restored. Checkpoints taken after the restored
+    /// one can't be restored anymore.
+    ///
+    /// Only supported by appendable storages.
+    fn restore_checkpoint(&mut self, _checkpoint: Checkpoint) -> OperationResult<()> {
+        Err(OperationError::service_error(
+            "Restoring checkpoint is not supported by this vector storage",
+        ))
+    }
+
+    /// Remove all vectors and deletion flags, next inserted vector starts at offset 0
+    ///
+    /// Quantized data is dropped as well. Only supported by in-memory storage.
+    fn clear(&mut self) -> OperationResult<()> {
+        Err(OperationError::service_error(
+            "Clearing is not supported by this vector storage",
+        ))
+    }
+
+    /// Stream all storage [`files`](VectorStorage::files) into a single tar archive
+    ///
+    /// Files are stored relative to their common parent directory, so the archive can be unpacked
+    /// with [`VectorStorageEnum::restore_from_tar`]. Storage should be flushed beforehand.
+    fn snapshot_to_tar(&self, writer: &mut dyn Write) -> OperationResult<()> {
+        let files = self.files();
+        let base = common_parent(&files).ok_or_else(|| {
+            OperationError::service_error("Vector storage has no files to snapshot")
+        })?;
+        let mut builder = tar::Builder::new(writer);
+        for file in &files {
+            utils::tar::append_file_relative_to_base(&mut builder, &base, file, Path::new(""))?;
+        }
+        builder.finish()?;
+        Ok(())
+    }
+
+    /// Serialize vectors and deletion flags into a single versioned frame
+    ///
+    /// Frame layout: magic bytes, version, total vector count and dimension, deletion flags packed
+    /// into bytes, then raw vector elements in native byte order. Unlike [`VectorStorage::files`],
+    /// doesn't need access to the storage files. Restore with [`VectorStorageEnum::read_snapshot`].
+    fn write_snapshot(&self, writer: &mut dyn Write) -> OperationResult<()> {
+        let total_vector_count = self.total_vector_count();
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        writer.write_all(&(total_vector_count as u64).to_le_bytes())?;
+        writer.write_all(&(self.vector_dim() as u64).to_le_bytes())?;
+
+        let mut deleted = vec![0u8; div_ceil(total_vector_count, u8::BITS as usize)];
+        for id in self
+            .deleted_vector_bitslice()
+            .iter_ones()
+            .take_while(|&id| id < total_vector_count)
+        {
+            deleted[id / u8::BITS as usize] |= 1 << (id % u8::BITS as usize);
+        }
+        writer.write_all(&deleted)?;
+
+        // Mmap storages hand out vectors straight from their maps
+        for key in 0..total_vector_count as PointOffsetType {
+            writer.write_all(transmute_to_u8_slice(&self.get_vector(key)))?;
+        }
+        Ok(())
+    }
+
+    /// Get ids of vectors deleted since the `prev` snapshot of [`VectorStorage::deleted_vector_bitslice`]
+    ///
+    /// Ids beyond the length of `prev` are considered not deleted in the snapshot.
+    fn deleted_since(&self, prev: &BitSlice) -> Vec<PointOffsetType> {
+        let total_vector_count = self.total_vector_count();
+        self.deleted_vector_bitslice()
+            .iter_ones()
+            .take_while(|&id| id < total_vector_count)
+            .filter(|&id| !prev.get(id).map(|bit| *bit).unwrap_or(false))
+            .map(|id| id as PointOffsetType)
+            .collect()
+    }
+
+    /// Get ids of all vectors which are not deleted as a compressed bitmap
+    ///
+    /// Compact for sparse deletion patterns, and cheap to intersect with filter results.
+    #[cfg(feature = "roaring")]
+    fn live_ids_bitmap(&self) -> RoaringBitmap {
+        let total_vector_count = self.total_vector_count();
+        let mut bitmap = RoaringBitmap::new();
+        bitmap.insert_range(0..total_vector_count as PointOffsetType);
+        self.deleted_vector_bitslice()
+            .iter_ones()
+            .take_while(|&id| id < total_vector_count)
+            .for_each(|id| {
+                bitmap.remove(id as PointOffsetType);
+            });
+        bitmap
+    }
+}
+
+/// Previous data of vectors overwritten after a [`Checkpoint`] was taken, by offset
+pub type OverwrittenVectors = HashMap<PointOffsetType, Vec<VectorElementType>>;
+
+/// Length and deletion state of a vector storage at some point in time
+///
+/// See [`VectorStorage::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub total_vector_count: usize,
+    pub deleted: BitVec,
+    /// Filled by the storage while the checkpoint is alive, for vectors below
+    /// `total_vector_count` which are overwritten
+    pub overwritten: Arc<Mutex<OverwrittenVectors>>,
+}
+
+impl Checkpoint {
+    pub fn new(total_vector_count: usize, deleted: &BitSlice) -> Self {
+        Self {
+            total_vector_count,
+            deleted: deleted[..total_vector_count.min(deleted.len())].to_bitvec(),
+            overwritten: Default::default(),
+        }
+    }
+}
+
+/// Checkpoints taken from a storage which supports restoring them, oldest first
+///
+/// Only weak references are kept, dropped checkpoints stop recording overwrites.
+#[derive(Default)]
+pub(crate) struct CheckpointJournals {
+    journals: Mutex<Vec<(usize, Weak<Mutex<OverwrittenVectors>>)>>,
+}
+
+impl CheckpointJournals {
+    pub fn checkpoint(&self, total_vector_count: usize, deleted: &BitSlice) -> Checkpoint {
+        let checkpoint = Checkpoint::new(total_vector_count, deleted);
+        self.journals.lock().push((
+            checkpoint.total_vector_count,
+            Arc::downgrade(&checkpoint.overwritten),
+        ));
+        checkpoint
+    }
+
+    /// Remember `current` data of `key` before it is overwritten, for checkpoints covering it
+    pub fn record_overwrite(&mut self, key: PointOffsetType, current: &[VectorElementType]) {
+        let journals = self.journals.get_mut();
+        journals.retain(|(_, journal)| journal.strong_count() > 0);
+        for (total_vector_count, journal) in journals.iter() {
+            if key as usize >= *total_vector_count {
+                continue;
+            }
+            if let Some(journal) = journal.upgrade() {
+                journal
+                    .lock()
+                    .entry(key)
+                    .or_insert_with(|| current.to_vec());
+            }
+        }
+    }
+
+    /// Take vectors to put back for restoring `checkpoint`, forgetting checkpoints taken after it
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> OperationResult<OverwrittenVectors> {
+        let journals = self.journals.get_mut();
+        let journal = Arc::downgrade(&checkpoint.overwritten);
+        let Some(position) = journals.iter().position(|(_, j)| j.ptr_eq(&journal)) else {
+            return Err(OperationError::service_error(
+                "Can't restore checkpoint not taken from this storage, or taken after a restored one",
+            ));
+        };
+        journals.truncate(position + 1);
+        Ok(std::mem::take(&mut *checkpoint.overwritten.lock()))
+    }
 }
 
 pub enum VectorStorageEnum {
@@ -110,6 +542,198 @@ pub enum VectorStorageEnum {
     AppendableMemmap(Box<AppendableMmapVectorStorage>),
 }
 
+impl VectorStorageEnum {
+    /// Open an existing vector storage in `dir`, inferring its kind from the files on disk
+    ///
+    /// Falls back to `config.storage_type` if there is no known storage in the directory yet.
+    /// In-memory storage is loaded from a RocksDB database in `dir`, using the default vector column.
+    pub fn try_from_files(dir: &Path, config: &VectorDataConfig) -> OperationResult<Self> {
+        let storage_type = if dir
+            .join(appendable_mmap_vector_storage::VECTORS_DIR_PATH)
+            .is_dir()
+        {
+            VectorStorageType::ChunkedMmap
+        } else if dir.join(memmap_vector_storage::VECTORS_PATH).is_file() {
+            VectorStorageType::Mmap
+        } else if check_db_exists(dir) {
+            VectorStorageType::Memory
+        } else {
+            config.storage_type
+        };
+
+        let storage = match storage_type {
+            VectorStorageType::Memory => {
+                let database = open_db(dir, &[DB_VECTOR_CF]).map_err(|err| {
+                    OperationError::service_error(format!("RocksDB open error: {err}"))
+                })?;
+                open_simple_vector_storage(database, DB_VECTOR_CF, config.size, config.distance)?
+            }
+            VectorStorageType::Mmap => open_memmap_vector_storage_with_datatype(
+                dir,
+                config.size,
+                config.distance,
+                config.datatype,
+            )?,
+            VectorStorageType::ChunkedMmap => {
+                open_appendable_memmap_vector_storage(dir, config.size, config.distance)?
+            }
+        };
+
+        Arc::try_unwrap(storage)
+            .map(AtomicRefCell::into_inner)
+            .map_err(|_| OperationError::service_error("opened vector storage is already shared"))
+    }
+
+    /// Unpack an archive made by [`VectorStorage::snapshot_to_tar`] into `dir` and open it
+    pub fn restore_from_tar(
+        reader: &mut dyn Read,
+        dir: &Path,
+        config: &VectorDataConfig,
+    ) -> OperationResult<Self> {
+        tar::Archive::new(reader).unpack(dir).map_err(|err| {
+            OperationError::service_error(format!(
+                "failed to unpack vector storage archive into {dir:?}: {err}"
+            ))
+        })?;
+        Self::try_from_files(dir, config)
+    }
+
+    /// Load a frame made by [`VectorStorage::write_snapshot`] into a new storage in `dir`
+    ///
+    /// Storage kind is taken from `config`, `dir` must not contain a storage yet. Immutable
+    /// memmap storage can't be filled this way, load into a chunked mmap storage instead.
+    pub fn read_snapshot(
+        reader: &mut dyn Read,
+        dir: &Path,
+        config: &VectorDataConfig,
+    ) -> OperationResult<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(OperationError::service_error(
+                "Vector storage snapshot has wrong magic bytes",
+            ));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != SNAPSHOT_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported vector storage snapshot version {version}, expected {SNAPSHOT_VERSION}",
+            )));
+        }
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let total_vector_count = u64::from_le_bytes(header) as usize;
+        reader.read_exact(&mut header)?;
+        let dim = u64::from_le_bytes(header) as usize;
+        if dim != config.size {
+            return Err(OperationError::WrongVector {
+                expected_dim: config.size,
+                received_dim: dim,
+            });
+        }
+
+        let mut deleted = vec![0u8; div_ceil(total_vector_count, u8::BITS as usize)];
+        reader.read_exact(&mut deleted)?;
+
+        let mut storage = Self::try_from_files(dir, config)?;
+        if matches!(storage, VectorStorageEnum::Memmap(_)) {
+            return Err(OperationError::service_error(
+                "Can't load vector storage snapshot into immutable memmap storage",
+            ));
+        }
+        if storage.total_vector_count() != 0 {
+            return Err(OperationError::service_error(format!(
+                "Can't load vector storage snapshot into non-empty storage in {dir:?}",
+            )));
+        }
+        storage.reserve(total_vector_count)?;
+        let element_size = size_of::<VectorElementType>();
+        let mut bytes = vec![0u8; dim * element_size];
+        let mut vector: Vec<VectorElementType> = Vec::with_capacity(dim);
+        for key in 0..total_vector_count as PointOffsetType {
+            reader.read_exact(&mut bytes)?;
+            vector.clear();
+            vector.extend(
+                bytes
+                    .chunks_exact(element_size)
+                    .map(|chunk| VectorElementType::from_ne_bytes(chunk.try_into().unwrap())),
+            );
+            storage.insert_vector(key, &vector)?;
+        }
+        for id in 0..total_vector_count {
+            if deleted[id / u8::BITS as usize] & (1 << (id % u8::BITS as usize)) != 0 {
+                storage.delete_vector(id as PointOffsetType)?;
+            }
+        }
+        storage.flusher()()?;
+        Ok(storage)
+    }
+
+    /// Score only points accepted by `filter`, returning top `top` of them
+    ///
+    /// Also returns the total number of scored points, which excludes deleted and filtered out ones.
+    pub fn count_scored_with_filter(
+        &self,
+        query: QueryVector,
+        filter: &dyn FilterContext,
+        point_deleted: &BitSlice,
+        top: usize,
+    ) -> OperationResult<(usize, Vec<ScoredPointOffset>)> {
+        let raw_scorer = new_raw_scorer(query, self, point_deleted)?;
+        let mut scored_count = 0;
+        let mut points = (0..self.total_vector_count() as PointOffsetType)
+            .filter(|&point_id| filter.check(point_id) && raw_scorer.check_vector(point_id))
+            .inspect(|_| scored_count += 1);
+        let scored = raw_scorer.peek_top_iter(&mut points, top);
+        Ok((scored_count, scored))
+    }
+
+    /// Brute-force search of top `top` points, splitting the id range across rayon threads
+    ///
+    /// Each thread keeps its own top, which are merged at the end. Number of threads follows
+    /// the same rules as `max_indexing_threads`, `0` means auto selection.
+    pub fn par_iter_scores(
+        &self,
+        query: QueryVector,
+        point_deleted: &BitSlice,
+        top: usize,
+        max_threads: usize,
+        is_stopped: &AtomicBool,
+    ) -> OperationResult<Vec<ScoredPointOffset>> {
+        let num_threads = max_rayon_threads(max_threads);
+        let total_vector_count = self.total_vector_count();
+        let chunk_size = div_ceil(total_vector_count, num_threads).max(1);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .thread_name(|idx| format!("brute-force-search-{idx}"))
+            .num_threads(num_threads)
+            .build()?;
+
+        let chunk_tops = pool.install(|| {
+            (0..total_vector_count)
+                .step_by(chunk_size)
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .map(|start| {
+                    let end = (start + chunk_size).min(total_vector_count);
+                    let raw_scorer =
+                        new_stoppable_raw_scorer(query.clone(), self, point_deleted, is_stopped)?;
+                    let mut points = start as PointOffsetType..end as PointOffsetType;
+                    Ok(raw_scorer.peek_top_iter(&mut points, top))
+                })
+                .collect::<OperationResult<Vec<_>>>()
+        })?;
+        check_process_stopped(is_stopped)?;
+
+        Ok(peek_top_largest_iterable(
+            chunk_tops.into_iter().flatten(),
+            top,
+        ))
+    }
+}
+
 impl VectorStorage for VectorStorageEnum {
     fn vector_dim(&self) -> usize {
         match self {
@@ -135,7 +759,15 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
-    fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
+    fn size_of_available_vectors_in_bytes(&self) -> usize {
+        match self {
+            VectorStorageEnum::Simple(v) => v.size_of_available_vectors_in_bytes(),
+            VectorStorageEnum::Memmap(v) => v.size_of_available_vectors_in_bytes(),
+            VectorStorageEnum::AppendableMemmap(v) => v.size_of_available_vectors_in_bytes(),
+        }
+    }
+
+    fn get_vector(&self, key: PointOffsetType) -> CowVector<'_> {
         match self {
             VectorStorageEnum::Simple(v) => v.get_vector(key),
             VectorStorageEnum::Memmap(v) => v.get_vector(key),
@@ -143,6 +775,14 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
+    fn get_vector_opt(&self, key: PointOffsetType) -> Option<CowVector<'_>> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.get_vector_opt(key),
+            VectorStorageEnum::Memmap(v) => v.get_vector_opt(key),
+            VectorStorageEnum::AppendableMemmap(v) => v.get_vector_opt(key),
+        }
+    }
+
     fn insert_vector(
         &mut self,
         key: PointOffsetType,
@@ -155,6 +795,26 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
+    fn reserve(&mut self, additional: usize) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.reserve(additional),
+            VectorStorageEnum::Memmap(v) => v.reserve(additional),
+            VectorStorageEnum::AppendableMemmap(v) => v.reserve(additional),
+        }
+    }
+
+    fn insert_vectors(
+        &mut self,
+        start_key: PointOffsetType,
+        vectors: &[VectorOrSparseRef],
+    ) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.insert_vectors(start_key, vectors),
+            VectorStorageEnum::Memmap(v) => v.insert_vectors(start_key, vectors),
+            VectorStorageEnum::AppendableMemmap(v) => v.insert_vectors(start_key, vectors),
+        }
+    }
+
     fn update_from(
         &mut self,
         other: &VectorStorageEnum,
@@ -168,6 +828,18 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
+    fn merge_many(
+        &mut self,
+        sources: &[&VectorStorageEnum],
+        stopped: &AtomicBool,
+    ) -> OperationResult<Vec<Range<PointOffsetType>>> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.merge_many(sources, stopped),
+            VectorStorageEnum::Memmap(v) => v.merge_many(sources, stopped),
+            VectorStorageEnum::AppendableMemmap(v) => v.merge_many(sources, stopped),
+        }
+    }
+
     fn flusher(&self) -> Flusher {
         match self {
             VectorStorageEnum::Simple(v) => v.flusher(),
@@ -176,6 +848,30 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
+    fn mmap_advise(&self, advice: Advice) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.mmap_advise(advice),
+            VectorStorageEnum::Memmap(v) => v.mmap_advise(advice),
+            VectorStorageEnum::AppendableMemmap(v) => v.mmap_advise(advice),
+        }
+    }
+
+    fn progress_flusher(&self) -> ProgressFlusher {
+        match self {
+            VectorStorageEnum::Simple(v) => v.progress_flusher(),
+            VectorStorageEnum::Memmap(v) => v.progress_flusher(),
+            VectorStorageEnum::AppendableMemmap(v) => v.progress_flusher(),
+        }
+    }
+
+    fn flush_with_progress(&self, progress: impl Fn(u64, u64)) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.flush_with_progress(progress),
+            VectorStorageEnum::Memmap(v) => v.flush_with_progress(progress),
+            VectorStorageEnum::AppendableMemmap(v) => v.flush_with_progress(progress),
+        }
+    }
+
     fn quantize(
         &mut self,
         data_path: &Path,
@@ -228,6 +924,14 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
+    fn delete_vectors(&mut self, keys: &[PointOffsetType]) -> OperationResult<usize> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.delete_vectors(keys),
+            VectorStorageEnum::Memmap(v) => v.delete_vectors(keys),
+            VectorStorageEnum::AppendableMemmap(v) => v.delete_vectors(keys),
+        }
+    }
+
     fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
         match self {
             VectorStorageEnum::Simple(v) => v.is_deleted_vector(key),
@@ -244,6 +948,22 @@ impl VectorStorage for VectorStorageEnum {
         }
     }
 
+    fn recount_deleted_vectors(&mut self) -> usize {
+        match self {
+            VectorStorageEnum::Simple(v) => v.recount_deleted_vectors(),
+            VectorStorageEnum::Memmap(v) => v.recount_deleted_vectors(),
+            VectorStorageEnum::AppendableMemmap(v) => v.recount_deleted_vectors(),
+        }
+    }
+
+    fn validate_counts(&self) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.validate_counts(),
+            VectorStorageEnum::Memmap(v) => v.validate_counts(),
+            VectorStorageEnum::AppendableMemmap(v) => v.validate_counts(),
+        }
+    }
+
     fn deleted_vector_bitslice(&self) -> &BitSlice {
         match self {
             VectorStorageEnum::Simple(v) => v.deleted_vector_bitslice(),
@@ -251,4 +971,98 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::AppendableMemmap(v) => v.deleted_vector_bitslice(),
         }
     }
+
+    fn iter_vectors(&self) -> Box<dyn Iterator<Item = (PointOffsetType, CowVector)> + '_> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.iter_vectors(),
+            VectorStorageEnum::Memmap(v) => v.iter_vectors(),
+            VectorStorageEnum::AppendableMemmap(v) => v.iter_vectors(),
+        }
+    }
+
+    fn assert_contiguous(&self) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.assert_contiguous(),
+            VectorStorageEnum::Memmap(v) => v.assert_contiguous(),
+            VectorStorageEnum::AppendableMemmap(v) => v.assert_contiguous(),
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        match self {
+            VectorStorageEnum::Simple(v) => v.checkpoint(),
+            VectorStorageEnum::Memmap(v) => v.checkpoint(),
+            VectorStorageEnum::AppendableMemmap(v) => v.checkpoint(),
+        }
+    }
+
+    fn restore_checkpoint(&mut self, checkpoint: Checkpoint) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.restore_checkpoint(checkpoint),
+            VectorStorageEnum::Memmap(v) => v.restore_checkpoint(checkpoint),
+            VectorStorageEnum::AppendableMemmap(v) => v.restore_checkpoint(checkpoint),
+        }
+    }
+
+    fn clear(&mut self) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.clear(),
+            VectorStorageEnum::Memmap(v) => v.clear(),
+            VectorStorageEnum::AppendableMemmap(v) => v.clear(),
+        }
+    }
+
+    fn snapshot_to_tar(&self, writer: &mut dyn Write) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.snapshot_to_tar(writer),
+            VectorStorageEnum::Memmap(v) => v.snapshot_to_tar(writer),
+            VectorStorageEnum::AppendableMemmap(v) => v.snapshot_to_tar(writer),
+        }
+    }
+
+    fn write_snapshot(&self, writer: &mut dyn Write) -> OperationResult<()> {
+        match self {
+            VectorStorageEnum::Simple(v) => v.write_snapshot(writer),
+            VectorStorageEnum::Memmap(v) => v.write_snapshot(writer),
+            VectorStorageEnum::AppendableMemmap(v) => v.write_snapshot(writer),
+        }
+    }
+}
+
+/// Check that all `sources` can be merged into a storage with `dim` and `distance`
+pub(super) fn check_merge_sources(
+    dim: usize,
+    distance: Distance,
+    sources: &[&VectorStorageEnum],
+) -> OperationResult<()> {
+    for (i, source) in sources.iter().enumerate() {
+        if source.vector_dim() != dim {
+            return Err(OperationError::WrongVector {
+                expected_dim: dim,
+                received_dim: source.vector_dim(),
+            });
+        }
+        if source.distance() != distance {
+            return Err(OperationError::ValidationError {
+                description: format!(
+                    "Cannot merge source {i} with {:?} distance into storage with {distance:?} distance",
+                    source.distance(),
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Deepest directory containing all `files`
+fn common_parent(files: &[PathBuf]) -> Option<PathBuf> {
+    let mut base = files.first()?.parent()?.to_path_buf();
+    for file in files {
+        while !file.starts_with(&base) {
+            if !base.pop() {
+                return None;
+            }
+        }
+    }
+    Some(base)
 }
@@ -38,6 +38,10 @@ fn test_batch_and_single_request_equivalency() {
                 storage_type: VectorStorageType::Memory,
                 index: Indexes::Plain {},
                 quantization_config: None,
+                max_sparse_dimension: None,
+                datatype: Default::default(),
+                vector_cache_size: None,
+                strict_vector_checks: false,
             },
         )]),
         payload_storage_type: Default::default(),
@@ -162,19 +166,23 @@ fn test_batch_and_single_request_equivalency() {
             payload_value.into(),
         )));
 
-        let search_res_1 =
-            hnsw_index.search(&[&query_vector_1], Some(&filter), 10, None, &false.into());
+        let search_res_1 = hnsw_index
+            .search(&[&query_vector_1], Some(&filter), 10, None, &false.into())
+            .unwrap();
 
-        let search_res_2 =
-            hnsw_index.search(&[&query_vector_2], Some(&filter), 10, None, &false.into());
+        let search_res_2 = hnsw_index
+            .search(&[&query_vector_2], Some(&filter), 10, None, &false.into())
+            .unwrap();
 
-        let batch_res = hnsw_index.search(
-            &[&query_vector_1, &query_vector_2],
-            Some(&filter),
-            10,
-            None,
-            &false.into(),
-        );
+        let batch_res = hnsw_index
+            .search(
+                &[&query_vector_1, &query_vector_2],
+                Some(&filter),
+                10,
+                None,
+                &false.into(),
+            )
+            .unwrap();
 
         assert_eq!(search_res_1[0], batch_res[0]);
         assert_eq!(search_res_2[0], batch_res[1]);
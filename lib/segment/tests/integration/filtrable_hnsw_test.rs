@@ -47,6 +47,10 @@ fn test_filterable_hnsw() {
                 storage_type: VectorStorageType::Memory,
                 index: Indexes::Plain {},
                 quantization_config: None,
+                max_sparse_dimension: None,
+                datatype: Default::default(),
+                vector_cache_size: None,
+                strict_vector_checks: false,
             },
         )]),
         payload_storage_type: Default::default(),
@@ -157,16 +161,18 @@ fn test_filterable_hnsw() {
         let filter_query = Some(&filter);
         // let filter_query = None;
 
-        let index_result = hnsw_index.search(
-            &[&query],
-            filter_query,
-            top,
-            Some(&SearchParams {
-                hnsw_ef: Some(ef),
-                ..Default::default()
-            }),
-            &false.into(),
-        );
+        let index_result = hnsw_index
+            .search(
+                &[&query],
+                filter_query,
+                top,
+                Some(&SearchParams {
+                    hnsw_ef: Some(ef),
+                    ..Default::default()
+                }),
+                &false.into(),
+            )
+            .unwrap();
 
         // check that search was performed using HNSW index
         assert_eq!(
@@ -180,7 +186,8 @@ fn test_filterable_hnsw() {
         let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
             .vector_index
             .borrow()
-            .search(&[&query], filter_query, top, None, &false.into());
+            .search(&[&query], filter_query, top, None, &false.into())
+            .unwrap();
 
         if plain_result == index_result {
             hits += 1;
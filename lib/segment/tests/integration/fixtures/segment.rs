@@ -120,6 +120,10 @@ pub fn build_segment_3(path: &Path) -> Segment {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
                 (
@@ -130,6 +134,10 @@ pub fn build_segment_3(path: &Path) -> Segment {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
                 (
@@ -140,6 +148,10 @@ pub fn build_segment_3(path: &Path) -> Segment {
                         storage_type: VectorStorageType::Memory,
                         index: Indexes::Plain {},
                         quantization_config: None,
+                        max_sparse_dimension: None,
+                        datatype: Default::default(),
+                        vector_cache_size: None,
+                        strict_vector_checks: false,
                     },
                 ),
             ]),
@@ -49,6 +49,10 @@ fn build_test_segments(path_struct: &Path, path_plain: &Path) -> (Segment, Segme
                 storage_type: VectorStorageType::Memory,
                 index: Indexes::Plain {},
                 quantization_config: None,
+                max_sparse_dimension: None,
+                datatype: Default::default(),
+                vector_cache_size: None,
+                strict_vector_checks: false,
             },
         )]),
         payload_storage_type: Default::default(),
@@ -153,6 +157,10 @@ fn build_test_segments_nested_payload(path_struct: &Path, path_plain: &Path) ->
                 storage_type: VectorStorageType::Memory,
                 index: Indexes::Plain {},
                 quantization_config: None,
+                max_sparse_dimension: None,
+                datatype: Default::default(),
+                vector_cache_size: None,
+                strict_vector_checks: false,
             },
         )]),
         payload_storage_type: Default::default(),
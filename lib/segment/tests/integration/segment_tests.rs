@@ -264,6 +264,7 @@ fn test_update_named_vector() {
         exact: true,
         quantization: None,
         indexed_only: false,
+        normalize_score: false,
     };
     let nearest_upsert = segment
         .search(
@@ -0,0 +1,97 @@
+//! Compact on-disk encoding of sorted sparse vector indices
+//!
+//! Indices are stored as the first index followed by gaps between consecutive indices,
+//! each written as a LEB128 variable length integer. Small gaps take a single byte.
+
+use crate::common::types::DimId;
+
+/// Encode sorted `indices` into delta encoded bytes
+///
+/// # Panics
+///
+/// Panics in debug mode if `indices` are not sorted.
+pub fn encode_indices(indices: &[DimId]) -> Vec<u8> {
+    debug_assert!(
+        indices.windows(2).all(|pair| pair[0] <= pair[1]),
+        "indices must be sorted for delta encoding",
+    );
+    let mut bytes = Vec::with_capacity(indices.len());
+    let mut previous = 0;
+    for &idx in indices {
+        write_varint(&mut bytes, idx - previous);
+        previous = idx;
+    }
+    bytes
+}
+
+/// Decode indices, produced by [`encode_indices`]
+///
+/// Returns `None` if `bytes` are truncated or malformed.
+pub fn decode_indices(bytes: &[u8]) -> Option<Vec<DimId>> {
+    let mut indices = Vec::with_capacity(bytes.len());
+    let mut previous: DimId = 0;
+    let mut position = 0;
+    while position < bytes.len() {
+        let (delta, read) = read_varint(&bytes[position..])?;
+        previous = previous.checked_add(delta)?;
+        indices.push(previous);
+        position += read;
+    }
+    Some(indices)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: DimId) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Returns decoded value and number of bytes read
+fn read_varint(bytes: &[u8]) -> Option<(DimId, usize)> {
+    let mut value: DimId = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        if shift >= DimId::BITS {
+            return None;
+        }
+        value |= DimId::from(byte & 0x7F).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_encoding_round_trip() {
+        let cases: [&[DimId]; 4] = [
+            &[],
+            &[0],
+            &[1, 2, 3, 5, 8, 13, 130, 131, 20_000],
+            &[0, 0, 7, DimId::MAX - 1, DimId::MAX],
+        ];
+        for indices in cases {
+            let encoded = encode_indices(indices);
+            assert_eq!(decode_indices(&encoded).unwrap(), indices);
+        }
+
+        // Dense-ish vector fits into a byte per index
+        let indices: Vec<DimId> = (1000..2000).step_by(3).collect();
+        let encoded = encode_indices(&indices);
+        assert_eq!(encoded.len(), indices.len() + 1);
+        assert!(encoded.len() < indices.len() * std::mem::size_of::<DimId>());
+    }
+
+    #[test]
+    fn delta_decoding_malformed() {
+        let encoded = encode_indices(&[1, 300]);
+        assert_eq!(decode_indices(&encoded[..encoded.len() - 1]), None);
+        assert_eq!(decode_indices(&[0xFF; 6]), None);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod delta_encoding;
 pub mod sparse_vector;
 pub mod types;
+pub mod weight_quantization;
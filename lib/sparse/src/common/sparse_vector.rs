@@ -1,13 +1,1243 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 use crate::common::types::{DimId, DimWeight};
 
-#[derive(Debug, PartialEq, Clone)]
+/// Sparse vector as parallel arrays of indices and weights
+///
+/// Equality and hashing compare both arrays as they are, assuming sorted indices like the scoring
+/// code does. Bring vectors to canonical form with [`SparseVector::canonicalize`] before comparing,
+/// or use [`SparseVector::eq_unordered`].
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SparseVector {
     pub indices: Vec<DimId>,
     pub weights: Vec<DimWeight>,
 }
 
+/// Borrowed [`SparseVector`], e.g. sliced from memory-mapped storage
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SparseVectorRef<'a> {
+    pub indices: &'a [DimId],
+    pub weights: &'a [DimWeight],
+}
+
+impl<'a> SparseVectorRef<'a> {
+    pub fn new(indices: &'a [DimId], weights: &'a [DimWeight]) -> Self {
+        SparseVectorRef { indices, weights }
+    }
+
+    /// Number of stored dimensions
+    pub fn len(self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn to_owned_vector(self) -> SparseVector {
+        SparseVector::new(self.indices.to_vec(), self.weights.to_vec())
+    }
+
+    /// Same as [`SparseVector::merge_iter`]
+    pub fn merge_iter(self, other: SparseVectorRef<'a>) -> impl Iterator<Item = MergeItem> + 'a {
+        debug_assert!(
+            has_strictly_increasing_indices(self.indices)
+                && has_strictly_increasing_indices(other.indices),
+            "indices must be strictly increasing",
+        );
+        MergeIter {
+            left: self,
+            right: other,
+            i: 0,
+            j: 0,
+        }
+    }
+
+    /// Same as [`SparseVector::dot`]
+    pub fn dot(self, other: SparseVectorRef) -> DimWeight {
+        self.merge_iter(other)
+            .map(|item| match item {
+                MergeItem::Both(_, left, right) => left * right,
+                MergeItem::LeftOnly(..) | MergeItem::RightOnly(..) => 0.0,
+            })
+            .sum()
+    }
+
+    /// Same as [`SparseVector::cosine_with_norm`]
+    pub fn cosine_with_norm(self, other: SparseVectorRef, self_norm: DimWeight) -> f32 {
+        let norms = self_norm * other.l2_norm();
+        if norms == 0.0 {
+            return 0.0;
+        }
+        self.dot(other) / norms
+    }
+
+    fn l2_norm(self) -> DimWeight {
+        self.weights.iter().map(|w| w * w).sum::<DimWeight>().sqrt()
+    }
+}
+
+/// How [`SparseVector::from_tokens`] treats tokens missing in vocabulary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownTokens {
+    Skip,
+    Reject,
+}
+
+/// Malformed [`SparseVector`], or error of its construction from external data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SparseError {
+    /// Index is negative or does not fit into [`DimId`]
+    InvalidIndex {
+        position: usize,
+        index: i64,
+    },
+    LengthMismatch {
+        indices: usize,
+        weights: usize,
+    },
+    /// Token is missing in vocabulary, see [`UnknownTokens::Reject`]
+    UnknownToken(String),
+    DuplicateIndex {
+        index: DimId,
+    },
+    NaNWeight {
+        index: DimId,
+    },
+    UnsortedIndex {
+        index: DimId,
+    },
+    InfiniteWeight {
+        index: DimId,
+    },
+}
+
+impl std::fmt::Display for SparseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SparseError::InvalidIndex { position, index } => write!(
+                f,
+                "Invalid sparse vector index {index} at position {position}, expected value within [0;{}]",
+                DimId::MAX,
+            ),
+            SparseError::LengthMismatch { indices, weights } => write!(
+                f,
+                "Sparse vector has {indices} indices but {weights} weights",
+            ),
+            SparseError::UnknownToken(token) => write!(f, "Unknown token: {token}"),
+            SparseError::DuplicateIndex { index } => {
+                write!(f, "Sparse vector has duplicate index {index}")
+            }
+            SparseError::NaNWeight { index } => {
+                write!(f, "Sparse vector has NaN weight at index {index}")
+            }
+            SparseError::UnsortedIndex { index } => {
+                write!(f, "Sparse vector indices are not sorted at index {index}")
+            }
+            SparseError::InfiniteWeight { index } => {
+                write!(f, "Sparse vector has infinite weight at index {index}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SparseError {}
+
 impl SparseVector {
     pub fn new(indices: Vec<DimId>, weights: Vec<DimWeight>) -> SparseVector {
         SparseVector { indices, weights }
     }
+
+    /// Scale weights to unit L2 norm, zero vector is left unchanged
+    pub fn normalize(&mut self) {
+        let norm = self.l2_norm();
+        if norm == 0.0 {
+            return;
+        }
+        self.weights.iter_mut().for_each(|weight| *weight /= norm);
+    }
+
+    /// Same as [`SparseVector::normalize`], without modifying `self`
+    pub fn normalized(&self) -> SparseVector {
+        let mut vector = self.clone();
+        vector.normalize();
+        vector
+    }
+
+    pub fn as_vec_ref(&self) -> SparseVectorRef<'_> {
+        SparseVectorRef::new(&self.indices, &self.weights)
+    }
+
+    /// Number of stored dimensions
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Largest stored dimension index, `None` for empty vector
+    ///
+    /// Indices are not required to be sorted.
+    pub fn max_index(&self) -> Option<DimId> {
+        self.indices.iter().max().copied()
+    }
+
+    /// Approximate memory footprint of the vector in bytes
+    ///
+    /// Counts struct itself and stored indices and weights, spare capacity is not included.
+    pub fn estimated_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.indices.len() * std::mem::size_of::<DimId>()
+            + self.weights.len() * std::mem::size_of::<DimWeight>()
+    }
+
+    /// Check that vector is well-formed
+    ///
+    /// Indices and weights must have equal lengths, indices must be unique and weights must not
+    /// be NaN. Indices are not required to be sorted, see [`SparseVector::sort_by_indices`].
+    pub fn validate(&self) -> Result<(), SparseError> {
+        if self.indices.len() != self.weights.len() {
+            return Err(SparseError::LengthMismatch {
+                indices: self.indices.len(),
+                weights: self.weights.len(),
+            });
+        }
+        if let Some((&index, _)) = self
+            .indices
+            .iter()
+            .zip(&self.weights)
+            .find(|(_, weight)| weight.is_nan())
+        {
+            return Err(SparseError::NaNWeight { index });
+        }
+        let mut indices = self.indices.clone();
+        indices.sort_unstable();
+        if let Some(pair) = indices.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(SparseError::DuplicateIndex { index: pair[0] });
+        }
+        Ok(())
+    }
+
+    /// Same as [`SparseVector::validate`], but also requires sorted indices and finite weights
+    ///
+    /// Vectors passing this check can be used in [`SparseVector::dot`] directly.
+    pub fn validate_sorted(&self) -> Result<(), SparseError> {
+        self.validate()?;
+        if let Some(pair) = self.indices.windows(2).find(|pair| pair[0] > pair[1]) {
+            return Err(SparseError::UnsortedIndex { index: pair[1] });
+        }
+        if let Some((&index, _)) = self
+            .indices
+            .iter()
+            .zip(&self.weights)
+            .find(|(_, weight)| weight.is_infinite())
+        {
+            return Err(SparseError::InfiniteWeight { index });
+        }
+        Ok(())
+    }
+
+    /// Sort indices in ascending order, moving weights along with them
+    pub fn sort_by_indices(&mut self) {
+        let mut pairs: Vec<_> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .collect();
+        pairs.sort_unstable_by_key(|(idx, _)| *idx);
+        (self.indices, self.weights) = pairs.into_iter().unzip();
+    }
+
+    /// Build vector from signed indices, as produced by external formats (e.g. scipy `int32`)
+    ///
+    /// Negative indices are rejected instead of being wrapped into huge dimensions.
+    pub fn from_signed_indices<I: Copy + Into<i64>>(
+        indices: &[I],
+        weights: Vec<DimWeight>,
+    ) -> Result<SparseVector, SparseError> {
+        if indices.len() != weights.len() {
+            return Err(SparseError::LengthMismatch {
+                indices: indices.len(),
+                weights: weights.len(),
+            });
+        }
+        let indices = indices
+            .iter()
+            .enumerate()
+            .map(|(position, &index)| {
+                let index = index.into();
+                DimId::try_from(index).map_err(|_| SparseError::InvalidIndex { position, index })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(SparseVector::new(indices, weights))
+    }
+
+    /// Build vector from weighted tokens, mapping them to dimensions with `vocab`
+    ///
+    /// Weights of repeated tokens are summed, the result is in canonical form,
+    /// see [`SparseVector::canonicalize`]. Tokens missing in `vocab` are handled according to
+    /// `unknown_tokens`, rejection reports the first unknown token.
+    pub fn from_tokens(
+        tokens: &[(&str, DimWeight)],
+        vocab: &HashMap<String, DimId>,
+        unknown_tokens: UnknownTokens,
+    ) -> Result<SparseVector, SparseError> {
+        let mut indices = Vec::with_capacity(tokens.len());
+        let mut weights = Vec::with_capacity(tokens.len());
+        for &(token, weight) in tokens {
+            match vocab.get(token) {
+                Some(&idx) => {
+                    indices.push(idx);
+                    weights.push(weight);
+                }
+                None => match unknown_tokens {
+                    UnknownTokens::Skip => {}
+                    UnknownTokens::Reject => {
+                        return Err(SparseError::UnknownToken(token.to_string()))
+                    }
+                },
+            }
+        }
+        let mut vector = SparseVector::new(indices, weights);
+        vector.canonicalize();
+        Ok(vector)
+    }
+
+    /// Walk indices of both vectors in increasing order, pairing up shared dimensions
+    ///
+    /// Both vectors must be sorted by index, e.g. with [`SparseVector::canonicalize`].
+    pub fn merge_iter<'a>(
+        &'a self,
+        other: &'a SparseVector,
+    ) -> impl Iterator<Item = MergeItem> + 'a {
+        self.as_vec_ref().merge_iter(other.as_vec_ref())
+    }
+
+    /// Dot product with `other`, in linear time
+    ///
+    /// Both vectors must be sorted by index, see [`SparseVector::merge_iter`].
+    pub fn dot(&self, other: &SparseVector) -> DimWeight {
+        self.as_vec_ref().dot(other.as_vec_ref())
+    }
+
+    /// Angle between two vectors in radians, in range `[0, π]`.
+    ///
+    /// Unlike cosine similarity, the angle satisfies the triangle inequality.
+    /// If any of the vectors is zero, the vectors are considered orthogonal.
+    pub fn angular_distance(&self, other: &SparseVector) -> f32 {
+        // float drift can push cosine slightly out of range, which gives NaN in `acos`
+        self.cosine(other).clamp(-1.0, 1.0).acos()
+    }
+
+    /// Cosine similarity, in range `[-1, 1]`
+    ///
+    /// If any of the vectors is zero, the vectors are considered orthogonal.
+    /// Vectors don't need to be normalized or sorted by indices.
+    pub fn cosine(&self, other: &SparseVector) -> DimWeight {
+        self.cosine_with_norm(other, self.l2_norm())
+    }
+
+    /// Same as [`SparseVector::cosine`], with precomputed L2 norm of `self`
+    pub fn cosine_with_norm(&self, other: &SparseVector, self_norm: DimWeight) -> f32 {
+        self.sorted()
+            .as_vec_ref()
+            .cosine_with_norm(other.sorted().as_vec_ref(), self_norm)
+    }
+
+    /// Weighted Jaccard (Tanimoto) similarity: `dot / (|a|² + |b|² - dot)`
+    ///
+    /// Returns 0.0 if both vectors are zero.
+    pub fn tanimoto(&self, other: &SparseVector) -> f32 {
+        let dot = dot_product(self, other);
+        let denominator = self.squared_l2_norm() + other.squared_l2_norm() - dot;
+        if denominator == 0.0 {
+            return 0.0;
+        }
+        dot / denominator
+    }
+
+    /// Number of dimensions present in both vectors, weights are not considered
+    ///
+    /// Zero means the vectors can't have a non-zero dot product. Vectors don't need to be sorted
+    /// by indices, for sorted ones this is the same as [`SparseVector::intersection_count`].
+    pub fn intersection_size(&self, other: &SparseVector) -> usize {
+        self.sorted().intersection_count(&other.sorted())
+    }
+
+    /// Number of dimensions present in both vectors, in linear time
+    ///
+    /// Both vectors must be sorted by index, see [`SparseVector::dot`].
+    /// Use [`SparseVector::intersection_size`] for unsorted vectors.
+    pub fn intersection_count(&self, other: &SparseVector) -> usize {
+        self.merge_iter(other)
+            .filter(|item| matches!(item, MergeItem::Both(..)))
+            .count()
+    }
+
+    /// Number of dimensions present in any of the vectors, in linear time
+    ///
+    /// Both vectors must be sorted by index, see [`SparseVector::dot`].
+    pub fn union_count(&self, other: &SparseVector) -> usize {
+        self.indices.len() + other.indices.len() - self.intersection_count(other)
+    }
+
+    /// Cosine distance `1 - cosine`, clamped to `[0, 2]`
+    pub fn cosine_distance(&self, other: &SparseVector) -> f32 {
+        (1.0 - self.cosine(other)).clamp(0.0, 2.0)
+    }
+
+    /// Set all present weights to 1.0, keeping only the set of dimensions
+    ///
+    /// Dot product of two binarized vectors is the size of their index intersection.
+    pub fn binarize(&mut self) {
+        self.weights.iter_mut().for_each(|weight| *weight = 1.0);
+    }
+
+    /// Map each weight through `f`, keeping indices intact
+    ///
+    /// Useful for sublinear weight scaling, e.g. `|w| (1.0 + w).ln()`.
+    pub fn apply_weight_fn(&mut self, f: impl Fn(DimWeight) -> DimWeight) {
+        self.weights
+            .iter_mut()
+            .for_each(|weight| *weight = f(*weight));
+    }
+
+    /// Multiply all weights by `factor`
+    pub fn scale(&mut self, factor: DimWeight) {
+        self.weights.iter_mut().for_each(|weight| *weight *= factor);
+    }
+
+    /// Add `other` to `self`, summing weights of shared dimensions
+    ///
+    /// Result holds the union of dimensions of both vectors. Both vectors must have
+    /// strictly increasing indices, same as for [`SparseVector::dot`], and so will the result.
+    pub fn add_assign(&mut self, other: &SparseVector) {
+        let capacity = self.indices.len() + other.indices.len();
+        let mut indices = Vec::with_capacity(capacity);
+        let mut weights = Vec::with_capacity(capacity);
+        for item in self.merge_iter(other) {
+            let (idx, weight) = match item {
+                MergeItem::Both(idx, left, right) => (idx, left + right),
+                MergeItem::LeftOnly(idx, weight) | MergeItem::RightOnly(idx, weight) => {
+                    (idx, weight)
+                }
+            };
+            indices.push(idx);
+            weights.push(weight);
+        }
+        (self.indices, self.weights) = (indices, weights);
+    }
+
+    /// Remove dimensions with absolute weight below `threshold`
+    ///
+    /// Order of the remaining dimensions is preserved.
+    pub fn prune(&mut self, threshold: DimWeight) {
+        let (indices, weights) = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .filter(|(_, weight)| weight.abs() >= threshold)
+            .unzip();
+        (self.indices, self.weights) = (indices, weights);
+    }
+
+    /// Keep only `k` dimensions with the largest absolute weights, sorted by index
+    pub fn top_k(&mut self, k: usize) {
+        let mut pairs: Vec<_> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .collect();
+        if k < pairs.len() {
+            pairs.select_nth_unstable_by(k, |(_, a), (_, b)| b.abs().total_cmp(&a.abs()));
+            pairs.truncate(k);
+        }
+        pairs.sort_unstable_by_key(|(idx, _)| *idx);
+        (self.indices, self.weights) = pairs.into_iter().unzip();
+    }
+
+    /// Densify over the index range `0..n`, filling missing dimensions with 0.0 weight
+    ///
+    /// Resulting indices are sorted, dimensions outside of the range are kept after it.
+    pub fn pad_to_range(&self, n: usize) -> SparseVector {
+        let mut weights = vec![0.0; n];
+        let mut outside = Vec::new();
+        for (&idx, &weight) in self.indices.iter().zip(&self.weights) {
+            match weights.get_mut(idx as usize) {
+                Some(dense_weight) => *dense_weight = weight,
+                None => outside.push((idx, weight)),
+            }
+        }
+        outside.sort_unstable_by_key(|(idx, _)| *idx);
+        let (outside_indices, outside_weights): (Vec<_>, Vec<_>) = outside.into_iter().unzip();
+
+        let mut indices: Vec<DimId> = (0..n as DimId).collect();
+        indices.extend(outside_indices);
+        weights.extend(outside_weights);
+        SparseVector { indices, weights }
+    }
+
+    /// Bring vector to its canonical form
+    ///
+    /// Sorts by index, merges duplicate indices by summing their weights and drops exact-zero
+    /// weights. Semantically equal vectors have identical canonical forms.
+    pub fn canonicalize(&mut self) {
+        let mut pairs: Vec<_> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .collect();
+        pairs.sort_unstable_by_key(|(idx, _)| *idx);
+
+        self.indices.clear();
+        self.weights.clear();
+        for (idx, weight) in pairs {
+            if self.indices.last() == Some(&idx) {
+                *self.weights.last_mut().unwrap() += weight;
+            } else {
+                self.indices.push(idx);
+                self.weights.push(weight);
+            }
+        }
+
+        let mut i = 0;
+        self.indices.retain(|_| {
+            let keep = self.weights[i] != 0.0;
+            i += 1;
+            keep
+        });
+        self.weights.retain(|&weight| weight != 0.0);
+    }
+
+    /// Same as `==`, but ignores the order of dimensions
+    ///
+    /// Duplicate indices and zero weights are compared as is, unlike after
+    /// [`SparseVector::canonicalize`].
+    pub fn eq_unordered(&self, other: &SparseVector) -> bool {
+        if self.indices.len() != other.indices.len() || self.weights.len() != other.weights.len() {
+            return false;
+        }
+        let sorted_pairs = |vector: &SparseVector| {
+            let mut pairs: Vec<_> = vector
+                .indices
+                .iter()
+                .copied()
+                .zip(vector.weights.iter().copied())
+                .collect();
+            pairs.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            pairs
+        };
+        sorted_pairs(self) == sorted_pairs(other)
+    }
+
+    fn has_strictly_increasing_indices(&self) -> bool {
+        has_strictly_increasing_indices(&self.indices)
+    }
+
+    /// Borrow `self` if it is sorted by index, sort a copy otherwise
+    fn sorted(&self) -> Cow<'_, SparseVector> {
+        if self.has_strictly_increasing_indices() {
+            return Cow::Borrowed(self);
+        }
+        let mut sorted = self.clone();
+        sorted.sort_by_indices();
+        Cow::Owned(sorted)
+    }
+
+    fn l2_norm(&self) -> DimWeight {
+        self.as_vec_ref().l2_norm()
+    }
+
+    fn squared_l2_norm(&self) -> DimWeight {
+        self.weights.iter().map(|w| w * w).sum()
+    }
+}
+
+/// Consistent with `PartialEq`, so order of dimensions matters
+impl Hash for SparseVector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.indices.hash(state);
+        state.write_usize(self.weights.len());
+        for &weight in &self.weights {
+            // `0.0 == -0.0`, so they must hash the same
+            let bits = if weight == 0.0 { 0 } else { weight.to_bits() };
+            state.write_u32(bits);
+        }
+    }
+}
+
+/// Single step of [`SparseVector::merge_iter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeItem {
+    /// Dimension present in both vectors, with left and right weights
+    Both(DimId, DimWeight, DimWeight),
+    LeftOnly(DimId, DimWeight),
+    RightOnly(DimId, DimWeight),
+}
+
+fn has_strictly_increasing_indices(indices: &[DimId]) -> bool {
+    indices.windows(2).all(|pair| pair[0] < pair[1])
+}
+
+struct MergeIter<'a> {
+    left: SparseVectorRef<'a>,
+    right: SparseVectorRef<'a>,
+    i: usize,
+    j: usize,
+}
+
+impl Iterator for MergeIter<'_> {
+    type Item = MergeItem;
+
+    fn next(&mut self) -> Option<MergeItem> {
+        let left = self.left.indices.get(self.i);
+        let right = self.right.indices.get(self.j);
+        let item = match (left, right) {
+            (None, None) => return None,
+            (Some(&idx), None) => MergeItem::LeftOnly(idx, self.left.weights[self.i]),
+            (None, Some(&idx)) => MergeItem::RightOnly(idx, self.right.weights[self.j]),
+            (Some(&left_idx), Some(&right_idx)) => match left_idx.cmp(&right_idx) {
+                std::cmp::Ordering::Less => {
+                    MergeItem::LeftOnly(left_idx, self.left.weights[self.i])
+                }
+                std::cmp::Ordering::Greater => {
+                    MergeItem::RightOnly(right_idx, self.right.weights[self.j])
+                }
+                std::cmp::Ordering::Equal => MergeItem::Both(
+                    left_idx,
+                    self.left.weights[self.i],
+                    self.right.weights[self.j],
+                ),
+            },
+        };
+        match item {
+            MergeItem::Both(..) => {
+                self.i += 1;
+                self.j += 1;
+            }
+            MergeItem::LeftOnly(..) => self.i += 1,
+            MergeItem::RightOnly(..) => self.j += 1,
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.left.indices.len() - self.i;
+        let right = self.right.indices.len() - self.j;
+        (left.max(right), Some(left + right))
+    }
+}
+
+/// Scores many stored vectors against a single query by cosine similarity
+///
+/// Query norm is computed once, instead of once per scored vector. The query is sorted once as
+/// well if needed, stored vectors must be sorted by index, see [`SparseVector::dot`].
+pub struct SparseCosineScorer<'a> {
+    query: Cow<'a, SparseVector>,
+    query_norm: DimWeight,
+}
+
+impl<'a> SparseCosineScorer<'a> {
+    pub fn new(query: &'a SparseVector) -> Self {
+        Self {
+            query: query.sorted(),
+            query_norm: query.l2_norm(),
+        }
+    }
+
+    pub fn score(&self, vector: SparseVectorRef) -> f32 {
+        self.query
+            .as_vec_ref()
+            .cosine_with_norm(vector, self.query_norm)
+    }
+
+    pub fn score_batch<'b>(
+        &self,
+        vectors: impl IntoIterator<Item = SparseVectorRef<'b>>,
+    ) -> Vec<f32> {
+        vectors
+            .into_iter()
+            .map(|vector| self.score(vector))
+            .collect()
+    }
+}
+
+/// Count how many vectors have a weight in each dimension
+///
+/// Indices within each vector are expected to be unique.
+pub fn dimension_histogram<'a>(
+    vectors: impl Iterator<Item = &'a SparseVector>,
+) -> HashMap<DimId, usize> {
+    let mut histogram = HashMap::new();
+    for vector in vectors {
+        for &idx in &vector.indices {
+            *histogram.entry(idx).or_insert(0) += 1;
+        }
+    }
+    histogram
+}
+
+/// Dot product of two sparse vectors, indices are not required to be sorted
+fn dot_product(a: &SparseVector, b: &SparseVector) -> DimWeight {
+    a.sorted().dot(&b.sorted())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_test() {
+        let a = SparseVector::new(vec![1, 3, 5], vec![1.0, 2.0, 3.0]);
+
+        // disjoint
+        let b = SparseVector::new(vec![0, 2, 4, 6], vec![1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(a.dot(&b), 0.0);
+
+        // fully overlapping
+        let b = SparseVector::new(vec![1, 3, 5], vec![0.5, -1.0, 2.0]);
+        assert_eq!(a.dot(&b), 0.5 - 2.0 + 6.0);
+        assert_eq!(a.dot(&a), 14.0);
+
+        // partially overlapping
+        let b = SparseVector::new(vec![0, 3, 4, 5, 9], vec![7.0, 2.0, 7.0, -1.0, 7.0]);
+        assert_eq!(a.dot(&b), 4.0 - 3.0);
+        assert_eq!(a.dot(&b), b.dot(&a));
+        assert_eq!(a.dot(&b), dot_product(&a, &b));
+
+        // empty
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(a.dot(&empty), 0.0);
+        assert_eq!(empty.dot(&a), 0.0);
+        assert_eq!(empty.dot(&empty), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "indices must be strictly increasing")]
+    #[cfg(debug_assertions)]
+    fn dot_unsorted_test() {
+        let a = SparseVector::new(vec![3, 1], vec![1.0, 2.0]);
+        a.dot(&a);
+    }
+
+    #[test]
+    fn merge_iter_test() {
+        let a = SparseVector::new(vec![1, 3, 5, 9], vec![1.0, 2.0, 3.0, 4.0]);
+        let b = SparseVector::new(vec![0, 3, 9, 12], vec![0.5, 0.25, 2.0, 1.5]);
+        let items: Vec<_> = a.merge_iter(&b).collect();
+        assert_eq!(
+            items,
+            vec![
+                MergeItem::RightOnly(0, 0.5),
+                MergeItem::LeftOnly(1, 1.0),
+                MergeItem::Both(3, 2.0, 0.25),
+                MergeItem::LeftOnly(5, 3.0),
+                MergeItem::Both(9, 4.0, 2.0),
+                MergeItem::RightOnly(12, 1.5),
+            ],
+        );
+
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(empty.merge_iter(&empty).next(), None);
+        let items: Vec<_> = empty.merge_iter(&b).collect();
+        assert_eq!(items.len(), 4);
+        assert!(items
+            .iter()
+            .all(|item| matches!(item, MergeItem::RightOnly(..))));
+    }
+
+    #[test]
+    fn normalize_test() {
+        let mut a = SparseVector::new(vec![1, 5, 3], vec![3.0, -4.0, 12.0]);
+        a.normalize();
+        assert_eq!(a.indices, vec![1, 5, 3]);
+        assert!((a.l2_norm() - 1.0).abs() < 1e-6);
+        assert!((a.weights[0] - 3.0 / 13.0).abs() < 1e-6);
+
+        let b = SparseVector::new(vec![2, 7], vec![0.5, 0.5]);
+        let normalized = b.normalized();
+        assert_eq!(b.weights, vec![0.5, 0.5]);
+        assert!((normalized.l2_norm() - 1.0).abs() < 1e-6);
+
+        for zero in [
+            SparseVector::new(vec![], vec![]),
+            SparseVector::new(vec![1, 2], vec![0.0, 0.0]),
+        ] {
+            let normalized = zero.normalized();
+            assert_eq!(normalized, zero);
+            assert!(normalized.weights.iter().all(|weight| !weight.is_nan()));
+        }
+    }
+
+    #[test]
+    fn len_test() {
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.max_index(), None);
+
+        let single = SparseVector::new(vec![DimId::MAX], vec![1.0]);
+        assert_eq!(single.len(), 1);
+        assert!(!single.is_empty());
+        assert_eq!(single.max_index(), Some(DimId::MAX));
+
+        let unsorted = SparseVector::new(vec![7, 100_000, 3], vec![0.1, 0.2, 0.3]);
+        assert_eq!(unsorted.len(), 3);
+        assert_eq!(unsorted.max_index(), Some(100_000));
+    }
+
+    #[test]
+    fn vec_ref_test() {
+        let a = SparseVector::new(vec![1, 3, 5], vec![0.1, 0.3, 0.5]);
+        let a_ref = a.as_vec_ref();
+        assert_eq!(a_ref.len(), 3);
+        assert!(!a_ref.is_empty());
+        assert_eq!(a_ref.to_owned_vector(), a);
+
+        let indices = [2, 4];
+        let weights = [1.0, -1.0];
+        let b_ref = SparseVectorRef::new(&indices, &weights);
+        assert_eq!(b_ref.to_owned_vector().as_vec_ref(), b_ref);
+        assert_eq!(a_ref.dot(b_ref), 0.0);
+        assert_eq!(a_ref.dot(a_ref), a.dot(&a));
+        assert!(SparseVector::new(vec![], vec![]).as_vec_ref().is_empty());
+    }
+
+    #[test]
+    fn estimated_bytes_test() {
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(empty.estimated_bytes(), std::mem::size_of::<SparseVector>());
+
+        let per_dimension = std::mem::size_of::<DimId>() + std::mem::size_of::<DimWeight>();
+        for len in [1, 10, 1000] {
+            let vector = SparseVector::new((0..len).collect(), vec![1.0; len as usize]);
+            assert_eq!(
+                vector.estimated_bytes(),
+                empty.estimated_bytes() + len as usize * per_dimension,
+            );
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        let valid = SparseVector::new(vec![5, 1, 3], vec![0.5, 0.1, 0.3]);
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(SparseVector::new(vec![], vec![]).validate(), Ok(()));
+
+        let error = SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2]).validate();
+        assert_eq!(
+            error,
+            Err(SparseError::LengthMismatch {
+                indices: 3,
+                weights: 2
+            })
+        );
+
+        let error = SparseVector::new(vec![3, 1, 3], vec![0.1, 0.2, 0.3]).validate();
+        assert_eq!(error, Err(SparseError::DuplicateIndex { index: 3 }));
+
+        let error = SparseVector::new(vec![1, 2], vec![0.1, f32::NAN]).validate();
+        assert_eq!(error, Err(SparseError::NaNWeight { index: 2 }));
+    }
+
+    #[test]
+    fn validate_sorted_test() {
+        assert_eq!(
+            SparseVector::new(vec![1, 3, 8], vec![0.1, -0.2, 0.3]).validate_sorted(),
+            Ok(()),
+        );
+        assert_eq!(
+            SparseVector::new(vec![1, 8, 3], vec![0.1, 0.2, 0.3]).validate_sorted(),
+            Err(SparseError::UnsortedIndex { index: 3 }),
+        );
+        assert_eq!(
+            SparseVector::new(vec![1, 3], vec![0.1, DimWeight::INFINITY]).validate_sorted(),
+            Err(SparseError::InfiniteWeight { index: 3 }),
+        );
+        assert_eq!(
+            SparseVector::new(vec![3, 3], vec![0.1, 0.2]).validate_sorted(),
+            Err(SparseError::DuplicateIndex { index: 3 }),
+        );
+    }
+
+    #[test]
+    fn sort_by_indices_test() {
+        let mut a = SparseVector::new(vec![5, 1, 3, 0], vec![0.5, 0.1, 0.3, 0.0]);
+        a.sort_by_indices();
+        assert_eq!(a.indices, vec![0, 1, 3, 5]);
+        assert_eq!(a.weights, vec![0.0, 0.1, 0.3, 0.5]);
+        assert_eq!(a.validate(), Ok(()));
+    }
+
+    #[test]
+    fn angular_distance_test() {
+        let a = SparseVector::new(vec![1, 2, 3], vec![1.0, 2.0, 3.0]);
+        let a_unsorted = SparseVector::new(vec![3, 1, 2], vec![3.0, 1.0, 2.0]);
+        assert!(a.angular_distance(&a).abs() < 1e-3);
+        assert!(a.angular_distance(&a_unsorted).abs() < 1e-3);
+
+        let orthogonal = SparseVector::new(vec![4, 5], vec![1.0, 1.0]);
+        let distance = a.angular_distance(&orthogonal);
+        assert!((distance - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let opposite = SparseVector::new(vec![1, 2, 3], vec![-1.0, -2.0, -3.0]);
+        let distance = a.angular_distance(&opposite);
+        assert!((distance - std::f32::consts::PI).abs() < 1e-3);
+        assert!(!distance.is_nan());
+    }
+
+    #[test]
+    fn dimension_histogram_test() {
+        let vectors = [
+            SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2, 0.3]),
+            SparseVector::new(vec![2, 3], vec![0.2, 0.3]),
+            SparseVector::new(vec![3, 10], vec![0.3, 1.0]),
+            SparseVector::new(vec![], vec![]),
+        ];
+
+        let histogram = dimension_histogram(vectors.iter());
+
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram[&1], 1);
+        assert_eq!(histogram[&2], 2);
+        assert_eq!(histogram[&3], 3);
+        assert_eq!(histogram[&10], 1);
+        assert!(!histogram.contains_key(&4));
+    }
+
+    #[test]
+    fn binarize_test() {
+        let mut a = SparseVector::new(vec![1, 2, 3, 7], vec![0.1, -0.2, 3.0, 0.5]);
+        let mut b = SparseVector::new(vec![7, 3, 4], vec![2.0, 0.01, 1.5]);
+        a.binarize();
+        b.binarize();
+
+        assert_eq!(a.indices, vec![1, 2, 3, 7]);
+        assert!(a.weights.iter().all(|&w| w == 1.0));
+        assert_eq!(dot_product(&a, &b), 2.0);
+    }
+
+    #[test]
+    fn apply_weight_fn_test() {
+        let mut a = SparseVector::new(vec![3, 1, 8], vec![4.0, 9.0, 0.25]);
+        a.apply_weight_fn(DimWeight::sqrt);
+
+        assert_eq!(a.indices, vec![3, 1, 8]);
+        assert_eq!(a.weights, vec![2.0, 3.0, 0.5]);
+    }
+
+    #[test]
+    fn add_assign_test() {
+        let mut a = SparseVector::new(vec![1, 3, 5], vec![0.1, 0.3, 0.5]);
+        a.add_assign(&SparseVector::new(
+            vec![0, 3, 4, 9],
+            vec![1.0, 1.0, 1.0, 1.0],
+        ));
+        assert_eq!(
+            a,
+            SparseVector::new(vec![0, 1, 3, 4, 5, 9], vec![1.0, 0.1, 1.3, 1.0, 0.5, 1.0]),
+        );
+        assert!(a.has_strictly_increasing_indices());
+
+        let mut disjoint = SparseVector::new(vec![7, 8], vec![0.7, 0.8]);
+        disjoint.add_assign(&SparseVector::new(vec![2], vec![0.2]));
+        assert_eq!(disjoint.indices, vec![2, 7, 8]);
+        assert_eq!(disjoint.weights, vec![0.2, 0.7, 0.8]);
+
+        // Cancelled out dimensions stay in place with zero weight
+        let mut b = SparseVector::new(vec![1, 2], vec![0.5, 1.0]);
+        b.add_assign(&SparseVector::new(vec![2], vec![-1.0]));
+        assert_eq!(b, SparseVector::new(vec![1, 2], vec![0.5, 0.0]));
+
+        let mut empty = SparseVector::new(vec![], vec![]);
+        empty.add_assign(&b);
+        assert_eq!(empty, b);
+        b.add_assign(&SparseVector::new(vec![], vec![]));
+        assert_eq!(empty, b);
+    }
+
+    #[test]
+    fn scale_test() {
+        let mut sum = SparseVector::new(vec![1, 3], vec![1.0, 2.0]);
+        sum.add_assign(&SparseVector::new(vec![3, 4], vec![2.0, 4.0]));
+        sum.scale(0.5);
+        assert_eq!(sum, SparseVector::new(vec![1, 3, 4], vec![0.5, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn prune_test() {
+        let mut a = SparseVector::new(vec![1, 2, 5, 7, 9], vec![0.01, -0.5, 0.2, -0.001, 0.1]);
+        a.prune(0.1);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.indices, vec![2, 5, 9]);
+        assert_eq!(a.weights, vec![-0.5, 0.2, 0.1]);
+
+        a.prune(1.0);
+        assert!(a.is_empty());
+        assert!(a.weights.is_empty());
+    }
+
+    #[test]
+    fn top_k_test() {
+        let mut a = SparseVector::new(vec![9, 1, 5, 3, 7], vec![0.1, -0.9, 0.3, 0.5, -0.2]);
+        a.top_k(3);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.indices, vec![1, 3, 5]);
+        assert_eq!(a.weights, vec![-0.9, 0.5, 0.3]);
+
+        // Larger `k` only sorts the vector
+        let mut b = SparseVector::new(vec![4, 2], vec![1.0, 2.0]);
+        b.top_k(10);
+        assert_eq!(b, SparseVector::new(vec![2, 4], vec![2.0, 1.0]));
+
+        b.top_k(0);
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn pad_to_range_test() {
+        let a = SparseVector::new(vec![3, 1, 7], vec![0.3, 0.1, 0.7]);
+
+        let padded = a.pad_to_range(5);
+        assert_eq!(padded.indices, vec![0, 1, 2, 3, 4, 7]);
+        assert_eq!(padded.weights, vec![0.0, 0.1, 0.0, 0.3, 0.0, 0.7]);
+        assert_eq!(dot_product(&a, &a), dot_product(&padded, &padded));
+
+        let padded = a.pad_to_range(8);
+        assert_eq!(padded.indices, (0..8).collect::<Vec<_>>());
+        assert_eq!(padded.weights[7], 0.7);
+    }
+
+    #[test]
+    fn cosine_test() {
+        let a = SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2, 0.3]);
+        assert!((a.cosine(&a) - 1.0).abs() < 1e-6);
+        // Result does not depend on scale
+        let b = SparseVector::new(vec![1, 2, 3], vec![1.0, 2.0, 3.0]);
+        assert!((a.cosine(&b) - 1.0).abs() < 1e-6);
+
+        let disjoint = SparseVector::new(vec![4, 5], vec![1.0, 1.0]);
+        assert_eq!(a.cosine(&disjoint), 0.0);
+
+        let partial = SparseVector::new(vec![3, 4], vec![1.0, 1.0]);
+        let expected = 0.3 / (0.14f32.sqrt() * 2f32.sqrt());
+        assert!((a.cosine(&partial) - expected).abs() < 1e-6);
+
+        let zero = SparseVector::new(vec![1, 2], vec![0.0, 0.0]);
+        assert_eq!(a.cosine(&zero), 0.0);
+        assert_eq!(zero.cosine(&zero), 0.0);
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(empty.cosine(&a), 0.0);
+    }
+
+    #[test]
+    fn cosine_distance_test() {
+        let a = SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2, 0.3]);
+        let a_scaled = SparseVector::new(vec![3, 2, 1], vec![3.0, 2.0, 1.0]);
+        assert!(a.cosine_distance(&a) < 1e-6);
+        assert!(a.cosine_distance(&a_scaled) < 1e-6);
+
+        let orthogonal = SparseVector::new(vec![4, 5], vec![1.0, 1.0]);
+        assert_eq!(a.cosine_distance(&orthogonal), 1.0);
+
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(a.cosine_distance(&empty), 1.0);
+
+        let opposite = SparseVector::new(vec![1, 2, 3], vec![-0.1, -0.2, -0.3]);
+        assert!((a.cosine_distance(&opposite) - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn canonicalize_test() {
+        fn to_bytes(vector: &SparseVector) -> Vec<u8> {
+            let indices = vector.indices.iter().flat_map(|idx| idx.to_le_bytes());
+            let weights = vector.weights.iter().flat_map(|w| w.to_le_bytes());
+            indices.chain(weights).collect()
+        }
+
+        let mut a = SparseVector::new(vec![5, 1, 3, 7], vec![0.5, 0.1, 0.3, 0.0]);
+        let mut b = SparseVector::new(vec![3, 5, 1, 3, 3], vec![0.25, 0.5, 0.1, 0.05, 0.0]);
+        let mut c = SparseVector::new(vec![1, 2, 3, 5, 2], vec![0.1, 1.0, 0.3, 0.5, -1.0]);
+        a.canonicalize();
+        b.canonicalize();
+        c.canonicalize();
+
+        assert_eq!(a.indices, vec![1, 3, 5]);
+        assert_eq!(a.weights, vec![0.1, 0.3, 0.5]);
+        assert_eq!(to_bytes(&a), to_bytes(&b));
+        assert_eq!(to_bytes(&a), to_bytes(&c));
+    }
+
+    #[test]
+    fn equality_and_hash_test() {
+        fn hash_of(vector: &SparseVector) -> u64 {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            vector.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut a = SparseVector::new(vec![1, 3, 5], vec![0.1, 0.3, 0.5]);
+        let mut b = SparseVector::new(vec![5, 1, 3], vec![0.5, 0.1, 0.3]);
+
+        // Order of dimensions matters for `==`
+        assert_ne!(a, b);
+        assert!(a.eq_unordered(&b));
+        assert!(b.eq_unordered(&a));
+        assert!(!a.eq_unordered(&SparseVector::new(vec![5, 1, 3], vec![0.1, 0.5, 0.3])));
+        assert!(!a.eq_unordered(&SparseVector::new(vec![1, 3], vec![0.1, 0.3])));
+
+        a.canonicalize();
+        b.canonicalize();
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+        assert_ne!(
+            hash_of(&a),
+            hash_of(&SparseVector::new(vec![1, 3, 6], vec![0.1, 0.3, 0.5]))
+        );
+
+        let zero = SparseVector::new(vec![2], vec![0.0]);
+        let negative_zero = SparseVector::new(vec![2], vec![-0.0]);
+        assert_eq!(zero, negative_zero);
+        assert_eq!(hash_of(&zero), hash_of(&negative_zero));
+    }
+
+    #[test]
+    fn tanimoto_test() {
+        let a = SparseVector::new(vec![1, 2, 3], vec![1.0, 2.0, 3.0]);
+        let a_unsorted = SparseVector::new(vec![3, 1, 2], vec![3.0, 1.0, 2.0]);
+        assert_eq!(a.tanimoto(&a), 1.0);
+        assert_eq!(a.tanimoto(&a_unsorted), 1.0);
+
+        let disjoint = SparseVector::new(vec![4, 5], vec![1.0, 1.0]);
+        assert_eq!(a.tanimoto(&disjoint), 0.0);
+
+        // dot = 2, |a|² = 2, |b|² = 3
+        let b = SparseVector::new(vec![1, 2], vec![1.0, 1.0]);
+        let c = SparseVector::new(vec![1, 2, 3], vec![1.0, 1.0, 1.0]);
+        assert_eq!(b.tanimoto(&c), 2.0 / 3.0);
+
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(empty.tanimoto(&empty), 0.0);
+        assert_eq!(a.tanimoto(&empty), 0.0);
+    }
+
+    #[test]
+    fn intersection_size_test() {
+        let a = SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2, 0.3]);
+        let disjoint = SparseVector::new(vec![4, 5], vec![1.0, 1.0]);
+        assert_eq!(a.intersection_size(&disjoint), 0);
+
+        let overlapping = SparseVector::new(vec![7, 3, 1], vec![0.0, 1.0, 1.0]);
+        assert_eq!(a.intersection_size(&overlapping), 2);
+        assert_eq!(overlapping.intersection_size(&a), 2);
+        assert_eq!(a.intersection_size(&a), 3);
+
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(a.intersection_size(&empty), 0);
+    }
+
+    #[test]
+    fn intersection_union_count_test() {
+        let a = SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2, 0.3]);
+        let disjoint = SparseVector::new(vec![4, 5], vec![1.0, 1.0]);
+        assert_eq!(a.intersection_count(&disjoint), 0);
+        assert_eq!(a.union_count(&disjoint), 5);
+
+        assert_eq!(a.intersection_count(&a), 3);
+        assert_eq!(a.union_count(&a), 3);
+
+        let nested = SparseVector::new(vec![0, 1, 2, 3, 9], vec![1.0; 5]);
+        assert_eq!(a.intersection_count(&nested), 3);
+        assert_eq!(nested.intersection_count(&a), 3);
+        assert_eq!(a.union_count(&nested), 5);
+
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(a.intersection_count(&empty), 0);
+        assert_eq!(a.union_count(&empty), 3);
+        assert_eq!(empty.union_count(&empty), 0);
+    }
+
+    #[test]
+    fn cosine_scorer_test() {
+        let query = SparseVector::new(vec![3, 1, 2], vec![0.3, 0.1, -0.2]);
+        let stored = vec![
+            SparseVector::new(vec![1, 2, 3], vec![1.0, 2.0, 3.0]),
+            SparseVector::new(vec![4, 5], vec![1.0, 1.0]),
+            SparseVector::new(vec![2, 7], vec![-0.5, 0.5]),
+            SparseVector::new(vec![], vec![]),
+        ];
+
+        let scorer = SparseCosineScorer::new(&query);
+        let batch_scores = scorer.score_batch(stored.iter().map(SparseVector::as_vec_ref));
+        let pair_scores: Vec<_> = stored.iter().map(|vector| query.cosine(vector)).collect();
+        assert_eq!(batch_scores, pair_scores);
+
+        let empty = SparseVector::new(vec![], vec![]);
+        let scorer = SparseCosineScorer::new(&empty);
+        assert!(scorer
+            .score_batch(stored.iter().map(SparseVector::as_vec_ref))
+            .iter()
+            .all(|&score| score == 0.0));
+    }
+
+    #[test]
+    fn from_tokens_test() {
+        let vocab: HashMap<String, DimId> = [("quick", 7), ("brown", 2), ("fox", 5)]
+            .into_iter()
+            .map(|(token, idx)| (token.to_string(), idx))
+            .collect();
+        let tokens = [("fox", 0.5), ("quick", 1.0), ("jumps", 0.3), ("fox", 0.25)];
+
+        let vector = SparseVector::from_tokens(&tokens, &vocab, UnknownTokens::Skip).unwrap();
+        assert_eq!(vector.indices, vec![5, 7]);
+        assert_eq!(vector.weights, vec![0.75, 1.0]);
+
+        let error = SparseVector::from_tokens(&tokens, &vocab, UnknownTokens::Reject).unwrap_err();
+        assert_eq!(error, SparseError::UnknownToken("jumps".to_string()));
+        assert!(error.to_string().contains("jumps"));
+    }
+
+    #[test]
+    fn from_signed_indices_test() {
+        let vector = SparseVector::from_signed_indices(&[3i32, 0, 7], vec![0.1, 0.2, 0.3]).unwrap();
+        assert_eq!(
+            vector,
+            SparseVector::new(vec![3, 0, 7], vec![0.1, 0.2, 0.3])
+        );
+
+        let error = SparseVector::from_signed_indices(&[3i32, -1, 7], vec![0.1, 0.2, 0.3]);
+        assert_eq!(
+            error,
+            Err(SparseError::InvalidIndex {
+                position: 1,
+                index: -1
+            })
+        );
+
+        let error = SparseVector::from_signed_indices(&[i64::from(DimId::MAX) + 1], vec![1.0]);
+        assert!(matches!(
+            error,
+            Err(SparseError::InvalidIndex { position: 0, .. })
+        ));
+
+        let error = SparseVector::from_signed_indices(&[1i32, 2], vec![1.0]);
+        assert_eq!(
+            error,
+            Err(SparseError::LengthMismatch {
+                indices: 2,
+                weights: 1
+            })
+        );
+    }
 }
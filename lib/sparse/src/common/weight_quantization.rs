@@ -0,0 +1,114 @@
+//! Per-vector int8 quantization of sparse vector weights
+//!
+//! Each weight is stored as `i8` together with a single `f32` scale per vector, which takes
+//! 4 times less space than `f32` weights. Indices are kept as is.
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::sparse_vector::SparseVector;
+use crate::common::types::{DimId, DimWeight};
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct QuantizedSparseVector {
+    pub indices: Vec<DimId>,
+    pub weights: Vec<i8>,
+    /// Weight value of a single quantization step
+    pub scale: DimWeight,
+}
+
+impl QuantizedSparseVector {
+    /// Quantize weights symmetrically, see [`quantize_weights`]
+    pub fn quantize(vector: &SparseVector) -> Self {
+        let (weights, scale) = quantize_weights(&vector.weights);
+        QuantizedSparseVector {
+            indices: vector.indices.clone(),
+            weights,
+            scale,
+        }
+    }
+
+    /// Decode weights back to `f32`
+    pub fn decode(&self) -> SparseVector {
+        SparseVector::new(
+            self.indices.clone(),
+            decode_weights(&self.weights, self.scale),
+        )
+    }
+
+    /// Upper bound of the absolute error of a single decoded weight
+    pub fn max_error(&self) -> DimWeight {
+        self.scale / 2.0
+    }
+}
+
+/// Quantize weights symmetrically, mapping the largest absolute weight to `i8::MAX`
+///
+/// Returns quantized weights and their scale.
+pub fn quantize_weights(weights: &[DimWeight]) -> (Vec<i8>, DimWeight) {
+    let max_abs = weights
+        .iter()
+        .fold(0.0 as DimWeight, |max, weight| max.max(weight.abs()));
+    let scale = if max_abs > 0.0 {
+        max_abs / DimWeight::from(i8::MAX)
+    } else {
+        1.0
+    };
+    let quantized = weights
+        .iter()
+        .map(|&weight| {
+            (weight / scale)
+                .round()
+                .clamp(-DimWeight::from(i8::MAX), DimWeight::from(i8::MAX)) as i8
+        })
+        .collect();
+    (quantized, scale)
+}
+
+/// Decode weights, produced by [`quantize_weights`]
+pub fn decode_weights(weights: &[i8], scale: DimWeight) -> Vec<DimWeight> {
+    weights
+        .iter()
+        .map(|&weight| DimWeight::from(weight) * scale)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_quantization_error_bound() {
+        let vector = SparseVector::new(
+            vec![1, 4, 9, 16, 25],
+            vec![0.013, -2.5, 0.77, 1.25, -0.0001],
+        );
+        let quantized = QuantizedSparseVector::quantize(&vector);
+        let decoded = quantized.decode();
+
+        assert_eq!(decoded.indices, vector.indices);
+        assert_eq!(quantized.weights[1], -i8::MAX);
+        for (original, decoded) in vector.weights.iter().zip(&decoded.weights) {
+            assert!((original - decoded).abs() <= quantized.max_error() + f32::EPSILON);
+        }
+
+        let empty = SparseVector::new(vec![], vec![]);
+        assert_eq!(QuantizedSparseVector::quantize(&empty).decode(), empty);
+
+        let zeros = SparseVector::new(vec![3, 5], vec![0.0, 0.0]);
+        assert_eq!(QuantizedSparseVector::quantize(&zeros).decode(), zeros);
+    }
+
+    #[test]
+    fn weight_quantization_scoring() {
+        let query = SparseVector::new(vec![1, 2, 3, 5, 8], vec![0.3, 1.2, -0.4, 0.9, 0.05]);
+        let stored = [
+            SparseVector::new(vec![1, 3, 8], vec![0.5, 0.25, 1.5]),
+            SparseVector::new(vec![2, 5, 13], vec![-0.7, 0.33, 2.1]),
+            SparseVector::new(vec![1, 2, 3, 5, 8], vec![0.3, 1.2, -0.4, 0.9, 0.05]),
+        ];
+        for vector in &stored {
+            let decoded = QuantizedSparseVector::quantize(vector).decode();
+            assert!((query.cosine(vector) - query.cosine(&decoded)).abs() < 0.01);
+        }
+    }
+}
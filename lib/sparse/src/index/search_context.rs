@@ -15,6 +15,7 @@ pub struct SearchContext<'a> {
     query: SparseVector,
     top: usize,
     result_queue: FixedLengthPriorityQueue<ScoredPointOffset>, // keep the largest elements and peek smallest
+    candidates_scored: usize, // number of candidates fully scored by the WAND search
 }
 
 impl<'a> SearchContext<'a> {
@@ -40,6 +41,7 @@ impl<'a> SearchContext<'a> {
             query,
             top,
             result_queue,
+            candidates_scored: 0,
         }
     }
 
@@ -147,6 +149,98 @@ impl<'a> SearchContext<'a> {
         queue.into_vec()
     }
 
+    /// Search with WAND (Weak AND) early termination
+    ///
+    /// The upper bound of the score contribution of each posting list is the query weight times
+    /// the max weight left in the list. Posting lists are ordered by their current record id and
+    /// the pivot is the first record at which the accumulated upper bounds exceed the current top-k
+    /// threshold. Records before the pivot can't enter the top-k, they are skipped without scoring.
+    ///
+    /// Assumes non-negative query weights, same as `prune_longest_posting_list`.
+    pub fn search_wand(&mut self) -> Vec<ScoredPointOffset> {
+        loop {
+            // drop exhausted posting lists and order the remaining ones by current record id
+            self.postings_iterators
+                .retain(|posting_iterator| posting_iterator.posting_list_iterator.peek().is_some());
+            self.postings_iterators.sort_by_key(|posting_iterator| {
+                posting_iterator
+                    .posting_list_iterator
+                    .peek()
+                    .map(|element| element.record_id)
+            });
+
+            // candidates must beat the smallest score of a full result queue
+            let threshold = if self.result_queue.len() == self.top {
+                self.result_queue
+                    .top()
+                    .map_or(f32::NEG_INFINITY, |element| element.score)
+            } else {
+                f32::NEG_INFINITY
+            };
+
+            // find the pivot record id
+            let mut upper_bound = 0.0;
+            let mut pivot = None;
+            for posting_iterator in &self.postings_iterators {
+                let element = posting_iterator.posting_list_iterator.peek().unwrap();
+                let max_weight_from_list = element.weight.max(element.max_next_weight);
+                upper_bound +=
+                    max_weight_from_list * self.query.weights[posting_iterator.query_weight_offset];
+                if upper_bound > threshold {
+                    pivot = Some(element.record_id);
+                    break;
+                }
+            }
+            let Some(pivot_id) = pivot else {
+                // no remaining record can enter the top-k
+                break;
+            };
+
+            let first_id = self.postings_iterators[0]
+                .posting_list_iterator
+                .peek()
+                .unwrap()
+                .record_id;
+
+            if first_id == pivot_id {
+                // all posting lists up to the pivot are aligned, fully score the candidate
+                let mut score = 0.0;
+                for posting_iterator in self.postings_iterators.iter_mut() {
+                    match posting_iterator.posting_list_iterator.peek().copied() {
+                        Some(element) if element.record_id == pivot_id => {
+                            score += element.weight
+                                * self.query.weights[posting_iterator.query_weight_offset];
+                            posting_iterator.posting_list_iterator.next();
+                        }
+                        _ => break,
+                    }
+                }
+                self.candidates_scored += 1;
+                self.result_queue.push(ScoredPointOffset {
+                    score,
+                    idx: pivot_id,
+                });
+            } else {
+                // records before the pivot can't beat the threshold, skip them
+                for posting_iterator in self.postings_iterators.iter_mut() {
+                    let record_id = posting_iterator
+                        .posting_list_iterator
+                        .peek()
+                        .map(|element| element.record_id);
+                    match record_id {
+                        Some(record_id) if record_id < pivot_id => {
+                            posting_iterator.posting_list_iterator.skip_to(pivot_id);
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+        // posting iterators exhausted or pruned, return result queue
+        let queue = std::mem::take(&mut self.result_queue);
+        queue.into_vec()
+    }
+
     /// Prune posting lists that cannot possibly contribute to the top results
     /// Assumes longest posting list is at the head of the posting list iterators
     /// Returns true if the longest posting list was pruned
@@ -393,6 +487,66 @@ mod tests {
         _search_with_hot_key_test(&inverted_index);
     }
 
+    fn _search_wand_test(inverted_index: &InvertedIndex) {
+        let query = SparseVector {
+            indices: vec![1, 2, 3],
+            weights: vec![1.0, 1.0, 1.0],
+        };
+
+        // exhaustive top-k: score every candidate
+        let mut exhaustive_context = SearchContext::new(query.clone(), 3, inverted_index);
+        let mut exhaustive = Vec::new();
+        while let Some(candidate) = exhaustive_context.advance() {
+            exhaustive.push(candidate);
+        }
+        let candidates_count = exhaustive.len();
+        exhaustive.sort_by(|a, b| b.cmp(a));
+        exhaustive.truncate(3);
+
+        let mut search_context = SearchContext::new(query, 3, inverted_index);
+        assert_eq!(search_context.search_wand(), exhaustive);
+
+        assert_eq!(candidates_count, 9);
+        assert_eq!(search_context.candidates_scored, 3);
+    }
+
+    #[test]
+    fn search_wand_test() {
+        let inverted_index_ram = InvertedIndexBuilder::new()
+            .add(
+                1,
+                PostingList::from(vec![
+                    (1, 10.0),
+                    (2, 20.0),
+                    (3, 30.0),
+                    (4, 1.0),
+                    (5, 2.0),
+                    (6, 3.0),
+                    (7, 4.0),
+                    (8, 5.0),
+                    (9, 6.0),
+                ]),
+            )
+            .add(2, PostingList::from(vec![(1, 10.0), (2, 20.0), (3, 30.0)]))
+            .add(3, PostingList::from(vec![(1, 10.0), (2, 20.0), (3, 30.0)]))
+            .build();
+
+        let inverted_index = InvertedIndex::Ram(inverted_index_ram.clone());
+
+        // test with ram index
+        _search_wand_test(&inverted_index);
+
+        // test with mmap index
+        let tmp_dir_path = tempfile::Builder::new()
+            .prefix("test_index_dir")
+            .tempdir()
+            .unwrap();
+        let inverted_index_mmap =
+            InvertedIndexMmap::convert_and_save(&inverted_index_ram, &tmp_dir_path).unwrap();
+        let inverted_index = InvertedIndex::Mmap(inverted_index_mmap);
+        _search_wand_test(&inverted_index);
+    }
+
     fn _prune_test(inverted_index: &InvertedIndex) {
         let mut search_context = SearchContext::new(
             SparseVector {
@@ -92,6 +92,7 @@ fn test_alias_operation() {
                             hnsw_config: None,
                             quantization_config: None,
                             on_disk: None,
+                            datatype: None,
                         }
                         .into(),
                         hnsw_config: None,
@@ -1222,6 +1222,7 @@ mod tests {
                                 hnsw_config: None,
                                 quantization_config: None,
                                 on_disk: None,
+                                datatype: None,
                             }
                             .into(),
                             hnsw_config: None,
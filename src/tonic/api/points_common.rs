@@ -710,8 +710,8 @@ pub async fn search(
     let response = SearchResponse {
         result: scored_points
             .into_iter()
-            .map(|point| point.into())
-            .collect(),
+            .map(|point| point.try_into())
+            .collect::<Result<_, _>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -750,10 +750,15 @@ pub async fn search_batch(
     let response = SearchBatchResponse {
         result: scored_points
             .into_iter()
-            .map(|points| BatchResult {
-                result: points.into_iter().map(|p| p.into()).collect(),
+            .map(|points| {
+                Ok(BatchResult {
+                    result: points
+                        .into_iter()
+                        .map(|p| p.try_into())
+                        .collect::<Result<_, _>>()?,
+                })
             })
-            .collect(),
+            .collect::<Result<_, Status>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -792,10 +797,15 @@ pub async fn core_search_batch(
     let response = SearchBatchResponse {
         result: scored_points
             .into_iter()
-            .map(|points| BatchResult {
-                result: points.into_iter().map(|p| p.into()).collect(),
+            .map(|points| {
+                Ok(BatchResult {
+                    result: points
+                        .into_iter()
+                        .map(|p| p.try_into())
+                        .collect::<Result<_, _>>()?,
+                })
             })
-            .collect(),
+            .collect::<Result<_, Status>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -829,7 +839,7 @@ pub async fn search_groups(
     .map_err(error_to_status)?;
 
     let response = SearchGroupsResponse {
-        result: Some(groups_result.into()),
+        result: Some(groups_result.try_into()?),
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -909,8 +919,8 @@ pub async fn recommend(
     let response = RecommendResponse {
         result: recommended_points
             .into_iter()
-            .map(|point| point.into())
-            .collect(),
+            .map(|point| point.try_into())
+            .collect::<Result<_, _>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -942,10 +952,15 @@ pub async fn recommend_batch(
     let response = RecommendBatchResponse {
         result: scored_points
             .into_iter()
-            .map(|points| BatchResult {
-                result: points.into_iter().map(|p| p.into()).collect(),
+            .map(|points| {
+                Ok(BatchResult {
+                    result: points
+                        .into_iter()
+                        .map(|p| p.try_into())
+                        .collect::<Result<_, _>>()?,
+                })
             })
-            .collect(),
+            .collect::<Result<_, Status>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -977,7 +992,7 @@ pub async fn recommend_groups(
     .map_err(error_to_status)?;
 
     let response = RecommendGroupsResponse {
-        result: Some(groups_result.into()),
+        result: Some(groups_result.try_into()?),
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -1026,8 +1041,8 @@ pub async fn scroll(
         result: scrolled_points
             .points
             .into_iter()
-            .map(|point| point.into())
-            .collect(),
+            .map(|point| point.try_into())
+            .collect::<Result<_, _>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 
@@ -1102,7 +1117,10 @@ pub async fn get(
     .map_err(error_to_status)?;
 
     let response = GetResponse {
-        result: records.into_iter().map(|point| point.into()).collect(),
+        result: records
+            .into_iter()
+            .map(|point| point.try_into())
+            .collect::<Result<_, _>>()?,
         time: timing.elapsed().as_secs_f64(),
     };
 